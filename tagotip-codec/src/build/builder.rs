@@ -0,0 +1,283 @@
+use crate::consts::{MAX_META_PAIRS, MAX_VARIABLES};
+use crate::error::BuildError;
+use crate::inline_vec::InlineVec;
+use crate::types::{
+    AuthToken, MAX_TOTAL_META, MetaPair, MetaRange, MetadataBlock, Method, PushBody, Serial,
+    StructuredBody, UplinkFrame, Value, Variable, is_escaped_value,
+};
+use crate::validate;
+
+/// Fluent builder for a structured PUSH [`UplinkFrame`].
+///
+/// Variable modifiers (`unit`, `timestamp`, `group`, `meta`) apply to the most
+/// recently added variable, and the builder keeps the shared `meta_pool` and
+/// its ranges consistent. Setters never fail; the first invalid input is
+/// remembered and reported by [`build`](Self::build).
+///
+/// ```
+/// use tagotip_codec::build::PushFrameBuilder;
+///
+/// let frame = PushFrameBuilder::new("4deedd7bab8817ec")
+///     .serial("sensor-01")
+///     .seq(7)
+///     .number("temp", "21.5")
+///     .unit("C")
+///     .meta("source", "dht22")
+///     .boolean("active", true)
+///     .build()
+///     .unwrap();
+/// assert_eq!(frame.seq, Some(7));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PushFrameBuilder<'a> {
+    seq: Option<u32>,
+    auth: &'a str,
+    serial: &'a str,
+    group: Option<&'a str>,
+    timestamp: Option<&'a str>,
+    body_meta: MetadataBlock<'a>,
+    variables: InlineVec<Variable<'a>, MAX_VARIABLES>,
+    meta_pool: InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
+    error: Option<BuildError>,
+}
+
+impl<'a> PushFrameBuilder<'a> {
+    /// Start a PUSH frame authenticated with `auth`.
+    #[must_use]
+    pub fn new(auth: &'a str) -> Self {
+        Self {
+            seq: None,
+            auth,
+            serial: "",
+            group: None,
+            timestamp: None,
+            body_meta: MetadataBlock::new(),
+            variables: InlineVec::new(),
+            meta_pool: InlineVec::new(),
+            error: None,
+        }
+    }
+
+    /// Start a PUSH frame from an already validated auth hash and serial.
+    #[must_use]
+    pub fn for_device(auth: AuthToken<'a>, serial: Serial<'a>) -> Self {
+        let mut builder = Self::new(auth.as_str());
+        builder.serial = serial.as_str();
+        builder
+    }
+
+    /// Set the device serial.
+    pub fn serial(&mut self, serial: &'a str) -> &mut Self {
+        self.serial = serial;
+        self
+    }
+
+    /// Set the sequence counter.
+    pub fn seq(&mut self, seq: u32) -> &mut Self {
+        self.seq = Some(seq);
+        self
+    }
+
+    /// Set the body-level group (`^group`).
+    pub fn body_group(&mut self, group: &'a str) -> &mut Self {
+        self.apply(|b| {
+            validate::validate_group(group, 0).map_err(|_| BuildError::invalid_input())?;
+            b.group = Some(group);
+            Ok(())
+        })
+    }
+
+    /// Set the body-level timestamp (`@ts`).
+    pub fn body_timestamp(&mut self, timestamp: &'a str) -> &mut Self {
+        self.apply(|b| {
+            check_timestamp(timestamp)?;
+            b.timestamp = Some(timestamp);
+            Ok(())
+        })
+    }
+
+    /// Add a body-level metadata pair.
+    pub fn body_meta(&mut self, key: &'a str, value: &'a str) -> &mut Self {
+        self.apply(|b| {
+            check_meta_pair(key, value)?;
+            b.body_meta
+                .push(MetaPair { key, value })
+                .map_err(|_| BuildError::invalid_input())
+        })
+    }
+
+    /// Add a number variable (`name:=value`).
+    pub fn number(&mut self, name: &'a str, value: &'a str) -> &mut Self {
+        self.push_variable(Variable::new_number(name, value, None))
+    }
+
+    /// Add a string variable (`name=value`). `value` must be in escaped wire form.
+    pub fn string(&mut self, name: &'a str, value: &'a str) -> &mut Self {
+        self.push_variable(Variable::new_string(name, value, None))
+    }
+
+    /// Add a boolean variable (`name?=true|false`).
+    pub fn boolean(&mut self, name: &'a str, value: bool) -> &mut Self {
+        self.push_variable(Variable::new_boolean(name, value))
+    }
+
+    /// Add a location variable (`name@=lat,lng[,alt]`).
+    pub fn location(
+        &mut self,
+        name: &'a str,
+        lat: &'a str,
+        lng: &'a str,
+        alt: Option<&'a str>,
+    ) -> &mut Self {
+        self.push_variable(Variable::new_location(name, lat, lng, alt))
+    }
+
+    /// Set the unit of the last variable (`#unit`).
+    pub fn unit(&mut self, unit: &'a str) -> &mut Self {
+        self.apply(|b| {
+            validate::validate_unit(unit, 0).map_err(|_| BuildError::invalid_input())?;
+            let var = b.last_variable()?;
+            if matches!(var.value, Value::Location { .. }) {
+                return Err(BuildError::invalid_input());
+            }
+            var.unit = Some(unit);
+            Ok(())
+        })
+    }
+
+    /// Set the timestamp of the last variable (`@ts`).
+    pub fn timestamp(&mut self, timestamp: &'a str) -> &mut Self {
+        self.apply(|b| {
+            check_timestamp(timestamp)?;
+            b.last_variable()?.timestamp = Some(timestamp);
+            Ok(())
+        })
+    }
+
+    /// Set the group of the last variable (`^group`).
+    pub fn group(&mut self, group: &'a str) -> &mut Self {
+        self.apply(|b| {
+            validate::validate_group(group, 0).map_err(|_| BuildError::invalid_input())?;
+            b.last_variable()?.group = Some(group);
+            Ok(())
+        })
+    }
+
+    /// Add a metadata pair to the last variable.
+    pub fn meta(&mut self, key: &'a str, value: &'a str) -> &mut Self {
+        self.apply(|b| {
+            check_meta_pair(key, value)?;
+            // Only the last variable can gain pairs, so its range always ends
+            // at the end of the pool and extending it keeps the range contiguous.
+            let start = b.meta_pool.len();
+            let var = b
+                .variables
+                .as_mut_slice()
+                .last_mut()
+                .ok_or_else(BuildError::invalid_input)?;
+            let range = var.meta.get_or_insert(MetaRange {
+                start: u16::try_from(start).map_err(|_| BuildError::invalid_input())?,
+                len: 0,
+            });
+            if usize::from(range.len) >= MAX_META_PAIRS {
+                return Err(BuildError::invalid_input());
+            }
+            b.meta_pool
+                .push(MetaPair { key, value })
+                .map_err(|_| BuildError::invalid_input())?;
+            range.len += 1;
+            Ok(())
+        })
+    }
+
+    /// Assemble the frame, or return the first error recorded by a setter.
+    ///
+    /// Fails with `InvalidInput` if the auth hash or serial is invalid or no
+    /// variable was added.
+    pub fn build(&self) -> Result<UplinkFrame<'a>, BuildError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        crate::parse::validate_auth(self.auth).map_err(|_| BuildError::invalid_input())?;
+        validate::validate_serial(self.serial, 0).map_err(|_| BuildError::invalid_input())?;
+        if self.variables.is_empty() {
+            return Err(BuildError::invalid_input());
+        }
+
+        // Lay the pool out like the parser does: body-level pairs first, then
+        // each variable's pairs, shifting the variable ranges to match.
+        let offset =
+            u16::try_from(self.body_meta.len()).map_err(|_| BuildError::invalid_input())?;
+        let mut meta_pool = InlineVec::new();
+        meta_pool
+            .try_extend_from_slice(&self.body_meta)
+            .and_then(|()| meta_pool.try_extend_from_slice(&self.meta_pool))
+            .map_err(|_| BuildError::invalid_input())?;
+        let body_meta = (offset > 0).then_some(MetaRange {
+            start: 0,
+            len: offset,
+        });
+        let mut variables = self.variables.clone();
+        for var in variables.as_mut_slice() {
+            if let Some(range) = var.meta.as_mut() {
+                range.start += offset;
+            }
+        }
+
+        Ok(UplinkFrame {
+            method: Method::Push,
+            seq: self.seq,
+            auth: self.auth,
+            serial: self.serial,
+            push_body: Some(PushBody::Structured(StructuredBody {
+                group: self.group,
+                timestamp: self.timestamp,
+                body_meta,
+                variables,
+                meta_pool,
+                unknown_modifiers: InlineVec::new(),
+            })),
+            pull_body: None,
+        })
+    }
+
+    fn push_variable(&mut self, var: Result<Variable<'a>, BuildError>) -> &mut Self {
+        self.apply(|b| {
+            b.variables
+                .push(var?)
+                .map_err(|_| BuildError::invalid_input())
+        })
+    }
+
+    fn last_variable(&mut self) -> Result<&mut Variable<'a>, BuildError> {
+        self.variables
+            .as_mut_slice()
+            .last_mut()
+            .ok_or_else(BuildError::invalid_input)
+    }
+
+    /// Run `f` unless an earlier setter already failed, recording its error.
+    fn apply(&mut self, f: impl FnOnce(&mut Self) -> Result<(), BuildError>) -> &mut Self {
+        if self.error.is_none() {
+            if let Err(err) = f(self) {
+                self.error = Some(err);
+            }
+        }
+        self
+    }
+}
+
+fn check_timestamp(ts: &str) -> Result<(), BuildError> {
+    if ts.is_empty() || !ts.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(BuildError::invalid_input());
+    }
+    Ok(())
+}
+
+fn check_meta_pair(key: &str, value: &str) -> Result<(), BuildError> {
+    validate::validate_meta_key(key, 0).map_err(|_| BuildError::invalid_input())?;
+    if !is_escaped_value(value) {
+        return Err(BuildError::invalid_input());
+    }
+    Ok(())
+}
@@ -1,20 +1,27 @@
+use crate::crc;
 use crate::error::BuildError;
 use crate::fmt;
+
+use super::sink::{ByteSink, SliceSink};
 use crate::types::{
-    AckDetail, AckFrame, AckStatus, HeadlessFrame, MetaPair, MetaRange, Method, Operator,
-    PassthroughEncoding, PullBody, PushBody, UplinkFrame, Value, Variable,
+    AckDetail, AckFrame, AckStatus, ErrorCode, HeadlessFrame, MetaPair, Method, ModifierSlot,
+    Operator, PassthroughEncoding, PullBody, PushBody, StructuredBody, UplinkFrame, Value,
+    Variable,
 };
 
-/// A cursor-based writer into a caller-provided byte buffer.
-pub struct FrameWriter<'buf> {
-    buf: &'buf mut [u8],
+/// A writer that tracks how many bytes it has sent to a [`ByteSink`].
+///
+/// With a `&mut [u8]` sink this is the allocation-free cursor used by the
+/// `build_*` functions; the `build_*_to` variants accept any sink.
+pub struct FrameWriter<S: ByteSink> {
+    sink: S,
     pos: usize,
 }
 
-impl<'buf> FrameWriter<'buf> {
-    /// Create a new writer over the given buffer.
-    pub fn new(buf: &'buf mut [u8]) -> Self {
-        Self { buf, pos: 0 }
+impl<S: ByteSink> FrameWriter<S> {
+    /// Create a new writer over the given sink.
+    pub fn new(sink: S) -> Self {
+        Self { sink, pos: 0 }
     }
 
     /// Returns the number of bytes written so far.
@@ -23,22 +30,21 @@ impl<'buf> FrameWriter<'buf> {
         self.pos
     }
 
-    /// Write raw bytes to the buffer.
+    /// Consume the writer and return the sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    /// Write raw bytes to the sink.
     fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError> {
-        if self.pos + data.len() > self.buf.len() {
-            return Err(BuildError::buffer_too_small());
-        }
-        self.buf[self.pos..self.pos + data.len()].copy_from_slice(data);
+        self.sink.write_bytes(data)?;
         self.pos += data.len();
         Ok(())
     }
 
     /// Write a single byte.
     fn write_byte(&mut self, b: u8) -> Result<(), BuildError> {
-        if self.pos >= self.buf.len() {
-            return Err(BuildError::buffer_too_small());
-        }
-        self.buf[self.pos] = b;
+        self.sink.write_byte(b)?;
         self.pos += 1;
         Ok(())
     }
@@ -55,10 +61,33 @@ impl<'buf> FrameWriter<'buf> {
 
     /// Write a u32 value as decimal.
     fn write_u32(&mut self, value: u32) -> Result<(), BuildError> {
-        let n = fmt::format_u32(value, &mut self.buf[self.pos..])
-            .ok_or_else(BuildError::buffer_too_small)?;
-        self.pos += n;
-        Ok(())
+        let mut digits = [0u8; 10];
+        let n = fmt::format_u32(value, &mut digits).ok_or_else(BuildError::buffer_too_small)?;
+        self.write_bytes(&digits[..n])
+    }
+
+    /// Write an ACK detail field (without the leading pipe).
+    fn write_ack_detail(
+        &mut self,
+        detail: &AckDetail<'_>,
+        error_text: AckErrorText,
+    ) -> Result<(), BuildError> {
+        match detail {
+            AckDetail::Count(count) => self.write_u32(*count),
+            AckDetail::Variables(vars) => self.write_str(vars),
+            AckDetail::Command(cmd) => self.write_str(cmd),
+            AckDetail::Error { code, text } => self.write_str(error_text.pick(*code, text)),
+            AckDetail::Errors(errors) => {
+                for (i, (code, text)) in errors.iter().enumerate() {
+                    if i > 0 {
+                        self.write_byte(b';')?;
+                    }
+                    self.write_str(error_text.pick(code, text))?;
+                }
+                Ok(())
+            }
+            AckDetail::Raw(raw) => self.write_str(raw),
+        }
     }
 
     /// Write a variable's operator and value.
@@ -73,6 +102,10 @@ impl<'buf> FrameWriter<'buf> {
             Operator::String => {
                 self.write_byte(b'=')?;
                 if let Value::String(s) = value {
+                    #[cfg(feature = "quoted-strings")]
+                    if needs_quotes(s) {
+                        return self.write_quoted(s);
+                    }
                     self.write_str(s)?;
                 }
             }
@@ -98,6 +131,32 @@ impl<'buf> FrameWriter<'buf> {
         Ok(())
     }
 
+    /// Write a string value as a quoted literal, escaping bare `"` as `\"`.
+    #[cfg(feature = "quoted-strings")]
+    fn write_quoted(&mut self, s: &str) -> Result<(), BuildError> {
+        let bytes = s.as_bytes();
+        // A trailing `\` would escape the closing quote, and frames are
+        // newline-delimited; neither can be carried inside quotes.
+        if bytes.last() == Some(&b'\\') || bytes.contains(&b'\n') {
+            return Err(BuildError::invalid_input());
+        }
+        self.write_byte(b'"')?;
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'"') {
+                self.write_bytes(&bytes[i..i + 2])?;
+                i += 2;
+                continue;
+            }
+            if bytes[i] == b'"' {
+                self.write_byte(b'\\')?;
+            }
+            self.write_byte(bytes[i])?;
+            i += 1;
+        }
+        self.write_byte(b'"')
+    }
+
     /// Write metadata pairs from a pool slice.
     fn write_metadata_pairs(&mut self, pairs: &[MetaPair<'_>]) -> Result<(), BuildError> {
         self.write_byte(b'{')?;
@@ -153,32 +212,135 @@ impl<'buf> FrameWriter<'buf> {
     /// Write body-level modifiers.
     fn write_body_modifiers(
         &mut self,
-        group: Option<&str>,
-        timestamp: Option<&str>,
-        body_meta: Option<MetaRange>,
+        body: &StructuredBody<'_>,
         meta_pool: &[MetaPair<'_>],
     ) -> Result<(), BuildError> {
-        if let Some(ts) = timestamp {
+        // Spec order `@ts^group{meta}`, the same relative order as variable
+        // suffixes (`#unit@ts^group{meta}`). Unknown modifiers go back into
+        // the slot they were parsed from.
+        self.write_unknown_modifiers(body, ModifierSlot::Leading)?;
+        if let Some(ts) = body.timestamp {
             self.write_byte(b'@')?;
             self.write_str(ts)?;
         }
-        if let Some(g) = group {
+        self.write_unknown_modifiers(body, ModifierSlot::AfterTimestamp)?;
+        if let Some(g) = body.group {
             self.write_byte(b'^')?;
             self.write_str(g)?;
         }
-        if let Some(range) = body_meta {
+        self.write_unknown_modifiers(body, ModifierSlot::AfterGroup)?;
+        if let Some(range) = body.body_meta {
             let start = range.start as usize;
             let end = start + range.len as usize;
             self.write_metadata_pairs(&meta_pool[start..end])?;
         }
+        self.write_unknown_modifiers(body, ModifierSlot::AfterMeta)
+    }
+
+    fn write_unknown_modifiers(
+        &mut self,
+        body: &StructuredBody<'_>,
+        slot: ModifierSlot,
+    ) -> Result<(), BuildError> {
+        for m in body.unknown_modifiers.iter().filter(|m| m.slot == slot) {
+            self.write_str(m.raw)?;
+        }
         Ok(())
     }
 }
 
+impl<'a> FrameWriter<SliceSink<'a>> {
+    /// Create a writer over `buf` that can be [`reset`](Self::reset) and reused.
+    pub fn from_slice(buf: &'a mut [u8]) -> Self {
+        Self::new(SliceSink::new(buf))
+    }
+
+    /// Discard everything written so far and start again at the front of the buffer.
+    pub fn reset(&mut self) {
+        self.sink.clear();
+        self.pos = 0;
+    }
+
+    /// The bytes written since creation or the last [`reset`](Self::reset).
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.sink.as_bytes()
+    }
+}
+
+#[cfg(feature = "std")]
+impl FrameWriter<std::vec::Vec<u8>> {
+    /// Discard everything written so far, keeping the vector's allocation.
+    pub fn reset(&mut self) {
+        self.sink.clear();
+        self.pos = 0;
+    }
+}
+
+/// Build an uplink frame followed by a `*XXXX` CRC-16/CCITT suffix.
+///
+/// The checksum covers every byte of the frame before the `*`. This is an
+/// optional integrity check for plaintext transports; frames built this way
+/// must be read back with `parse_uplink_with_crc`.
+pub fn build_uplink_with_crc(frame: &UplinkFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildError> {
+    let n = build_uplink(frame, buf)?;
+    let suffix = crc::format_suffix(crc::crc16_ccitt(&buf[..n]));
+    let end = n + suffix.len();
+    if end > buf.len() {
+        return Err(BuildError::buffer_too_small());
+    }
+    buf[n..end].copy_from_slice(&suffix);
+    Ok(end)
+}
+
+/// Build an uplink frame followed by a single `\n` terminator.
+///
+/// For newline-framed transports (TCP). Returns the number of bytes written,
+/// including the terminator.
+pub fn build_uplink_line(frame: &UplinkFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildError> {
+    let n = build_uplink(frame, &mut *buf)?;
+    let slot = buf.get_mut(n).ok_or_else(BuildError::buffer_too_small)?;
+    *slot = b'\n';
+    Ok(n + 1)
+}
+
+/// Build a complete uplink frame, then check that it parses back.
+///
+/// `build_uplink` writes fields as given, so e.g. an uppercase variable name
+/// or `Value::Number("abc")` produces a frame `parse_uplink` rejects. This
+/// variant re-parses the output with default options and, if that fails,
+/// returns `InvalidInput` with the parser's error in `cause` (its position
+/// locates the offending field in `buf`). `buf` holds the rejected output in
+/// that case.
+pub fn build_uplink_checked(frame: &UplinkFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildError> {
+    let n = build_uplink(frame, &mut *buf)?;
+    let out = core::str::from_utf8(&buf[..n]).map_err(|_| BuildError::invalid_input())?;
+    crate::parse::parse_uplink(out).map_err(BuildError::rejected_by_parser)?;
+    Ok(n)
+}
+
 /// Build a complete uplink frame into the buffer.
 /// Returns the number of bytes written.
 pub fn build_uplink(frame: &UplinkFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildError> {
-    let mut w = FrameWriter::new(buf);
+    build_uplink_to(frame, buf)
+}
+
+/// Build a complete uplink frame into any [`ByteSink`].
+/// Returns the number of bytes written.
+pub fn build_uplink_to<S: ByteSink>(frame: &UplinkFrame<'_>, sink: S) -> Result<usize, BuildError> {
+    let mut w = FrameWriter::new(sink);
+    build_uplink_into_writer(frame, &mut w)
+}
+
+/// Append a complete uplink frame to an existing writer.
+///
+/// Returns the number of bytes this frame added. Pair with
+/// [`FrameWriter::reset`] to reuse one writer and buffer across frames.
+pub fn build_uplink_into_writer<S: ByteSink>(
+    frame: &UplinkFrame<'_>,
+    w: &mut FrameWriter<S>,
+) -> Result<usize, BuildError> {
+    let start = w.written();
 
     // METHOD
     let method_str = match frame.method {
@@ -208,25 +370,69 @@ pub fn build_uplink(frame: &UplinkFrame<'_>, buf: &mut [u8]) -> Result<usize, Bu
         Method::Push => {
             if let Some(ref push_body) = frame.push_body {
                 w.write_pipe()?;
-                write_push_body(&mut w, push_body)?;
+                write_push_body(w, push_body)?;
             }
         }
         Method::Pull => {
             if let Some(ref pull_body) = frame.pull_body {
                 w.write_pipe()?;
-                write_pull_body(&mut w, pull_body)?;
+                write_pull_body(w, pull_body)?;
             }
         }
         Method::Ping => {}
     }
 
-    Ok(w.written())
+    Ok(w.written() - start)
+}
+
+/// Which text an `ERR` detail is built with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AckErrorText {
+    /// The detail's own `text`, even if it differs from the code.
+    #[default]
+    AsGiven,
+    /// The code's canonical text ([`ErrorCode::as_str`]), ignoring `text`.
+    /// `ErrorCode::Unknown` has none, so its `text` is kept.
+    Canonical,
+}
+
+impl AckErrorText {
+    fn pick(self, code: ErrorCode, text: &str) -> &str {
+        match self {
+            AckErrorText::AsGiven => text,
+            AckErrorText::Canonical => code.as_str().unwrap_or(text),
+        }
+    }
 }
 
 /// Build an ACK frame into the buffer.
 /// Returns the number of bytes written.
 pub fn build_ack(frame: &AckFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildError> {
-    let mut w = FrameWriter::new(buf);
+    build_ack_to(frame, buf)
+}
+
+/// Build an ACK frame into the buffer, choosing how `ERR` text is emitted.
+/// Returns the number of bytes written.
+pub fn build_ack_with(
+    frame: &AckFrame<'_>,
+    buf: &mut [u8],
+    error_text: AckErrorText,
+) -> Result<usize, BuildError> {
+    write_ack(frame, buf, error_text)
+}
+
+/// Build an ACK frame into any [`ByteSink`].
+/// Returns the number of bytes written.
+pub fn build_ack_to<S: ByteSink>(frame: &AckFrame<'_>, sink: S) -> Result<usize, BuildError> {
+    write_ack(frame, sink, AckErrorText::AsGiven)
+}
+
+fn write_ack<S: ByteSink>(
+    frame: &AckFrame<'_>,
+    sink: S,
+    error_text: AckErrorText,
+) -> Result<usize, BuildError> {
+    let mut w = FrameWriter::new(sink);
 
     w.write_str("ACK")?;
 
@@ -250,13 +456,7 @@ pub fn build_ack(frame: &AckFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildErr
     // |DETAIL (optional)
     if let Some(ref detail) = frame.detail {
         w.write_pipe()?;
-        match detail {
-            AckDetail::Count(count) => w.write_u32(*count)?,
-            AckDetail::Variables(vars) => w.write_str(vars)?,
-            AckDetail::Command(cmd) => w.write_str(cmd)?,
-            AckDetail::Error { text, .. } => w.write_str(text)?,
-            AckDetail::Raw(raw) => w.write_str(raw)?,
-        }
+        w.write_ack_detail(detail, error_text)?;
     }
 
     Ok(w.written())
@@ -265,7 +465,13 @@ pub fn build_ack(frame: &AckFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildErr
 /// Build an ACK inner frame for TagoTiP/S: `STATUS[|DETAIL]` (no `ACK|` prefix, no seq).
 /// Returns the number of bytes written.
 pub fn build_ack_inner(frame: &AckFrame<'_>, buf: &mut [u8]) -> Result<usize, BuildError> {
-    let mut w = FrameWriter::new(buf);
+    build_ack_inner_to(frame, buf)
+}
+
+/// Build an ACK inner frame into any [`ByteSink`].
+/// Returns the number of bytes written.
+pub fn build_ack_inner_to<S: ByteSink>(frame: &AckFrame<'_>, sink: S) -> Result<usize, BuildError> {
+    let mut w = FrameWriter::new(sink);
 
     let status_str = match frame.status {
         AckStatus::Ok => "OK",
@@ -277,13 +483,7 @@ pub fn build_ack_inner(frame: &AckFrame<'_>, buf: &mut [u8]) -> Result<usize, Bu
 
     if let Some(ref detail) = frame.detail {
         w.write_pipe()?;
-        match detail {
-            AckDetail::Count(count) => w.write_u32(*count)?,
-            AckDetail::Variables(vars) => w.write_str(vars)?,
-            AckDetail::Command(cmd) => w.write_str(cmd)?,
-            AckDetail::Error { text, .. } => w.write_str(text)?,
-            AckDetail::Raw(raw) => w.write_str(raw)?,
-        }
+        w.write_ack_detail(detail, AckErrorText::AsGiven)?;
     }
 
     Ok(w.written())
@@ -296,7 +496,17 @@ pub fn build_headless(
     frame: &HeadlessFrame<'_>,
     buf: &mut [u8],
 ) -> Result<usize, BuildError> {
-    let mut w = FrameWriter::new(buf);
+    build_headless_to(method, frame, buf)
+}
+
+/// Build a headless inner frame into any [`ByteSink`].
+/// Returns the number of bytes written.
+pub fn build_headless_to<S: ByteSink>(
+    method: Method,
+    frame: &HeadlessFrame<'_>,
+    sink: S,
+) -> Result<usize, BuildError> {
+    let mut w = FrameWriter::new(sink);
 
     w.write_str(frame.serial)?;
 
@@ -320,23 +530,22 @@ pub fn build_headless(
 }
 
 /// Write a PUSH body (structured or passthrough).
-fn write_push_body(w: &mut FrameWriter<'_>, body: &PushBody<'_>) -> Result<(), BuildError> {
+fn write_push_body<S: ByteSink>(
+    w: &mut FrameWriter<S>,
+    body: &PushBody<'_>,
+) -> Result<(), BuildError> {
     match body {
         PushBody::Passthrough(pt) => {
             match pt.encoding {
                 PassthroughEncoding::Hex => w.write_str(">x")?,
                 PassthroughEncoding::Base64 => w.write_str(">b")?,
+                PassthroughEncoding::Base64Url => w.write_str(">u")?,
             }
             w.write_str(pt.data)?;
         }
         PushBody::Structured(structured) => {
             let pool = structured.meta_pool.as_slice();
-            w.write_body_modifiers(
-                structured.group,
-                structured.timestamp,
-                structured.body_meta,
-                pool,
-            )?;
+            w.write_body_modifiers(structured, pool)?;
             w.write_byte(b'[')?;
             for (i, var) in structured.variables.iter().enumerate() {
                 if i > 0 {
@@ -351,7 +560,10 @@ fn write_push_body(w: &mut FrameWriter<'_>, body: &PushBody<'_>) -> Result<(), B
 }
 
 /// Write a PULL body.
-fn write_pull_body(w: &mut FrameWriter<'_>, body: &PullBody<'_>) -> Result<(), BuildError> {
+fn write_pull_body<S: ByteSink>(
+    w: &mut FrameWriter<S>,
+    body: &PullBody<'_>,
+) -> Result<(), BuildError> {
     w.write_byte(b'[')?;
     for (i, name) in body.variables.iter().enumerate() {
         if i > 0 {
@@ -398,3 +610,11 @@ pub fn build_metadata(pairs: &[MetaPair<'_>], buf: &mut [u8]) -> Result<usize, B
     w.write_metadata_pairs(pairs)?;
     Ok(w.written())
 }
+
+/// Whether a string value must be quoted to survive a round trip: it holds
+/// an unescaped delimiter, or starts with `"` and would otherwise be read as
+/// a quoted value.
+#[cfg(feature = "quoted-strings")]
+fn needs_quotes(s: &str) -> bool {
+    s.starts_with('"') || !crate::types::is_escaped_value(s)
+}
@@ -1,6 +1,16 @@
+pub mod builder;
 pub mod frame;
+pub mod sink;
 
+pub use builder::PushFrameBuilder;
+#[cfg(feature = "std")]
+pub use sink::IoSink;
+pub use sink::{ByteSink, SliceSink};
+
+pub use frame::{AckErrorText, FrameWriter};
 pub use frame::{
-    build_ack, build_ack_inner, build_headless, build_metadata, build_pull_body, build_push_body,
-    build_uplink, build_variable,
+    build_ack, build_ack_inner, build_ack_inner_to, build_ack_to, build_ack_with, build_headless,
+    build_headless_to, build_metadata, build_pull_body, build_push_body, build_uplink,
+    build_uplink_checked, build_uplink_into_writer, build_uplink_line, build_uplink_to,
+    build_uplink_with_crc, build_variable,
 };
@@ -0,0 +1,111 @@
+use crate::error::BuildError;
+
+/// Destination for bytes produced by the frame builders.
+///
+/// Implemented for `&mut [u8]` and [`SliceSink`] (fixed buffer,
+/// allocation-free) and, with the
+/// `std` feature, for `Vec<u8>` and any [`std::io::Write`] wrapped in
+/// [`IoSink`]. A `&mut` reference to a sink is itself a sink.
+pub trait ByteSink {
+    /// Append `data`, or fail without writing anything if it does not fit.
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError>;
+
+    /// Append a single byte.
+    fn write_byte(&mut self, b: u8) -> Result<(), BuildError> {
+        self.write_bytes(&[b])
+    }
+}
+
+/// Writes into the front of the slice and advances it past the written bytes.
+impl ByteSink for &mut [u8] {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError> {
+        if data.len() > self.len() {
+            return Err(BuildError::buffer_too_small());
+        }
+        let (head, tail) = core::mem::take(self).split_at_mut(data.len());
+        head.copy_from_slice(data);
+        *self = tail;
+        Ok(())
+    }
+}
+
+/// A fixed buffer that remembers how much of it has been written.
+///
+/// Unlike a bare `&mut [u8]`, the buffer is not consumed by writing, so the
+/// output can be read back with [`as_bytes`](Self::as_bytes) and the buffer
+/// reused after [`clear`](Self::clear).
+#[derive(Debug)]
+pub struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// Wrap `buf`, starting empty.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// The bytes written since creation or the last [`clear`](Self::clear).
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Forget the written bytes so the buffer can be filled again.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl ByteSink for SliceSink<'_> {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError> {
+        let dest = self
+            .buf
+            .get_mut(self.len..self.len + data.len())
+            .ok_or_else(BuildError::buffer_too_small)?;
+        dest.copy_from_slice(data);
+        self.len += data.len();
+        Ok(())
+    }
+}
+
+impl<S: ByteSink + ?Sized> ByteSink for &mut S {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError> {
+        (**self).write_bytes(data)
+    }
+
+    fn write_byte(&mut self, b: u8) -> Result<(), BuildError> {
+        (**self).write_byte(b)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ByteSink for std::vec::Vec<u8> {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError> {
+        self.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn write_byte(&mut self, b: u8) -> Result<(), BuildError> {
+        self.push(b);
+        Ok(())
+    }
+}
+
+/// Adapter that lets builders write to any [`std::io::Write`] (files, sockets, ...).
+///
+/// I/O errors are reported as `BuildErrorKind::SinkFailed`. Writes are not
+/// buffered; wrap the writer in a `BufWriter` for small-write-heavy outputs.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSink<W>(pub W);
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> ByteSink for IoSink<W> {
+    fn write_bytes(&mut self, data: &[u8]) -> Result<(), BuildError> {
+        self.0
+            .write_all(data)
+            .map_err(|_| BuildError::sink_failed())
+    }
+}
@@ -0,0 +1,150 @@
+//! Hex and base64 helpers for passthrough payloads.
+//!
+//! All functions write into caller-provided buffers and return the number of
+//! bytes written, or `None` if the input is invalid or `out` is too small.
+
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Whether `s` is a valid hex passthrough payload: even length, hex digits only.
+#[must_use]
+pub fn is_valid_hex(s: &str) -> bool {
+    s.len() % 2 == 0 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Number of bytes [`hex_encode`] writes for `len` input bytes.
+#[must_use]
+pub const fn hex_encoded_len(len: usize) -> usize {
+    len * 2
+}
+
+/// Encode `data` as uppercase hex.
+pub fn hex_encode(data: &[u8], out: &mut [u8]) -> Option<usize> {
+    let len = hex_encoded_len(data.len());
+    let out = out.get_mut(..len)?;
+    for (&b, pair) in data.iter().zip(out.chunks_exact_mut(2)) {
+        pair[0] = HEX_UPPER[usize::from(b >> 4)];
+        pair[1] = HEX_UPPER[usize::from(b & 0xF)];
+    }
+    Some(len)
+}
+
+/// Decode a hex string (either case). Odd-length input is rejected.
+pub fn hex_decode(s: &str, out: &mut [u8]) -> Option<usize> {
+    let bytes = s.as_bytes();
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    let len = bytes.len() / 2;
+    let out = out.get_mut(..len)?;
+    for (pair, slot) in bytes.chunks_exact(2).zip(out.iter_mut()) {
+        *slot = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+    }
+    Some(len)
+}
+
+/// Number of bytes [`base64_encode`] writes for `len` input bytes (padded).
+#[must_use]
+pub const fn base64_encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
+}
+
+/// Encode `data` as standard base64 with `=` padding.
+pub fn base64_encode(data: &[u8], out: &mut [u8]) -> Option<usize> {
+    let len = base64_encoded_len(data.len());
+    let out = out.get_mut(..len)?;
+    for (chunk, quad) in data.chunks(3).zip(out.chunks_exact_mut(4)) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied().unwrap_or(0);
+        let b2 = chunk.get(2).copied().unwrap_or(0);
+        quad[0] = BASE64_ALPHABET[usize::from(b0 >> 2)];
+        quad[1] = BASE64_ALPHABET[usize::from(((b0 & 0x03) << 4) | (b1 >> 4))];
+        quad[2] = if chunk.len() > 1 {
+            BASE64_ALPHABET[usize::from(((b1 & 0x0F) << 2) | (b2 >> 6))]
+        } else {
+            b'='
+        };
+        quad[3] = if chunk.len() > 2 {
+            BASE64_ALPHABET[usize::from(b2 & 0x3F)]
+        } else {
+            b'='
+        };
+    }
+    Some(len)
+}
+
+/// Decode standard base64. Padding is optional, but if present it must
+/// complete the final quad and may only appear at the end.
+pub fn base64_decode(s: &str, out: &mut [u8]) -> Option<usize> {
+    decode_base64_with(s, out, base64_digit)
+}
+
+/// Decode URL-safe base64 (`-` and `_` for 62 and 63), with the same padding
+/// rules as [`base64_decode`].
+pub fn base64url_decode(s: &str, out: &mut [u8]) -> Option<usize> {
+    decode_base64_with(s, out, base64url_digit)
+}
+
+fn decode_base64_with(s: &str, out: &mut [u8], digit: fn(u8) -> Option<u8>) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let data = bytes
+        .strip_suffix(b"==")
+        .or_else(|| bytes.strip_suffix(b"="))
+        .unwrap_or(bytes);
+    if data.len() != bytes.len() && bytes.len() % 4 != 0 {
+        return None;
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let len = data.len() / 4 * 3 + (data.len() % 4).saturating_sub(1);
+    let out = out.get_mut(..len)?;
+
+    let mut acc: u32 = 0;
+    let mut bits = 0u32;
+    let mut written = 0;
+    for &c in data {
+        acc = (acc << 6) | u32::from(digit(c)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            // Truncation keeps the low byte of the accumulator.
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                out[written] = (acc >> bits) as u8;
+            }
+            written += 1;
+        }
+    }
+    Some(written)
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn base64_digit(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+fn base64url_digit(c: u8) -> Option<u8> {
+    match c {
+        b'-' => Some(62),
+        b'_' => Some(63),
+        b'+' | b'/' => None,
+        _ => base64_digit(c),
+    }
+}
@@ -2,6 +2,8 @@
 pub const MAX_VARIABLES: usize = 100;
 
 /// Maximum number of metadata key-value pairs in a single `{}` block.
+///
+/// All blocks in a frame also share a pool of `MAX_TOTAL_META` pairs.
 pub const MAX_META_PAIRS: usize = 32;
 
 /// Maximum byte length of a variable name.
@@ -28,5 +30,8 @@ pub const AUTH_HASH_LEN: usize = 16;
 /// Maximum fields after pipe-splitting an uplink frame (METHOD|!N|AUTH|SERIAL|BODY = 5).
 pub const MAX_UPLINK_FIELDS: usize = 8;
 
+/// Maximum unrecognized body modifiers kept by `ParseOptions::allow_unknown_modifiers`.
+pub const MAX_UNKNOWN_MODIFIERS: usize = 8;
+
 /// Maximum fields after pipe-splitting an ACK frame (ACK|!N|STATUS|DETAIL = 4).
 pub const MAX_ACK_FIELDS: usize = 4;
@@ -0,0 +1,47 @@
+/// Length of the `*XXXX` checksum suffix appended to plaintext frames.
+pub const CRC_SUFFIX_LEN: usize = 5;
+
+/// CRC-16/CCITT-FALSE (poly `0x1021`, init `0xFFFF`, no reflection, no final XOR).
+///
+/// The check value for `b"123456789"` is `0x29B1`.
+#[must_use]
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &b in data {
+        crc ^= u16::from(b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Format a CRC as the `*XXXX` suffix (uppercase hex).
+#[must_use]
+pub fn format_suffix(crc: u16) -> [u8; CRC_SUFFIX_LEN] {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let mut out = [b'*'; CRC_SUFFIX_LEN];
+    for (i, slot) in out[1..].iter_mut().enumerate() {
+        *slot = HEX[usize::from((crc >> (12 - 4 * i)) & 0xF)];
+    }
+    out
+}
+
+/// Decode the four hex digits of a `*XXXX` suffix (either case).
+#[must_use]
+pub fn parse_suffix(suffix: &[u8]) -> Option<u16> {
+    let [b'*', digits @ ..] = suffix else {
+        return None;
+    };
+    if digits.len() != CRC_SUFFIX_LEN - 1 {
+        return None;
+    }
+    digits.iter().try_fold(0u16, |acc, &d| {
+        let v = char::from(d).to_digit(16)?;
+        Some((acc << 4) | u16::try_from(v).ok()?)
+    })
+}
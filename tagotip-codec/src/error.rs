@@ -35,6 +35,8 @@ pub enum ParseErrorKind {
     TooManyItems,
     /// Frame exceeds maximum size.
     FrameTooLarge,
+    /// CRC suffix is missing, malformed, or does not match the frame.
+    ChecksumMismatch,
 }
 
 /// Error returned by parsing functions.
@@ -71,6 +73,7 @@ impl fmt::Display for ParseError {
             ParseErrorKind::InvalidAck => "invalid ACK frame",
             ParseErrorKind::TooManyItems => "too many items",
             ParseErrorKind::FrameTooLarge => "frame too large",
+            ParseErrorKind::ChecksumMismatch => "checksum mismatch",
         };
         write!(f, "{} at byte {}", desc, self.position)
     }
@@ -86,12 +89,18 @@ pub enum BuildErrorKind {
     BufferTooSmall,
     /// Invalid input data (e.g., empty variable name).
     InvalidInput,
+    /// The output sink failed to accept bytes (e.g., an I/O error).
+    SinkFailed,
 }
 
 /// Error returned by builder functions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BuildError {
     pub kind: BuildErrorKind,
+    /// Why the parser rejected the built output, for errors from
+    /// [`build_uplink_checked`](crate::build::build_uplink_checked). Its
+    /// position is a byte offset into that output.
+    pub cause: Option<ParseError>,
 }
 
 impl BuildError {
@@ -99,6 +108,7 @@ impl BuildError {
     pub fn buffer_too_small() -> Self {
         Self {
             kind: BuildErrorKind::BufferTooSmall,
+            cause: None,
         }
     }
 
@@ -106,15 +116,37 @@ impl BuildError {
     pub fn invalid_input() -> Self {
         Self {
             kind: BuildErrorKind::InvalidInput,
+            cause: None,
+        }
+    }
+
+    /// `InvalidInput` caused by the built output failing to re-parse.
+    #[must_use]
+    pub fn rejected_by_parser(cause: ParseError) -> Self {
+        Self {
+            kind: BuildErrorKind::InvalidInput,
+            cause: Some(cause),
+        }
+    }
+
+    /// `SinkFailed`: the sink refused bytes for a reason other than running
+    /// out of space, such as an I/O error from a `std::io::Write` sink.
+    #[must_use]
+    pub fn sink_failed() -> Self {
+        Self {
+            kind: BuildErrorKind::SinkFailed,
+            cause: None,
         }
     }
 }
 
 impl fmt::Display for BuildError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.kind {
-            BuildErrorKind::BufferTooSmall => write!(f, "output buffer too small"),
-            BuildErrorKind::InvalidInput => write!(f, "invalid input data"),
+        match (self.kind, self.cause) {
+            (BuildErrorKind::BufferTooSmall, _) => write!(f, "output buffer too small"),
+            (BuildErrorKind::InvalidInput, Some(cause)) => write!(f, "invalid input data: {cause}"),
+            (BuildErrorKind::InvalidInput, None) => write!(f, "invalid input data"),
+            (BuildErrorKind::SinkFailed, _) => write!(f, "output sink failed"),
         }
     }
 }
@@ -8,7 +8,8 @@ pub fn needs_unescape(s: &str) -> bool {
 ///
 /// Decodes: `\|` → `|`, `\[` → `[`, `\]` → `]`, `\;` → `;`, `\,` → `,`,
 /// `\{` → `{`, `\}` → `}`, `\#` → `#`, `\@` → `@`, `\^` → `^`,
-/// `\\` → `\`, `\n` → newline (0x0A).
+/// `\\` → `\`, `\n` → newline (0x0A), and `\"` → `"` with the
+/// `quoted-strings` feature.
 ///
 /// Returns the number of bytes written to `out`, or `None` if `out` is too small.
 pub fn unescape_into(s: &str, out: &mut [u8]) -> Option<usize> {
@@ -18,20 +19,15 @@ pub fn unescape_into(s: &str, out: &mut [u8]) -> Option<usize> {
 
     while i < bytes.len() {
         if bytes[i] == b'\\' && i + 1 < bytes.len() {
-            let next = bytes[i + 1];
-            let decoded = match next {
-                b'|' | b'[' | b']' | b';' | b',' | b'{' | b'}' | b'#' | b'@' | b'^' | b'\\' => next,
-                b'n' => b'\n',
-                _ => {
-                    // Not a recognized escape — output the backslash literally
-                    if w >= out.len() {
-                        return None;
-                    }
-                    out[w] = b'\\';
-                    w += 1;
-                    i += 1;
-                    continue;
+            let Some(decoded) = decode_escape(bytes[i + 1]) else {
+                // Not a recognized escape — output the backslash literally
+                if w >= out.len() {
+                    return None;
                 }
+                out[w] = b'\\';
+                w += 1;
+                i += 1;
+                continue;
             };
             if w >= out.len() {
                 return None;
@@ -52,38 +48,134 @@ pub fn unescape_into(s: &str, out: &mut [u8]) -> Option<usize> {
     Some(w)
 }
 
+/// Unescape a `TagoTiP` string in place, returning the new length.
+///
+/// Decodes the same sequences as [`unescape_into`] and treats a trailing or
+/// unrecognized backslash the same way (kept literally). Unescaping never
+/// grows the data, so no second buffer is needed; bytes past the returned
+/// length are left unspecified.
+pub fn unescape_in_place(buf: &mut [u8]) -> usize {
+    let mut i = 0;
+    let mut w = 0;
+
+    while i < buf.len() {
+        if buf[i] == b'\\' && i + 1 < buf.len() {
+            if let Some(decoded) = decode_escape(buf[i + 1]) {
+                buf[w] = decoded;
+                w += 1;
+                i += 2;
+                continue;
+            }
+        }
+        buf[w] = buf[i];
+        w += 1;
+        i += 1;
+    }
+
+    w
+}
+
+/// Decode the byte following a backslash, or `None` if it is not an escape.
+fn decode_escape(next: u8) -> Option<u8> {
+    match next {
+        b'|' | b'[' | b']' | b';' | b',' | b'{' | b'}' | b'#' | b'@' | b'^' | b'\\' => Some(next),
+        #[cfg(feature = "quoted-strings")]
+        b'"' => Some(next),
+        b'n' => Some(b'\n'),
+        _ => None,
+    }
+}
+
+/// Index of the `"` closing a quoted value whose content starts at `start`,
+/// skipping `\"` (the only escape inside quotes). `None` if unterminated.
+#[cfg(feature = "quoted-strings")]
+pub(crate) fn closing_quote(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'"') {
+            i += 2;
+            continue;
+        }
+        if bytes[i] == b'"' {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// With the `quoted-strings` feature, a `"` as the first byte of a string
+/// or metadata value opens a quoted value in which delimiters are literal.
+/// Returns the index just past the span opening at `i` (`bytes.len()` if
+/// unterminated, which the value parser then rejects), or `None` if no span
+/// opens there.
+#[inline]
+pub(crate) fn quoted_span_end(bytes: &[u8], i: usize) -> Option<usize> {
+    #[cfg(feature = "quoted-strings")]
+    if bytes[i] == b'"' && i > 0 && bytes[i - 1] == b'=' && starts_value(bytes, i - 1) {
+        return Some(closing_quote(bytes, i + 1).map_or(bytes.len(), |end| end + 1));
+    }
+    #[cfg(not(feature = "quoted-strings"))]
+    let _ = (bytes, i);
+    None
+}
+
+/// Whether the `=` at `eq` follows a name (`msg=`, `{key=`) rather than
+/// sitting inside a value (`msg=a=`): the name bytes before it run back to
+/// the start of `bytes` or to an unescaped `[`, `;`, `{`, `,` or `|`.
+#[cfg(feature = "quoted-strings")]
+fn starts_value(bytes: &[u8], eq: usize) -> bool {
+    let start = bytes[..eq]
+        .iter()
+        .rposition(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+        .map_or(0, |d| d + 1);
+    if start == eq {
+        return false;
+    }
+    if start == 0 {
+        return true;
+    }
+    let d = start - 1;
+    let backslashes = bytes[..d].iter().rev().take_while(|&&b| b == b'\\').count();
+    matches!(bytes[d], b'[' | b';' | b'{' | b',' | b'|') && backslashes % 2 == 0
+}
+
 /// Characters that need escaping in string values and metadata values.
+#[cfg(not(feature = "quoted-strings"))]
 const STRUCTURAL: &[u8] = b"|[];,{}#@^\\\n";
 
+/// Characters that need escaping in string values and metadata values,
+/// plus `"` so an escaped value never reads as a quoted one.
+#[cfg(feature = "quoted-strings")]
+const STRUCTURAL: &[u8] = b"|[];,{}#@^\\\n\"";
+
 /// Returns `true` if the byte needs escaping in a string/metadata value context.
-fn needs_escape(b: u8) -> bool {
+pub(crate) fn needs_escape(b: u8) -> bool {
     STRUCTURAL.contains(&b)
 }
 
+/// Exact length of `s` once escaped by [`escape_into`], computed without writing.
+#[must_use]
+pub fn escaped_len(s: &str) -> usize {
+    s.len() + s.bytes().filter(|&b| needs_escape(b)).count()
+}
+
 /// Escape a string for use in a `TagoTiP` frame, writing into the output buffer.
 ///
-/// Returns the number of bytes written, or `None` if `out` is too small.
+/// Returns the number of bytes written, or `None` if `out` is too small
+/// (checked up front; use [`escaped_len`] to size the buffer).
 pub fn escape_into(s: &str, out: &mut [u8]) -> Option<usize> {
-    let bytes = s.as_bytes();
-    let mut w = 0;
+    if escaped_len(s) > out.len() {
+        return None;
+    }
 
-    for &b in bytes {
+    let mut w = 0;
+    for b in s.bytes() {
         if needs_escape(b) {
-            if w + 1 >= out.len() {
-                return None;
-            }
             out[w] = b'\\';
-            w += 1;
-            if b == b'\n' {
-                out[w] = b'n';
-            } else {
-                out[w] = b;
-            }
-            w += 1;
+            out[w + 1] = if b == b'\n' { b'n' } else { b };
+            w += 2;
         } else {
-            if w >= out.len() {
-                return None;
-            }
             out[w] = b;
             w += 1;
         }
@@ -51,6 +51,11 @@ impl<T, const N: usize> InlineVec<T, N> {
         Ok(())
     }
 
+    /// Pushes an element, dropping it if full. Returns whether it was kept.
+    pub fn push_truncating(&mut self, value: T) -> bool {
+        self.push(value).is_ok()
+    }
+
     /// Returns a slice of the initialized elements.
     pub fn as_slice(&self) -> &[T] {
         // SAFETY: elements 0..self.len are initialized.
@@ -84,18 +89,118 @@ impl<T, const N: usize> InlineVec<T, N> {
         }
     }
 
+    /// Removes and returns the element at `index`, shifting later elements
+    /// down. Returns `None` if `index` is out of bounds.
+    ///
+    /// Removing from a `meta_pool` or `variables` list invalidates the
+    /// [`MetaRange`](crate::types::MetaRange)s that point past `index`; the
+    /// caller must adjust them.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: index < self.len, so the element is initialized; the tail
+        // index+1..len is shifted down by one, staying within the array.
+        unsafe {
+            let base = self.data.as_mut_ptr().cast::<T>();
+            let value = ptr::read(base.add(index));
+            ptr::copy(base.add(index + 1), base.add(index), self.len - index - 1);
+            self.len -= 1;
+            Some(value)
+        }
+    }
+
+    /// Inserts `value` at `index`, shifting later elements up. Returns
+    /// `Err(value)` if the vector is full or `index > len`.
+    ///
+    /// Like [`remove`](Self::remove), this shifts pool positions, so any
+    /// `MetaRange` past `index` must be adjusted by the caller.
+    pub fn insert(&mut self, index: usize, value: T) -> Result<(), T> {
+        if self.len >= N || index > self.len {
+            return Err(value);
+        }
+        // SAFETY: self.len < N, so shifting index..len up by one stays within
+        // the array, and the slot at `index` is then free to write.
+        unsafe {
+            let base = self.data.as_mut_ptr().cast::<T>();
+            ptr::copy(base.add(index), base.add(index + 1), self.len - index);
+            ptr::write(base.add(index), value);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, compacting the
+    /// survivors to the front in their original order.
+    ///
+    /// Like [`remove`](Self::remove), this shifts pool positions, so any
+    /// `MetaRange` into a filtered `meta_pool` is invalidated; filter
+    /// structured bodies through a helper that rebuilds the ranges.
+    /// Does NOT call drop on removed elements.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        let items = self.as_mut_slice();
+        for i in 0..items.len() {
+            if f(&items[i]) {
+                items.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+
     /// Clears the vector, setting length to 0.
     /// Does NOT call drop on contained elements.
     pub fn clear(&mut self) {
         self.len = 0;
     }
 
+    /// Shortens the vector to `len` elements; no-op if already shorter.
+    /// Does NOT call drop on removed elements.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+    }
+
     /// Returns an iterator over the elements.
     pub fn iter(&self) -> slice::Iter<'_, T> {
         self.as_slice().iter()
     }
 }
 
+impl<T: Copy, const N: usize> InlineVec<T, N> {
+    /// Creates a vector holding a copy of `items`, or `Err(())` if it has
+    /// more than `N` elements.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_slice(items: &[T]) -> Result<Self, ()> {
+        let mut v = Self::new();
+        v.try_extend_from_slice(items).map_err(|_| ())?;
+        Ok(v)
+    }
+
+    /// Appends every element of `items`, stopping when the vector is full.
+    ///
+    /// Returns `Err(i)` with the index in `items` of the first element that
+    /// did not fit; the elements before it have been pushed and the vector
+    /// is left at capacity.
+    pub fn try_extend_from_slice(&mut self, items: &[T]) -> Result<(), usize> {
+        for (i, &item) in items.iter().enumerate() {
+            self.push(item).map_err(|_| i)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a InlineVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl<T, const N: usize> Default for InlineVec<T, N> {
     fn default() -> Self {
         Self::new()
@@ -140,3 +245,43 @@ impl<T: Clone, const N: usize> Clone for InlineVec<T, N> {
         new
     }
 }
+
+/// Serializes as a sequence of the initialized elements.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const N: usize> serde::Serialize for InlineVec<T, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.as_slice())
+    }
+}
+
+/// Deserializes from a sequence, failing with `invalid_length` if it has more
+/// than `N` elements.
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for InlineVec<T, N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SeqVisitor<T, const N: usize>(core::marker::PhantomData<T>);
+
+        impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::de::Visitor<'de> for SeqVisitor<T, N> {
+            type Value = InlineVec<T, N>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "a sequence of at most {N} elements")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut out = InlineVec::new();
+                while let Some(value) = seq.next_element()? {
+                    if out.push(value).is_err() {
+                        return Err(serde::de::Error::invalid_length(N + 1, &self));
+                    }
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor(core::marker::PhantomData))
+    }
+}
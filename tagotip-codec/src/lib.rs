@@ -4,7 +4,9 @@
 #[cfg(feature = "std")]
 extern crate std;
 
+pub mod codec;
 pub mod consts;
+pub mod crc;
 pub mod error;
 pub mod escape;
 pub mod fmt;
@@ -12,21 +14,32 @@ pub mod inline_vec;
 pub mod types;
 pub mod validate;
 
+#[cfg(feature = "test-util")]
+pub mod testutil;
+
 pub mod build;
 pub mod parse;
 
 pub use error::{BuildError, ParseError, ParseErrorKind};
 pub use types::*;
 
+// Re-export protocol limits (`MAX_TOTAL_META` comes from `types`)
+pub use consts::{
+    AUTH_HASH_LEN, MAX_FRAME_SIZE, MAX_GROUP_LEN, MAX_META_KEY_LEN, MAX_META_PAIRS, MAX_SERIAL_LEN,
+    MAX_UNIT_LEN, MAX_VARIABLES, MAX_VARNAME_LEN,
+};
+
 // Re-export granular parse functions
 pub use parse::{
-    ParsedVariable, extract_serial, parse_metadata, parse_method, parse_pull_body, parse_push_body,
-    parse_seq, parse_variable, validate_auth,
+    ParsedVariable, VariablesStream, extract_serial, parse_metadata, parse_method, parse_pull_body,
+    parse_push_body, parse_push_body_truncating, parse_seq, parse_variable,
+    parse_variables_streaming, validate_auth, validate_uplink,
 };
 
 // Re-export granular build functions
 pub use build::{
-    build_ack_inner, build_metadata, build_pull_body, build_push_body, build_variable,
+    PushFrameBuilder, build_ack_inner, build_metadata, build_pull_body, build_push_body,
+    build_variable,
 };
 
 // Re-export ACK inner frame parser for TagoTiP/S
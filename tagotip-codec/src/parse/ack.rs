@@ -1,20 +1,35 @@
 use crate::error::{ParseError, ParseErrorKind};
-use crate::types::{AckDetail, AckFrame, AckStatus, ErrorCode};
+use crate::types::{AckDetail, AckErrorList, AckFrame, AckStatus, ErrorCode};
 
-use super::frame::{parse_seq, split_fields};
+use super::frame::{check_frame_bytes, next_field_pos, parse_seq, split_fields};
+use super::options::ParseOptions;
 
 /// Parse an ACK inner frame from TagoTiP/S: `STATUS[|DETAIL]` (no `ACK|` prefix).
 pub fn parse_ack_inner(input: &str) -> Result<AckFrame<'_>, ParseError> {
+    parse_ack_inner_with(input, &ParseOptions::default())
+}
+
+/// Parse an ACK inner frame with custom [`ParseOptions`].
+pub fn parse_ack_inner_with<'a>(
+    input: &'a str,
+    opts: &ParseOptions,
+) -> Result<AckFrame<'a>, ParseError> {
     let fields = split_fields(input);
 
     if fields.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidAck, 0));
     }
 
-    let status = parse_ack_status(fields[0])?;
+    let status = parse_ack_status(fields[0], 0)?;
 
     let detail = if fields.len() > 1 {
-        Some(parse_ack_detail(fields[1], status)?)
+        let detail_pos = next_field_pos(0, fields[0]);
+        Some(parse_ack_detail(
+            &input[detail_pos..],
+            detail_pos,
+            status,
+            opts,
+        )?)
     } else {
         None
     };
@@ -33,7 +48,16 @@ pub fn parse_ack_inner(input: &str) -> Result<AckFrame<'_>, ParseError> {
 /// - `ACK|STATUS|DETAIL`
 /// - `ACK|!N|STATUS`
 /// - `ACK|!N|STATUS|DETAIL`
+///
+/// DETAIL runs to the end of the frame, so it may itself contain `|`.
 pub fn parse_ack(input: &str) -> Result<AckFrame<'_>, ParseError> {
+    parse_ack_with(input, &ParseOptions::default())
+}
+
+/// Parse an ACK (downlink) frame with custom [`ParseOptions`].
+pub fn parse_ack_with<'a>(input: &'a str, opts: &ParseOptions) -> Result<AckFrame<'a>, ParseError> {
+    check_frame_bytes(input)?;
+
     let fields = split_fields(input);
 
     if fields.is_empty() || fields[0] != "ACK" {
@@ -42,8 +66,10 @@ pub fn parse_ack(input: &str) -> Result<AckFrame<'_>, ParseError> {
 
     let field_count = fields.len();
 
+    // A truncated frame (`ACK`, `ACK|!5`) is missing its status: point just
+    // past the last field, where the status should have started.
     if field_count < 2 {
-        return Err(ParseError::new(ParseErrorKind::InvalidAck, 0));
+        return Err(ParseError::new(ParseErrorKind::InvalidAck, input.len()));
     }
 
     // Determine if field[1] is a sequence counter
@@ -55,14 +81,24 @@ pub fn parse_ack(input: &str) -> Result<AckFrame<'_>, ParseError> {
     };
 
     if field_count <= status_idx {
-        return Err(ParseError::new(ParseErrorKind::InvalidAck, 0));
+        return Err(ParseError::new(ParseErrorKind::InvalidAck, input.len()));
     }
 
-    let status = parse_ack_status(fields[status_idx])?;
+    let status_pos = fields[..status_idx]
+        .iter()
+        .fold(0, |pos, f| next_field_pos(pos, f));
+    let status = parse_ack_status(fields[status_idx], status_pos)?;
 
+    // The detail is the rest of the frame: CMD payloads (e.g. URLs) and ERR
+    // text may contain unescaped `|`.
     let detail = if field_count > status_idx + 1 {
-        let detail_str = fields[status_idx + 1];
-        Some(parse_ack_detail(detail_str, status)?)
+        let detail_pos = next_field_pos(status_pos, fields[status_idx]);
+        Some(parse_ack_detail(
+            &input[detail_pos..],
+            detail_pos,
+            status,
+            opts,
+        )?)
     } else {
         None
     };
@@ -74,19 +110,24 @@ pub fn parse_ack(input: &str) -> Result<AckFrame<'_>, ParseError> {
     })
 }
 
-/// Parse an ACK status string.
-fn parse_ack_status(s: &str) -> Result<AckStatus, ParseError> {
+/// Parse an ACK status string found at `pos`.
+fn parse_ack_status(s: &str, pos: usize) -> Result<AckStatus, ParseError> {
     match s {
         "OK" => Ok(AckStatus::Ok),
         "PONG" => Ok(AckStatus::Pong),
         "CMD" => Ok(AckStatus::Cmd),
         "ERR" => Ok(AckStatus::Err),
-        _ => Err(ParseError::new(ParseErrorKind::InvalidAck, 0)),
+        _ => Err(ParseError::new(ParseErrorKind::InvalidAck, pos)),
     }
 }
 
 /// Parse the DETAIL field of an ACK frame.
-fn parse_ack_detail(s: &str, status: AckStatus) -> Result<AckDetail<'_>, ParseError> {
+fn parse_ack_detail<'a>(
+    s: &'a str,
+    pos: usize,
+    status: AckStatus,
+    opts: &ParseOptions,
+) -> Result<AckDetail<'a>, ParseError> {
     match status {
         AckStatus::Ok => {
             // Could be a count (digits) or variables (bracket-wrapped)
@@ -102,27 +143,20 @@ fn parse_ack_detail(s: &str, status: AckStatus) -> Result<AckDetail<'_>, ParseEr
             }
         }
         AckStatus::Pong => {
-            // PONG shouldn't have detail, but if present, return raw
+            // PONG shouldn't have detail; leniently return it raw
+            if opts.reject_pong_detail {
+                return Err(ParseError::new(ParseErrorKind::InvalidAck, pos));
+            }
             Ok(AckDetail::Raw(s))
         }
         AckStatus::Cmd => Ok(AckDetail::Command(s)),
-        AckStatus::Err => {
-            let code = match s {
-                "invalid_token" => ErrorCode::InvalidToken,
-                "invalid_method" => ErrorCode::InvalidMethod,
-                "invalid_payload" => ErrorCode::InvalidPayload,
-                "invalid_seq" => ErrorCode::InvalidSeq,
-                "device_not_found" => ErrorCode::DeviceNotFound,
-                "variable_not_found" => ErrorCode::VariableNotFound,
-                "rate_limited" => ErrorCode::RateLimited,
-                "auth_failed" => ErrorCode::AuthFailed,
-                "unsupported_version" => ErrorCode::UnsupportedVersion,
-                "payload_too_large" => ErrorCode::PayloadTooLarge,
-                "server_error" => ErrorCode::ServerError,
-                _ => ErrorCode::Unknown,
-            };
-            Ok(AckDetail::Error { code, text: s })
-        }
+        AckStatus::Err if opts.allow_error_list && s.contains(';') => AckErrorList::parse(s)
+            .map(AckDetail::Errors)
+            .map_err(|e| ParseError::new(e.kind, pos.saturating_add(e.position))),
+        AckStatus::Err => Ok(AckDetail::Error {
+            code: ErrorCode::from_wire(s),
+            text: s,
+        }),
     }
 }
 
@@ -1,25 +1,45 @@
-use crate::consts::MAX_VARIABLES;
+use crate::consts::{MAX_UNKNOWN_MODIFIERS, MAX_VARIABLES};
 use crate::error::{ParseError, ParseErrorKind};
+use crate::escape;
 use crate::inline_vec::InlineVec;
 use crate::types::{
-    MAX_TOTAL_META, MetaPair, MetaRange, PassthroughBody, PassthroughEncoding, PullBody, PushBody,
-    StructuredBody, Variable,
+    MAX_TOTAL_META, MetaPair, MetaRange, ModifierSlot, PassthroughBody, PassthroughEncoding,
+    PullBody, PushBody, StructuredBody, UnknownModifier, Variable,
 };
 use crate::validate;
 
-use super::variable::{parse_metadata, parse_variable};
+use super::options::ParseOptions;
+use super::variable::{ParsedVariable, parse_metadata_with, parse_variable_with};
 
 /// Body-level modifiers parsed from the prefix before `[`.
 type BodyModifiers<'a> = (Option<&'a str>, Option<&'a str>, Option<MetaRange>);
 
 /// Parse a PUSH body string (everything after SERIAL|).
-pub fn parse_push_body<'a>(body: &'a str, base_pos: usize) -> Result<PushBody<'a>, ParseError> {
+pub fn parse_push_body(body: &str, base_pos: usize) -> Result<PushBody<'_>, ParseError> {
+    parse_push_body_recover(body, base_pos, &ParseOptions::default(), &mut Err, None)
+}
+
+/// Parse a PUSH body, routing per-variable errors through `recover`.
+///
+/// `recover` returns `Err` to abort (strict parsing) or `Ok(())` to skip the
+/// offending variable and continue. With `dropped`, variables past
+/// `MAX_VARIABLES` are counted there instead of failing with `TooManyItems`.
+pub(crate) fn parse_push_body_recover<'a>(
+    body: &'a str,
+    base_pos: usize,
+    opts: &ParseOptions,
+    recover: &mut impl FnMut(ParseError) -> Result<(), ParseError>,
+    dropped: Option<&mut usize>,
+) -> Result<PushBody<'a>, ParseError> {
     // Check for passthrough
     if let Some(rest) = body.strip_prefix(">x") {
         return parse_hex_passthrough(rest, base_pos + 2);
     }
     if let Some(rest) = body.strip_prefix(">b") {
-        return parse_base64_passthrough(rest, base_pos + 2);
+        return parse_base64_passthrough(rest, base_pos + 2, PassthroughEncoding::Base64);
+    }
+    if let Some(rest) = body.strip_prefix(">u") {
+        return parse_base64_passthrough(rest, base_pos + 2, PassthroughEncoding::Base64Url);
     }
 
     // Structured body: [body-mods] "[" var-list "]"
@@ -48,11 +68,24 @@ pub fn parse_push_body<'a>(body: &'a str, base_pos: usize) -> Result<PushBody<'a
     let mut meta_pool: InlineVec<MetaPair<'a>, MAX_TOTAL_META> = InlineVec::new();
 
     // Parse body-level modifiers
-    let (body_group, body_timestamp, body_meta) =
-        parse_body_modifiers(mod_str, base_pos, &mut meta_pool)?;
+    let mut unknown_modifiers = InlineVec::new();
+    let (body_group, body_timestamp, body_meta) = parse_body_modifiers(
+        mod_str,
+        base_pos,
+        opts,
+        &mut meta_pool,
+        &mut unknown_modifiers,
+    )?;
 
     // Parse variables
-    let variables = parse_variable_list(var_block, base_pos + bracket_pos + 1, &mut meta_pool)?;
+    let variables = parse_variable_list(
+        var_block,
+        base_pos + bracket_pos + 1,
+        &mut meta_pool,
+        opts,
+        recover,
+        dropped,
+    )?;
 
     if variables.is_empty() {
         return Err(ParseError::new(
@@ -67,11 +100,21 @@ pub fn parse_push_body<'a>(body: &'a str, base_pos: usize) -> Result<PushBody<'a
         body_meta,
         variables,
         meta_pool,
+        unknown_modifiers,
     }))
 }
 
 /// Parse a PULL body string: `[var1;var2;...]`.
-pub fn parse_pull_body<'a>(body: &'a str, base_pos: usize) -> Result<PullBody<'a>, ParseError> {
+pub fn parse_pull_body(body: &str, base_pos: usize) -> Result<PullBody<'_>, ParseError> {
+    parse_pull_body_recover(body, base_pos, &mut Err)
+}
+
+/// Parse a PULL body, routing per-name validation errors through `recover`.
+pub(crate) fn parse_pull_body_recover<'a>(
+    body: &'a str,
+    base_pos: usize,
+    recover: &mut impl FnMut(ParseError) -> Result<(), ParseError>,
+) -> Result<PullBody<'a>, ParseError> {
     if !body.starts_with('[') || !body.ends_with(']') {
         return Err(ParseError::new(ParseErrorKind::MissingBody, base_pos));
     }
@@ -97,10 +140,12 @@ pub fn parse_pull_body<'a>(body: &'a str, base_pos: usize) -> Result<PullBody<'a
         if at_end || is_semi {
             let name = &inner[start..i];
             if !name.is_empty() {
-                validate::validate_varname(name, base_pos + 1 + start)?;
-                variables.push(name).map_err(|_| {
-                    ParseError::new(ParseErrorKind::TooManyItems, base_pos + 1 + start)
-                })?;
+                match validate::validate_varname(name, base_pos + 1 + start) {
+                    Ok(()) => variables.push(name).map_err(|_| {
+                        ParseError::new(ParseErrorKind::TooManyItems, base_pos + 1 + start)
+                    })?,
+                    Err(e) => recover(e)?,
+                }
             }
             if at_end {
                 break;
@@ -132,7 +177,9 @@ pub fn parse_pull_body<'a>(body: &'a str, base_pos: usize) -> Result<PullBody<'a
 fn parse_body_modifiers<'a>(
     s: &'a str,
     base_pos: usize,
+    opts: &ParseOptions,
     meta_pool: &mut InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
+    unknown: &mut InlineVec<UnknownModifier<'a>, MAX_UNKNOWN_MODIFIERS>,
 ) -> Result<BodyModifiers<'a>, ParseError> {
     if s.is_empty() {
         return Ok((None, None, None));
@@ -150,7 +197,9 @@ fn parse_body_modifiers<'a>(
     while pos < bytes.len() {
         match bytes[pos] {
             b'@' => {
-                if phase > 0 {
+                // `^group@ts` is tolerated when the option allows any order.
+                let swapped = opts.allow_any_modifier_order && phase == 2 && timestamp.is_none();
+                if phase > 0 && !swapped {
                     return Err(ParseError::new(
                         ParseErrorKind::InvalidModifier,
                         base_pos + pos,
@@ -158,11 +207,21 @@ fn parse_body_modifiers<'a>(
                 }
                 pos += 1;
                 let start = pos;
-                pos = scan_until_mod(bytes, pos);
+                pos = if opts.allow_unknown_modifiers {
+                    scan_while(bytes, pos, |b| b.is_ascii_digit())
+                } else {
+                    scan_until_mod(bytes, pos)
+                };
                 let ts = &s[start..pos];
                 validate_digits(ts, base_pos + start)?;
+                if opts.reject_oversized_timestamps && !validate::digits_fit_u64(ts) {
+                    return Err(ParseError::new(
+                        ParseErrorKind::InvalidModifier,
+                        base_pos + start,
+                    ));
+                }
                 timestamp = Some(ts);
-                phase = 1;
+                phase = phase.max(1);
             }
             b'^' => {
                 if phase > 1 {
@@ -173,7 +232,18 @@ fn parse_body_modifiers<'a>(
                 }
                 pos += 1;
                 let start = pos;
-                pos = scan_until_any(bytes, pos, b"{");
+                let stops: &[u8] = if opts.allow_any_modifier_order {
+                    b"{@"
+                } else {
+                    b"{"
+                };
+                pos = if opts.allow_unknown_modifiers {
+                    scan_while(bytes, pos, |b| {
+                        b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_'
+                    })
+                } else {
+                    scan_until_any(bytes, pos, stops)
+                };
                 let g = &s[start..pos];
                 validate::validate_group(g, base_pos + start)?;
                 group = Some(g);
@@ -192,11 +262,20 @@ fn parse_body_modifiers<'a>(
                     ParseError::new(ParseErrorKind::InvalidMetadata, base_pos + start)
                 })?;
                 let meta_str = &s[start..start + end];
-                let parsed = parse_metadata(meta_str, base_pos + start)?;
+                let parsed = parse_metadata_with(meta_str, base_pos + start, opts)?;
                 meta_range = Some(add_to_pool(meta_pool, &parsed, base_pos + start)?);
                 pos = start + end + 1;
                 phase = 3;
             }
+            _ if opts.allow_unknown_modifiers => {
+                let start = pos;
+                pos = scan_until_any(bytes, pos + 1, b"@^{");
+                let slot = slot_after(phase);
+                let raw = &s[start..pos];
+                unknown
+                    .push(UnknownModifier { raw, slot })
+                    .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, base_pos + start))?;
+            }
             _ => {
                 return Err(ParseError::new(
                     ParseErrorKind::InvalidModifier,
@@ -209,17 +288,33 @@ fn parse_body_modifiers<'a>(
     Ok((group, timestamp, meta_range))
 }
 
+/// Slot for an unknown modifier seen in modifier `phase` (0=@, 1=^, 2={, 3=done).
+fn slot_after(phase: u8) -> ModifierSlot {
+    match phase {
+        0 => ModifierSlot::Leading,
+        1 => ModifierSlot::AfterTimestamp,
+        2 => ModifierSlot::AfterGroup,
+        _ => ModifierSlot::AfterMeta,
+    }
+}
+
 /// Add metadata pairs to the shared pool and return the range.
+///
+/// `pos` is the start of the variable (or body metadata block) that owns the
+/// pairs. The block is checked against the remaining pool capacity before
+/// anything is appended, so an overflow is reported at the block that caused
+/// it and leaves the pool untouched.
 fn add_to_pool<'a>(
     pool: &mut InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
     pairs: &InlineVec<MetaPair<'a>, { crate::consts::MAX_META_PAIRS }>,
     pos: usize,
 ) -> Result<MetaRange, ParseError> {
-    let start = pool.len() as u16;
-    for pair in pairs.iter() {
-        pool.push(*pair)
-            .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, pos))?;
+    if pool.len() + pairs.len() > MAX_TOTAL_META {
+        return Err(ParseError::new(ParseErrorKind::TooManyItems, pos));
     }
+    let start = pool.len() as u16;
+    pool.try_extend_from_slice(pairs)
+        .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, pos))?;
     Ok(MetaRange {
         start,
         len: pairs.len() as u16,
@@ -241,6 +336,14 @@ fn scan_until_mod(bytes: &[u8], mut pos: usize) -> usize {
     pos
 }
 
+/// Scan forward while `pred` holds.
+fn scan_while(bytes: &[u8], mut pos: usize, pred: impl Fn(u8) -> bool) -> usize {
+    while pos < bytes.len() && pred(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
 /// Scan forward until one of the stop bytes.
 fn scan_until_any(bytes: &[u8], mut pos: usize, stops: &[u8]) -> usize {
     while pos < bytes.len() {
@@ -281,6 +384,10 @@ fn find_closing_bracket(bytes: &[u8], start: usize) -> Option<usize> {
             i += 2;
             continue;
         }
+        if let Some(end) = escape::quoted_span_end(bytes, i) {
+            i = end;
+            continue;
+        }
         if bytes[i] == b'[' {
             depth += 1;
         } else if bytes[i] == b']' {
@@ -294,13 +401,82 @@ fn find_closing_bracket(bytes: &[u8], start: usize) -> Option<usize> {
     None
 }
 
+/// Streaming iterator over a `;`-separated variable list.
+///
+/// Yields one [`ParsedVariable`] per non-empty segment without buffering, so
+/// it is not bound by `MAX_VARIABLES`. Each variable keeps its metadata in
+/// `meta_pairs` (there is no shared pool). Iteration stops after the first
+/// error.
+#[derive(Debug, Clone)]
+pub struct VariablesStream<'a> {
+    s: &'a str,
+    base_pos: usize,
+    pos: usize,
+    done: bool,
+}
+
+impl<'a> VariablesStream<'a> {
+    /// Stream the variables in `s` (the content between `[` and `]`).
+    #[must_use]
+    pub fn new(s: &'a str, base_pos: usize) -> Self {
+        Self {
+            s,
+            base_pos,
+            pos: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'a> Iterator for VariablesStream<'a> {
+    type Item = Result<ParsedVariable<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.s.as_bytes();
+        while !self.done {
+            let start = self.pos;
+            let mut i = start;
+            while i < bytes.len() && bytes[i] != b';' {
+                if let Some(end) = escape::quoted_span_end(bytes, i) {
+                    i = end;
+                    continue;
+                }
+                if bytes[i] == b'\\' && i + 1 < bytes.len() {
+                    i += 1;
+                }
+                i += 1;
+            }
+            if i >= bytes.len() {
+                self.done = true;
+            } else {
+                self.pos = i + 1;
+            }
+
+            let var_str = &self.s[start..i];
+            if var_str.is_empty() {
+                continue;
+            }
+            let result =
+                parse_variable_with(var_str, self.base_pos + start, &ParseOptions::default());
+            if result.is_err() {
+                self.done = true;
+            }
+            return Some(result);
+        }
+        None
+    }
+}
+
 /// Parse the variable list inside `[]`, splitting by `;`.
 fn parse_variable_list<'a>(
     s: &'a str,
     base_pos: usize,
     meta_pool: &mut InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
+    opts: &ParseOptions,
+    recover: &mut impl FnMut(ParseError) -> Result<(), ParseError>,
+    mut dropped: Option<&mut usize>,
 ) -> Result<InlineVec<Variable<'a>, MAX_VARIABLES>, ParseError> {
-    let mut variables = InlineVec::new();
+    let mut variables: InlineVec<Variable<'a>, MAX_VARIABLES> = InlineVec::new();
     let bytes = s.as_bytes();
     let mut start = 0;
     let mut i = 0;
@@ -312,17 +488,24 @@ fn parse_variable_list<'a>(
         if at_end || is_semi {
             let var_str = &s[start..i];
             if !var_str.is_empty() {
-                let parsed = parse_variable(var_str, base_pos + start)?;
-                let mut var = parsed.variable;
-
-                // Add metadata to pool if present
-                if let Some(ref pairs) = parsed.meta_pairs {
-                    var.meta = Some(add_to_pool(meta_pool, pairs, base_pos + start)?);
+                match parse_variable_with(var_str, base_pos + start, opts) {
+                    Ok(parsed) => {
+                        let pos = base_pos + start;
+                        match dropped.as_deref_mut() {
+                            // Truncating: count the overflow before its
+                            // metadata reaches the pool.
+                            Some(count) if variables.len() == variables.capacity() => *count += 1,
+                            Some(_) => {
+                                variables
+                                    .push_truncating(with_pooled_meta(parsed, meta_pool, pos)?);
+                            }
+                            None => variables
+                                .push(with_pooled_meta(parsed, meta_pool, pos)?)
+                                .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, pos))?,
+                        }
+                    }
+                    Err(e) => recover(e)?,
                 }
-
-                variables
-                    .push(var)
-                    .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, base_pos + start))?;
             }
             if at_end {
                 break;
@@ -336,6 +519,10 @@ fn parse_variable_list<'a>(
             i += 2;
             continue;
         }
+        if let Some(end) = escape::quoted_span_end(bytes, i) {
+            i = end;
+            continue;
+        }
 
         i += 1;
     }
@@ -343,6 +530,19 @@ fn parse_variable_list<'a>(
     Ok(variables)
 }
 
+/// Move a parsed variable's metadata into the pool and return the variable.
+fn with_pooled_meta<'a>(
+    parsed: ParsedVariable<'a>,
+    meta_pool: &mut InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
+    pos: usize,
+) -> Result<Variable<'a>, ParseError> {
+    let mut var = parsed.variable;
+    if let Some(ref pairs) = parsed.meta_pairs {
+        var.meta = Some(add_to_pool(meta_pool, pairs, pos)?);
+    }
+    Ok(var)
+}
+
 /// Validate that a string is all decimal digits (for timestamps).
 fn validate_digits(s: &str, pos: usize) -> Result<(), ParseError> {
     if s.is_empty() {
@@ -361,32 +561,33 @@ fn parse_hex_passthrough(data: &str, pos: usize) -> Result<PushBody<'_>, ParseEr
     if data.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidPassthrough, pos));
     }
-    if data.len() % 2 != 0 {
+    if !crate::codec::is_valid_hex(data) {
         return Err(ParseError::new(ParseErrorKind::InvalidPassthrough, pos));
     }
-    for &b in data.as_bytes() {
-        if !b.is_ascii_hexdigit() {
-            return Err(ParseError::new(ParseErrorKind::InvalidPassthrough, pos));
-        }
-    }
     Ok(PushBody::Passthrough(PassthroughBody {
         encoding: PassthroughEncoding::Hex,
         data,
     }))
 }
 
-/// Parse base64 passthrough.
-fn parse_base64_passthrough(data: &str, pos: usize) -> Result<PushBody<'_>, ParseError> {
+/// Parse base64 passthrough in the standard or URL-safe alphabet.
+fn parse_base64_passthrough(
+    data: &str,
+    pos: usize,
+    encoding: PassthroughEncoding,
+) -> Result<PushBody<'_>, ParseError> {
     if data.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidPassthrough, pos));
     }
+    let (c62, c63) = if encoding == PassthroughEncoding::Base64Url {
+        (b'-', b'_')
+    } else {
+        (b'+', b'/')
+    };
     for &b in data.as_bytes() {
-        if !(b.is_ascii_alphanumeric() || b == b'+' || b == b'/' || b == b'=') {
+        if !(b.is_ascii_alphanumeric() || b == c62 || b == c63 || b == b'=') {
             return Err(ParseError::new(ParseErrorKind::InvalidPassthrough, pos));
         }
     }
-    Ok(PushBody::Passthrough(PassthroughBody {
-        encoding: PassthroughEncoding::Base64,
-        data,
-    }))
+    Ok(PushBody::Passthrough(PassthroughBody { encoding, data }))
 }
@@ -1,9 +1,33 @@
-use crate::consts::{AUTH_HASH_LEN, MAX_UPLINK_FIELDS};
+use crate::consts::{AUTH_HASH_LEN, MAX_FRAME_SIZE, MAX_UPLINK_FIELDS};
 use crate::error::{ParseError, ParseErrorKind};
+use crate::escape;
 use crate::inline_vec::InlineVec;
 use crate::types::Method;
 use crate::validate;
 
+/// Reject frames containing a NUL byte or exceeding `MAX_FRAME_SIZE`.
+pub fn check_frame_bytes(input: &str) -> Result<(), ParseError> {
+    // NUL byte check
+    if input.as_bytes().contains(&0) {
+        return Err(ParseError::new(ParseErrorKind::NulByte, 0));
+    }
+
+    // Frame size check
+    if input.len() > MAX_FRAME_SIZE {
+        return Err(ParseError::new(ParseErrorKind::FrameTooLarge, 0));
+    }
+    Ok(())
+}
+
+/// Position of the field following `field`, which starts at `pos`.
+///
+/// Saturates instead of overflowing; frame size limits keep real positions far
+/// below `usize::MAX`, so a saturated value only ever appears in error reports.
+#[must_use]
+pub fn next_field_pos(pos: usize, field: &str) -> usize {
+    pos.saturating_add(field.len()).saturating_add(1)
+}
+
 /// Split a frame string by `|`, respecting `\|` escape sequences.
 /// Returns slices into the original string.
 #[must_use]
@@ -18,6 +42,10 @@ pub fn split_fields(input: &str) -> InlineVec<&str, MAX_UPLINK_FIELDS> {
             i += 2; // skip escape sequence
             continue;
         }
+        if let Some(end) = escape::quoted_span_end(bytes, i) {
+            i = end;
+            continue;
+        }
         if bytes[i] == b'|' {
             let _ = fields.push(&input[start..i]);
             start = i + 1;
@@ -60,7 +88,12 @@ pub fn parse_seq(s: &str, pos: usize) -> Result<u32, ParseError> {
 }
 
 /// Validate an auth hash: exactly 16 hex chars.
+///
+/// Whitespace is reported at its own offset; other problems at `pos`.
 pub fn validate_auth(s: &str, pos: usize) -> Result<(), ParseError> {
+    if let Some(offset) = validate::whitespace_offset(s) {
+        return Err(ParseError::new(ParseErrorKind::InvalidAuth, pos + offset));
+    }
     if s.len() != AUTH_HASH_LEN {
         return Err(ParseError::new(ParseErrorKind::InvalidAuth, pos));
     }
@@ -1,12 +1,17 @@
 pub mod ack;
 pub mod body;
 pub mod frame;
+pub mod options;
 pub mod variable;
 
+pub use body::VariablesStream;
+pub use options::{ParseOptions, SerialPolicy};
 pub use variable::ParsedVariable;
 
-use crate::consts::MAX_FRAME_SIZE;
+use crate::crc;
 use crate::error::{ParseError, ParseErrorKind};
+use crate::escape;
+use crate::inline_vec::InlineVec;
 use crate::types::{
     AckFrame, HeadlessFrame, MetadataBlock, Method, PullBody, PushBody, UplinkFrame,
 };
@@ -20,6 +25,23 @@ pub fn parse_push_body(s: &str) -> Result<PushBody<'_>, ParseError> {
     body::parse_push_body(s, 0)
 }
 
+/// Parse a PUSH body, keeping the first `MAX_VARIABLES` variables.
+///
+/// Instead of failing with `TooManyItems`, extra variables are validated and
+/// dropped; returns the body and how many were dropped. Any other error still
+/// fails the parse.
+pub fn parse_push_body_truncating(s: &str) -> Result<(PushBody<'_>, usize), ParseError> {
+    let mut dropped = 0;
+    let body = body::parse_push_body_recover(
+        s,
+        0,
+        &ParseOptions::default(),
+        &mut Err,
+        Some(&mut dropped),
+    )?;
+    Ok((body, dropped))
+}
+
 /// Parse a PULL body string independently (e.g., `[temperature;humidity]`).
 pub fn parse_pull_body(s: &str) -> Result<PullBody<'_>, ParseError> {
     body::parse_pull_body(s, 0)
@@ -30,6 +52,21 @@ pub fn parse_variable(s: &str) -> Result<ParsedVariable<'_>, ParseError> {
     variable::parse_variable(s, 0)
 }
 
+/// Stream the variables of a variable list one at a time (e.g., `a:=1;b:=2`).
+///
+/// Surrounding `[` `]` are accepted and skipped. Unlike [`parse_push_body`],
+/// the number of variables is not limited by `MAX_VARIABLES`.
+#[must_use]
+pub fn parse_variables_streaming(s: &str) -> VariablesStream<'_> {
+    match s
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+    {
+        Some(inner) => VariablesStream::new(inner, 1),
+        None => VariablesStream::new(s, 0),
+    }
+}
+
 /// Parse a metadata block string independently (content between `{` and `}`).
 pub fn parse_metadata(s: &str) -> Result<MetadataBlock<'_>, ParseError> {
     variable::parse_metadata(s, 0)
@@ -59,19 +96,22 @@ pub fn extract_serial(s: &str) -> Result<&str, ParseError> {
 // Full-frame parse functions
 // ---------------------------------------------------------------------------
 
-/// Parse a complete uplink frame (PUSH, PULL, or PING).
-///
-/// The input should NOT include a trailing `\n`.
-pub fn parse_uplink(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
-    // NUL byte check
-    if input.as_bytes().contains(&0) {
-        return Err(ParseError::new(ParseErrorKind::NulByte, 0));
-    }
+/// Header fields of an uplink frame plus the raw body field (if any).
+struct UplinkHeader<'a> {
+    method: Method,
+    seq: Option<u32>,
+    auth: &'a str,
+    serial: &'a str,
+    body: Option<&'a str>,
+    body_pos: usize,
+}
 
-    // Frame size check
-    if input.len() > MAX_FRAME_SIZE {
-        return Err(ParseError::new(ParseErrorKind::FrameTooLarge, 0));
-    }
+/// Parse everything up to (but not including) the body of an uplink frame.
+fn parse_uplink_header<'a>(
+    input: &'a str,
+    opts: &ParseOptions,
+) -> Result<UplinkHeader<'a>, ParseError> {
+    frame::check_frame_bytes(input)?;
 
     // Strip trailing \n if present (TCP transport)
     let input = input.strip_suffix('\n').unwrap_or(input);
@@ -86,39 +126,67 @@ pub fn parse_uplink(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
 
     // Determine if field[1] is a sequence counter
     let (seq, auth_idx) = if fields.len() > 1 && fields[1].starts_with('!') {
-        let seq_val = frame::parse_seq(fields[1], fields[0].len() + 1)?;
+        let seq_val = frame::parse_seq(fields[1], frame::next_field_pos(0, fields[0]))?;
         (Some(seq_val), 2)
     } else {
         (None, 1)
     };
 
     // Compute positions for error reporting
-    let auth_pos: usize = fields[..auth_idx].iter().map(|f| f.len() + 1).sum();
+    let auth_pos = fields[..auth_idx]
+        .iter()
+        .fold(0, |pos, f| frame::next_field_pos(pos, f));
 
     if fields.len() <= auth_idx {
         return Err(ParseError::new(ParseErrorKind::InvalidAuth, auth_pos));
     }
     let auth = fields[auth_idx];
-    frame::validate_auth(auth, auth_pos)?;
+    if !(auth.is_empty() && method == Method::Ping && opts.allow_empty_auth_on_ping) {
+        frame::validate_auth(auth, auth_pos)?;
+    }
 
     let serial_idx = auth_idx + 1;
-    let serial_pos = auth_pos + auth.len() + 1;
+    let serial_pos = frame::next_field_pos(auth_pos, auth);
 
     if fields.len() <= serial_idx {
         return Err(ParseError::new(ParseErrorKind::InvalidSerial, serial_pos));
     }
     let serial = frame::extract_serial(fields[serial_idx], serial_pos)?;
+    opts.serial_policy.check(serial, serial_pos)?;
 
     let body_idx = serial_idx + 1;
-    let body_pos = serial_pos + serial.len() + 1;
+    let body_pos = frame::next_field_pos(serial_pos, serial);
+
+    Ok(UplinkHeader {
+        method,
+        seq,
+        auth,
+        serial,
+        body: fields.get(body_idx).copied(),
+        body_pos,
+    })
+}
+
+/// Parse the body of an uplink frame according to its method.
+fn parse_uplink_body<'a>(
+    header: UplinkHeader<'a>,
+    opts: &ParseOptions,
+    recover: &mut impl FnMut(ParseError) -> Result<(), ParseError>,
+) -> Result<UplinkFrame<'a>, ParseError> {
+    let UplinkHeader {
+        method,
+        seq,
+        auth,
+        serial,
+        body,
+        body_pos,
+    } = header;
 
     match method {
         Method::Push => {
-            if fields.len() <= body_idx {
-                return Err(ParseError::new(ParseErrorKind::MissingBody, body_pos));
-            }
-            let body_str = fields[body_idx];
-            let push_body = body::parse_push_body(body_str, body_pos)?;
+            let body_str =
+                body.ok_or_else(|| ParseError::new(ParseErrorKind::MissingBody, body_pos))?;
+            let push_body = body::parse_push_body_recover(body_str, body_pos, opts, recover, None)?;
             Ok(UplinkFrame {
                 method,
                 seq,
@@ -129,11 +197,9 @@ pub fn parse_uplink(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
             })
         }
         Method::Pull => {
-            if fields.len() <= body_idx {
-                return Err(ParseError::new(ParseErrorKind::MissingBody, body_pos));
-            }
-            let body_str = fields[body_idx];
-            let pull_body = body::parse_pull_body(body_str, body_pos)?;
+            let body_str =
+                body.ok_or_else(|| ParseError::new(ParseErrorKind::MissingBody, body_pos))?;
+            let pull_body = body::parse_pull_body_recover(body_str, body_pos, recover)?;
             Ok(UplinkFrame {
                 method,
                 seq,
@@ -154,11 +220,180 @@ pub fn parse_uplink(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
     }
 }
 
+/// Parse a complete uplink frame (PUSH, PULL, or PING).
+///
+/// The input should NOT include a trailing `\n`.
+pub fn parse_uplink(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
+    parse_uplink_with(input, &ParseOptions::default())
+}
+
+/// Parse an uplink frame after stripping any trailing `\r` and `\n` bytes.
+///
+/// For serial links that terminate frames with `\r\n` (or repeat the
+/// terminator). [`parse_uplink`] stays byte-exact and strips only one `\n`.
+pub fn parse_uplink_trimmed(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
+    parse_uplink(input.trim_end_matches(['\r', '\n']))
+}
+
+/// Parse an uplink frame and report whether it ended with a `\n` terminator.
+///
+/// Pair with [`build_uplink_line`](crate::build::build_uplink_line) to
+/// reproduce the frame byte-for-byte, terminator included.
+pub fn parse_uplink_line(input: &str) -> Result<(UplinkFrame<'_>, bool), ParseError> {
+    let frame = parse_uplink(input)?;
+    Ok((frame, input.ends_with('\n')))
+}
+
+/// Parse the first uplink frame of `input`, which may be followed by more.
+///
+/// The frame runs to the first `\n` (or the end of input), split the same way
+/// as [`parse_ack_many`]. Returns the frame and the bytes consumed, terminator
+/// included, so `&input[consumed..]` starts at the next frame.
+pub fn parse_uplink_prefix(input: &str) -> Result<(UplinkFrame<'_>, usize), ParseError> {
+    let frame = frame_lines(input).next().unwrap_or(input);
+    let consumed = (frame.len() + 1).min(input.len());
+    Ok((parse_uplink(frame)?, consumed))
+}
+
+/// Split `input` into `\n`-terminated frames.
+///
+/// Every raw LF ends a frame, even after a backslash: a newline inside a
+/// frame is only ever carried as the two-byte escape `\n`.
+fn frame_lines(input: &str) -> core::str::Split<'_, char> {
+    input.split('\n')
+}
+
+/// Parse an uplink frame carrying a `*XXXX` CRC-16/CCITT suffix.
+///
+/// The suffix is verified against the bytes before the `*` and stripped before
+/// the frame is parsed. A missing, malformed, or mismatched suffix returns
+/// `ChecksumMismatch`. A trailing `\n` after the suffix is allowed.
+pub fn parse_uplink_with_crc(input: &str) -> Result<UplinkFrame<'_>, ParseError> {
+    let input = input.strip_suffix('\n').unwrap_or(input);
+    let bytes = input.as_bytes();
+    let Some(star) = bytes.len().checked_sub(crc::CRC_SUFFIX_LEN) else {
+        return Err(ParseError::new(
+            ParseErrorKind::ChecksumMismatch,
+            bytes.len(),
+        ));
+    };
+    let expected = crc::parse_suffix(&bytes[star..])
+        .ok_or_else(|| ParseError::new(ParseErrorKind::ChecksumMismatch, star))?;
+    if crc::crc16_ccitt(&bytes[..star]) != expected {
+        return Err(ParseError::new(ParseErrorKind::ChecksumMismatch, star));
+    }
+    // `*` is ASCII, so `star` is a char boundary.
+    parse_uplink(&input[..star])
+}
+
+/// Parse a complete uplink frame with custom [`ParseOptions`].
+pub fn parse_uplink_with<'a>(
+    input: &'a str,
+    opts: &ParseOptions,
+) -> Result<UplinkFrame<'a>, ParseError> {
+    let header = parse_uplink_header(input, opts)?;
+    parse_uplink_body(header, opts, &mut Err)
+}
+
+/// Parse an uplink frame, collecting every problem instead of stopping at the first.
+///
+/// Errors are appended to `errors` (extra errors are dropped once it is full).
+///
+/// Recoverable errors — the offending item is skipped and parsing continues:
+/// - a PUSH variable that fails to parse (`InvalidVariable`, `InvalidMetadata`, ...)
+/// - a PULL variable name that fails validation
+///
+/// Fatal errors — recorded, and `None` is returned:
+/// - anything in the header (NUL byte, size, method, seq, auth, serial)
+/// - body structure: missing body, variable block, body-level modifiers, passthrough
+/// - pool/capacity overflow (`TooManyItems`)
+/// - a body in which no variable survives
+pub fn parse_uplink_diagnostics<'a, const N: usize>(
+    input: &'a str,
+    errors: &mut InlineVec<ParseError, N>,
+) -> Option<UplinkFrame<'a>> {
+    let header = match parse_uplink_header(input, &ParseOptions::default()) {
+        Ok(h) => h,
+        Err(e) => {
+            let _ = errors.push(e);
+            return None;
+        }
+    };
+
+    let mut skipped_any = false;
+    let mut recover = |e: ParseError| {
+        skipped_any = true;
+        let _ = errors.push(e);
+        Ok(())
+    };
+    let result = parse_uplink_body(header, &ParseOptions::default(), &mut recover);
+
+    match result {
+        Ok(frame) => Some(frame),
+        Err(e) => {
+            // A block emptied by skipped variables is reported via those errors alone.
+            if !skipped_any || e.kind != ParseErrorKind::InvalidVariableBlock {
+                let _ = errors.push(e);
+            }
+            None
+        }
+    }
+}
+
+/// Extract the serial from an uplink frame without parsing its body.
+///
+/// Validates the method, optional sequence counter, auth hash and serial just
+/// like [`parse_uplink`], so routers can pick a device key cheaply and hand
+/// the full frame on. Body errors are not detected.
+pub fn peek_serial(input: &str) -> Result<&str, ParseError> {
+    parse_uplink_header(input, &ParseOptions::default()).map(|header| header.serial)
+}
+
+/// Extract the auth hash from an uplink frame without parsing its body.
+///
+/// Like [`peek_serial`], the whole header (including an optional `!seq`) is
+/// validated, letting an authenticator reject bad frames before the body is
+/// touched.
+pub fn peek_auth(input: &str) -> Result<&str, ParseError> {
+    parse_uplink_header(input, &ParseOptions::default()).map(|header| header.auth)
+}
+
+/// Check that an uplink frame is valid without returning its structure.
+///
+/// Runs exactly the same checks as [`parse_uplink`] and reports the same
+/// error, so it can serve as an admission filter. It is a thin wrapper: the
+/// frame is still parsed in full on the stack (filling every `InlineVec`)
+/// and then dropped, so it costs the same as [`parse_uplink`].
+pub fn validate_uplink(input: &str) -> Result<(), ParseError> {
+    parse_uplink(input).map(|_| ())
+}
+
 /// Parse an ACK (downlink) frame.
 pub fn parse_ack(input: &str) -> Result<AckFrame<'_>, ParseError> {
+    parse_ack_with(input, &ParseOptions::default())
+}
+
+/// Parse an ACK (downlink) frame with custom [`ParseOptions`].
+pub fn parse_ack_with<'a>(input: &'a str, opts: &ParseOptions) -> Result<AckFrame<'a>, ParseError> {
     // Strip trailing \n if present
     let input = input.strip_suffix('\n').unwrap_or(input);
-    ack::parse_ack(input)
+    ack::parse_ack_with(input, opts)
+}
+
+/// Parse a `\n`-separated batch of ACK frames into `out`.
+///
+/// Each line is parsed on its own, so a malformed line only fails its own slot
+/// and error positions are relative to the start of that line. Empty lines are
+/// skipped. Parsing stops once `out` is full; returns the number of slots
+/// written.
+pub fn parse_ack_many<'a>(input: &'a str, out: &mut [Result<AckFrame<'a>, ParseError>]) -> usize {
+    let lines = frame_lines(input).filter(|line| !line.is_empty());
+    let mut count = 0;
+    for (slot, line) in out.iter_mut().zip(lines) {
+        *slot = ack::parse_ack_with(line, &ParseOptions::default());
+        count += 1;
+    }
+    count
 }
 
 /// Parse an ACK inner frame from TagoTiP/S: `STATUS[|DETAIL]` (no `ACK|` prefix).
@@ -166,6 +401,14 @@ pub fn parse_ack_inner(input: &str) -> Result<AckFrame<'_>, ParseError> {
     ack::parse_ack_inner(input)
 }
 
+/// Parse an ACK inner frame with custom [`ParseOptions`].
+pub fn parse_ack_inner_with<'a>(
+    input: &'a str,
+    opts: &ParseOptions,
+) -> Result<AckFrame<'a>, ParseError> {
+    ack::parse_ack_inner_with(input, opts)
+}
+
 /// Parse a headless inner frame (for TagoTiP/S).
 /// The method comes from the envelope flags byte.
 ///
@@ -174,6 +417,15 @@ pub fn parse_ack_inner(input: &str) -> Result<AckFrame<'_>, ParseError> {
 /// - PULL: `SERIAL|[var1;var2;...]`
 /// - PING: `SERIAL`
 pub fn parse_headless(method: Method, input: &str) -> Result<HeadlessFrame<'_>, ParseError> {
+    parse_headless_with(method, input, &ParseOptions::default())
+}
+
+/// Parse a headless inner frame with custom [`ParseOptions`].
+pub fn parse_headless_with<'a>(
+    method: Method,
+    input: &'a str,
+    opts: &ParseOptions,
+) -> Result<HeadlessFrame<'a>, ParseError> {
     match method {
         Method::Push => {
             // Split by first unescaped `|`
@@ -181,8 +433,10 @@ pub fn parse_headless(method: Method, input: &str) -> Result<HeadlessFrame<'_>,
                 .ok_or_else(|| ParseError::new(ParseErrorKind::MissingBody, 0))?;
 
             let serial = frame::extract_serial(serial_str, 0)?;
-            let body_pos = serial_str.len() + 1;
-            let push_body = body::parse_push_body(body_str, body_pos)?;
+            opts.serial_policy.check(serial, 0)?;
+            let body_pos = frame::next_field_pos(0, serial_str);
+            let push_body =
+                body::parse_push_body_recover(body_str, body_pos, opts, &mut Err, None)?;
 
             Ok(HeadlessFrame {
                 serial,
@@ -195,7 +449,8 @@ pub fn parse_headless(method: Method, input: &str) -> Result<HeadlessFrame<'_>,
                 .ok_or_else(|| ParseError::new(ParseErrorKind::MissingBody, 0))?;
 
             let serial = frame::extract_serial(serial_str, 0)?;
-            let body_pos = serial_str.len() + 1;
+            opts.serial_policy.check(serial, 0)?;
+            let body_pos = frame::next_field_pos(0, serial_str);
             let pull_body = body::parse_pull_body(body_str, body_pos)?;
 
             Ok(HeadlessFrame {
@@ -206,6 +461,7 @@ pub fn parse_headless(method: Method, input: &str) -> Result<HeadlessFrame<'_>,
         }
         Method::Ping => {
             let serial = frame::extract_serial(input, 0)?;
+            opts.serial_policy.check(serial, 0)?;
             Ok(HeadlessFrame {
                 serial,
                 push_body: None,
@@ -224,6 +480,10 @@ fn split_first_pipe(s: &str) -> Option<(&str, &str)> {
             i += 2;
             continue;
         }
+        if let Some(end) = escape::quoted_span_end(bytes, i) {
+            i = end;
+            continue;
+        }
         if bytes[i] == b'|' {
             return Some((&s[..i], &s[i + 1..]));
         }
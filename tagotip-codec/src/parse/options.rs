@@ -0,0 +1,131 @@
+use crate::consts::MAX_SERIAL_LEN;
+use crate::error::{ParseError, ParseErrorKind};
+
+/// Options controlling how strictly frames are parsed.
+///
+/// `ParseOptions::default()` matches the behavior of the plain `parse_*`
+/// entry points; the `*_with` variants accept a custom set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent toggles, not a state machine
+pub struct ParseOptions {
+    /// Reject metadata blocks that repeat a key (e.g. `{a=1,a=2}`) with
+    /// `InvalidMetadata` at the position of the second occurrence.
+    pub reject_duplicate_meta_keys: bool,
+    /// Reject `ACK|PONG|...` with `InvalidAck`; PONG must not carry a detail.
+    /// When disabled the detail is returned as `AckDetail::Raw`.
+    pub reject_pong_detail: bool,
+    /// Reject timestamps (variable `@ts` and body-level `@ts`) whose value
+    /// does not fit in a `u64`, instead of letting `timestamp_u64()` return
+    /// `None` later.
+    pub reject_oversized_timestamps: bool,
+    /// Accept body-level `^group@timestamp` in addition to the spec order
+    /// `@timestamp^group`. Builders always emit the spec order.
+    pub allow_any_modifier_order: bool,
+    /// Accept an empty string value (`[status=]`) as `Value::String("")`.
+    pub allow_empty_string: bool,
+    /// Parse `ACK|ERR|a;b` as `AckDetail::Errors` with one entry per
+    /// `;`-separated code. Without it the whole detail is a single `Error`.
+    pub allow_error_list: bool,
+    /// Keep unrecognized body-level modifiers (e.g. `~tag`) in
+    /// `StructuredBody::unknown_modifiers` instead of rejecting them with
+    /// `InvalidModifier`. A body timestamp then ends at its last digit and a
+    /// body group at its last name character, so a new modifier may follow
+    /// either. Builders re-emit them after the known modifiers.
+    pub allow_unknown_modifiers: bool,
+    /// Accept an empty auth field on PING frames (`PING||serial`), returning
+    /// `auth == ""`. PUSH and PULL still require a valid auth hash.
+    ///
+    /// Such a PING carries no credential at all: anyone can send one for any
+    /// serial, so only enable this where an unauthenticated liveness signal
+    /// (e.g. during device bootstrap) is acceptable.
+    pub allow_empty_auth_on_ping: bool,
+    /// Extra restrictions on the device serial, applied after the spec check
+    /// (`[A-Za-z0-9_-]`, 1 to `MAX_SERIAL_LEN` bytes). The default adds none.
+    pub serial_policy: SerialPolicy,
+}
+
+impl ParseOptions {
+    /// Options with every strictness check enabled and no leniency.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            reject_duplicate_meta_keys: true,
+            reject_pong_detail: true,
+            reject_oversized_timestamps: true,
+            allow_any_modifier_order: false,
+            allow_empty_string: false,
+            allow_error_list: false,
+            allow_unknown_modifiers: false,
+            allow_empty_auth_on_ping: false,
+            serial_policy: SerialPolicy::default(),
+        }
+    }
+}
+
+/// Narrows the set of serials a parser accepts.
+///
+/// A policy can only tighten the spec grammar, never widen it: a serial that
+/// fails the spec check is rejected whatever the policy says. Violations are
+/// reported as `InvalidSerial` at the start of the serial field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // one toggle per character class
+pub struct SerialPolicy {
+    /// Accept `A-Z`.
+    pub allow_uppercase: bool,
+    /// Accept `a-z`.
+    pub allow_lowercase: bool,
+    /// Accept `0-9`.
+    pub allow_digits: bool,
+    /// Accept `-`.
+    pub allow_hyphen: bool,
+    /// Accept `_`.
+    pub allow_underscore: bool,
+    /// Minimum serial length in bytes.
+    pub min_len: usize,
+    /// Maximum serial length in bytes. Values above `MAX_SERIAL_LEN` have no
+    /// effect.
+    pub max_len: usize,
+}
+
+impl Default for SerialPolicy {
+    fn default() -> Self {
+        Self {
+            allow_uppercase: true,
+            allow_lowercase: true,
+            allow_digits: true,
+            allow_hyphen: true,
+            allow_underscore: true,
+            min_len: 1,
+            max_len: MAX_SERIAL_LEN,
+        }
+    }
+}
+
+impl SerialPolicy {
+    /// Check an already spec-valid `serial` against this policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidSerial` at `pos` if the length is out of range or a
+    /// character belongs to a disallowed class.
+    pub fn check(&self, serial: &str, pos: usize) -> Result<(), ParseError> {
+        let err = || ParseError::new(ParseErrorKind::InvalidSerial, pos);
+        if serial.len() < self.min_len || serial.len() > self.max_len {
+            return Err(err());
+        }
+        for &b in serial.as_bytes() {
+            let allowed = match b {
+                b'A'..=b'Z' => self.allow_uppercase,
+                b'a'..=b'z' => self.allow_lowercase,
+                b'0'..=b'9' => self.allow_digits,
+                b'-' => self.allow_hyphen,
+                b'_' => self.allow_underscore,
+                _ => false,
+            };
+            if !allowed {
+                return Err(err());
+            }
+        }
+        Ok(())
+    }
+}
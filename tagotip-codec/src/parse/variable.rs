@@ -1,16 +1,32 @@
 use crate::error::{ParseError, ParseErrorKind};
+use crate::escape;
 use crate::types::{MetaPair, MetadataBlock, Operator, Value, Variable};
 use crate::validate;
 
+use super::options::ParseOptions;
+
 /// Result of parsing a single variable — includes metadata pairs to be added to the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedVariable<'a> {
     pub variable: Variable<'a>,
     pub meta_pairs: Option<MetadataBlock<'a>>,
+    /// Byte range `(start, end)` of the variable in the parsed source.
+    #[cfg(feature = "source-spans")]
+    pub source_span: (usize, usize),
 }
 
 /// Parse a single variable string (e.g., `temperature:=32.5#C@1694567890000^group1{k=v}`).
 /// Returns the variable and its metadata pairs (to be added to the shared pool by the caller).
 pub fn parse_variable(s: &str, base_pos: usize) -> Result<ParsedVariable<'_>, ParseError> {
+    parse_variable_with(s, base_pos, &ParseOptions::default())
+}
+
+/// Parse a single variable string with custom [`ParseOptions`].
+pub fn parse_variable_with<'a>(
+    s: &'a str,
+    base_pos: usize,
+    opts: &ParseOptions,
+) -> Result<ParsedVariable<'a>, ParseError> {
     let bytes = s.as_bytes();
     let len = bytes.len();
 
@@ -31,7 +47,7 @@ pub fn parse_variable(s: &str, base_pos: usize) -> Result<ParsedVariable<'_>, Pa
     let value_end = scan_value(bytes, &mut pos);
     let value_str = &s[value_start..value_end];
 
-    let value = parse_value(value_str, operator, base_pos + value_start)?;
+    let value = parse_value(value_str, operator, base_pos + value_start, opts)?;
 
     // Parse optional suffixes in order: #unit @timestamp ^group {metadata}
     let mut unit = None;
@@ -62,6 +78,12 @@ pub fn parse_variable(s: &str, base_pos: usize) -> Result<ParsedVariable<'_>, Pa
         pos = scan_until_any(bytes, pos, b"^{");
         let ts = &s[start..pos];
         validate_timestamp(ts, base_pos + start)?;
+        if opts.reject_oversized_timestamps && !validate::digits_fit_u64(ts) {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidVariable,
+                base_pos + start,
+            ));
+        }
         timestamp = Some(ts);
     }
 
@@ -82,7 +104,7 @@ pub fn parse_variable(s: &str, base_pos: usize) -> Result<ParsedVariable<'_>, Pa
         let end = find_closing_brace(bytes, pos)
             .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidMetadata, base_pos + start))?;
         let meta_str = &s[start..end];
-        meta_pairs = Some(parse_metadata(meta_str, base_pos + start)?);
+        meta_pairs = Some(parse_metadata_with(meta_str, base_pos + start, opts)?);
         pos = end + 1; // skip }
     }
 
@@ -99,6 +121,8 @@ pub fn parse_variable(s: &str, base_pos: usize) -> Result<ParsedVariable<'_>, Pa
             meta: None, // caller sets this after adding to pool
         },
         meta_pairs,
+        #[cfg(feature = "source-spans")]
+        source_span: (base_pos, base_pos + len),
     })
 }
 
@@ -127,6 +151,11 @@ fn find_operator(bytes: &[u8], base_pos: usize) -> Result<(usize, usize, Operato
 }
 
 /// Scan the value portion of a variable, handling escape sequences.
+///
+/// The scanners below work on bytes but only stop on ASCII delimiters. UTF-8
+/// continuation bytes are never ASCII, so every returned index (a delimiter
+/// or `bytes.len()`) lies on a char boundary and is safe to slice at, even if
+/// an escape skip transiently lands inside a multi-byte character.
 fn scan_value(bytes: &[u8], pos: &mut usize) -> usize {
     while *pos < bytes.len() {
         let b = bytes[*pos];
@@ -134,6 +163,10 @@ fn scan_value(bytes: &[u8], pos: &mut usize) -> usize {
             *pos += 2;
             continue;
         }
+        if let Some(end) = escape::quoted_span_end(bytes, *pos) {
+            *pos = end;
+            continue;
+        }
         if b == b'#' || b == b'@' || b == b'^' || b == b'{' {
             return *pos;
         }
@@ -174,7 +207,12 @@ fn find_closing_brace(bytes: &[u8], start: usize) -> Option<usize> {
 }
 
 /// Parse the value string according to the operator type.
-fn parse_value(s: &str, op: Operator, pos: usize) -> Result<Value<'_>, ParseError> {
+fn parse_value<'a>(
+    s: &'a str,
+    op: Operator,
+    pos: usize,
+    opts: &ParseOptions,
+) -> Result<Value<'a>, ParseError> {
     match op {
         Operator::Number => {
             if s.is_empty() {
@@ -184,7 +222,9 @@ fn parse_value(s: &str, op: Operator, pos: usize) -> Result<Value<'_>, ParseErro
             Ok(Value::Number(s))
         }
         Operator::String => {
-            if s.is_empty() {
+            #[cfg(feature = "quoted-strings")]
+            let s = unquote(s, pos)?;
+            if s.is_empty() && !opts.allow_empty_string {
                 return Err(ParseError::new(ParseErrorKind::InvalidVariable, pos));
             }
             Ok(Value::String(s))
@@ -198,6 +238,20 @@ fn parse_value(s: &str, op: Operator, pos: usize) -> Result<Value<'_>, ParseErro
     }
 }
 
+/// Strip the quotes from a quoted string value, leaving `\"` escapes in
+/// place. Unquoted values pass through; text after the closing quote or a
+/// missing closing quote is an error.
+#[cfg(feature = "quoted-strings")]
+fn unquote(s: &str, pos: usize) -> Result<&str, ParseError> {
+    if !s.starts_with('"') {
+        return Ok(s);
+    }
+    match escape::closing_quote(s.as_bytes(), 1) {
+        Some(end) if end + 1 == s.len() => Ok(&s[1..end]),
+        _ => Err(ParseError::new(ParseErrorKind::InvalidVariable, pos)),
+    }
+}
+
 /// Parse a location value: `lat,lng` or `lat,lng,alt`.
 fn parse_location(s: &str, pos: usize) -> Result<Value<'_>, ParseError> {
     let mut parts = s.splitn(4, ',');
@@ -254,6 +308,15 @@ fn validate_timestamp(s: &str, pos: usize) -> Result<(), ParseError> {
 
 /// Parse a metadata block string (content between `{` and `}`).
 pub fn parse_metadata(s: &str, base_pos: usize) -> Result<MetadataBlock<'_>, ParseError> {
+    parse_metadata_with(s, base_pos, &ParseOptions::default())
+}
+
+/// Parse a metadata block string with custom [`ParseOptions`].
+pub fn parse_metadata_with<'a>(
+    s: &'a str,
+    base_pos: usize,
+    opts: &ParseOptions,
+) -> Result<MetadataBlock<'a>, ParseError> {
     let mut block = MetadataBlock::new();
 
     if s.is_empty() {
@@ -272,6 +335,12 @@ pub fn parse_metadata(s: &str, base_pos: usize) -> Result<MetadataBlock<'_>, Par
             let pair_str = &s[start..i];
             if !pair_str.is_empty() {
                 let pair = parse_meta_pair(pair_str, base_pos + start)?;
+                if opts.reject_duplicate_meta_keys && block.iter().any(|p| p.key == pair.key) {
+                    return Err(ParseError::new(
+                        ParseErrorKind::InvalidMetadata,
+                        base_pos + start,
+                    ));
+                }
                 block
                     .push(pair)
                     .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, base_pos + start))?;
@@ -0,0 +1,61 @@
+//! Helpers for downstream test suites (enabled by the `test-util` feature).
+
+use crate::build::build_uplink;
+use crate::consts::MAX_FRAME_SIZE;
+use crate::error::ParseError;
+use crate::parse::parse_uplink;
+
+/// Parse `input`, build it back, and report whether the bytes are identical.
+///
+/// Returns `Err` if `input` does not parse. A frame that parses but builds to
+/// different bytes (or cannot be built) returns `Ok(false)`. A trailing `\n`
+/// is ignored, since builders never emit one.
+pub fn roundtrip_uplink(input: &str) -> Result<bool, ParseError> {
+    let frame = parse_uplink(input)?;
+    let mut scratch = [0u8; MAX_FRAME_SIZE];
+    let Ok(n) = build_uplink(&frame, &mut scratch) else {
+        return Ok(false);
+    };
+    let expected = input.strip_suffix('\n').unwrap_or(input);
+    Ok(&scratch[..n] == expected.as_bytes())
+}
+
+/// Minimal xorshift PRNG so fuzz-style tests stay dependency-free and
+/// deterministic.
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// Create a generator from `seed`.
+    ///
+    /// Xorshift never leaves the all-zero state, so a zero seed is replaced by
+    /// a fixed non-zero one.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    /// Next raw 64-bit output.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform-ish value in `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "XorShift::below called with n = 0");
+        (self.next_u64() % n as u64) as usize
+    }
+}
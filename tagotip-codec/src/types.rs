@@ -1,7 +1,16 @@
-use crate::consts::{MAX_META_PAIRS, MAX_VARIABLES};
+use core::fmt;
+
+use crate::consts::{MAX_META_PAIRS, MAX_UNKNOWN_MODIFIERS, MAX_VARIABLES};
+use crate::error::{BuildError, ParseError, ParseErrorKind};
 use crate::inline_vec::InlineVec;
+use crate::validate;
 
 /// Maximum total metadata pairs across all variables + body-level in a single frame.
+///
+/// Each `{}` block is bounded separately by [`MAX_META_PAIRS`], but the blocks
+/// share this pool, so a frame with many metadata-heavy variables can hit this
+/// limit first. The parser then fails with `TooManyItems` at the variable
+/// whose block would overflow the pool.
 pub const MAX_TOTAL_META: usize = 512;
 
 /// Uplink method.
@@ -31,6 +40,7 @@ pub enum Value<'a> {
     /// Raw number string (not parsed to f64 — avoids libm dependency in `no_std`).
     Number(&'a str),
     /// Raw string value (may contain escape sequences; use `unescape_into()` to decode).
+    /// Any UTF-8 is permitted, including non-ASCII characters.
     String(&'a str),
     /// Boolean value.
     Boolean(bool),
@@ -49,6 +59,54 @@ pub struct MetaPair<'a> {
     pub value: &'a str,
 }
 
+/// A device serial that has passed `validate_serial`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Serial<'a>(&'a str);
+
+impl<'a> Serial<'a> {
+    /// Validate `s` as a serial (`[A-Za-z0-9_-]`, 1 to `MAX_SERIAL_LEN` bytes).
+    pub fn parse(s: &'a str) -> Result<Self, ParseError> {
+        validate::validate_serial(s, 0)?;
+        Ok(Self(s))
+    }
+
+    /// The validated serial.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl fmt::Display for Serial<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// A frame authorization hash (16 hex chars) that has passed `validate_auth`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AuthToken<'a>(&'a str);
+
+impl<'a> AuthToken<'a> {
+    /// Validate `s` as an authorization hash.
+    pub fn parse(s: &'a str) -> Result<Self, ParseError> {
+        crate::parse::validate_auth(s)?;
+        Ok(Self(s))
+    }
+
+    /// The validated authorization hash.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl fmt::Display for AuthToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
 /// Index range into a shared metadata pool.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MetaRange {
@@ -72,7 +130,76 @@ pub struct Variable<'a> {
     pub meta: Option<MetaRange>,
 }
 
-impl Variable<'_> {
+impl<'a> Variable<'a> {
+    /// Create a number variable (`name:=value[#unit]`), validated like the parser would.
+    pub fn new_number(
+        name: &'a str,
+        value: &'a str,
+        unit: Option<&'a str>,
+    ) -> Result<Self, BuildError> {
+        validate::validate_number(value, 0).map_err(|_| BuildError::invalid_input())?;
+        Self::validated(name, Operator::Number, Value::Number(value), unit)
+    }
+
+    /// Create a string variable (`name=value[#unit]`).
+    ///
+    /// `value` is the wire form: structural characters must already be escaped
+    /// (see [`crate::escape::escape_into`]).
+    pub fn new_string(
+        name: &'a str,
+        value: &'a str,
+        unit: Option<&'a str>,
+    ) -> Result<Self, BuildError> {
+        if value.is_empty() || !is_escaped_value(value) {
+            return Err(BuildError::invalid_input());
+        }
+        Self::validated(name, Operator::String, Value::String(value), unit)
+    }
+
+    /// Create a boolean variable (`name?=true|false`).
+    pub fn new_boolean(name: &'a str, value: bool) -> Result<Self, BuildError> {
+        Self::validated(name, Operator::Boolean, Value::Boolean(value), None)
+    }
+
+    /// Create a location variable (`name@=lat,lng[,alt]`). Locations carry no unit.
+    pub fn new_location(
+        name: &'a str,
+        lat: &'a str,
+        lng: &'a str,
+        alt: Option<&'a str>,
+    ) -> Result<Self, BuildError> {
+        for n in [Some(lat), Some(lng), alt].into_iter().flatten() {
+            validate::validate_number(n, 0).map_err(|_| BuildError::invalid_input())?;
+        }
+        Self::validated(
+            name,
+            Operator::Location,
+            Value::Location { lat, lng, alt },
+            None,
+        )
+    }
+
+    fn validated(
+        name: &'a str,
+        operator: Operator,
+        value: Value<'a>,
+        unit: Option<&'a str>,
+    ) -> Result<Self, BuildError> {
+        validate::validate_varname(name, 0).map_err(|_| BuildError::invalid_input())?;
+        if let Some(u) = unit {
+            validate::validate_unit(u, 0).map_err(|_| BuildError::invalid_input())?;
+        }
+        Ok(Self {
+            name,
+            operator,
+            value,
+            unit,
+            timestamp: None,
+            group: None,
+            meta: None,
+        })
+    }
+
     /// Parse the timestamp suffix as a u64, if present.
     pub fn timestamp_u64(&self) -> Option<u64> {
         self.timestamp.and_then(parse_u64)
@@ -84,6 +211,8 @@ impl Variable<'_> {
 pub enum PassthroughEncoding {
     Hex,
     Base64,
+    /// URL-safe base64 (`-` and `_` in place of `+` and `/`), prefix `>u`.
+    Base64Url,
 }
 
 /// Passthrough body data.
@@ -93,6 +222,20 @@ pub struct PassthroughBody<'a> {
     pub data: &'a str,
 }
 
+impl PassthroughBody<'_> {
+    /// Decode the payload into `out`, returning the number of raw bytes.
+    ///
+    /// Returns `None` if the data is not valid for its encoding or `out` is
+    /// too small.
+    pub fn decode(&self, out: &mut [u8]) -> Option<usize> {
+        match self.encoding {
+            PassthroughEncoding::Hex => crate::codec::hex_decode(self.data, out),
+            PassthroughEncoding::Base64 => crate::codec::base64_decode(self.data, out),
+            PassthroughEncoding::Base64Url => crate::codec::base64url_decode(self.data, out),
+        }
+    }
+}
+
 /// Structured PUSH body (body-level modifiers + variable list).
 /// Metadata for both body-level and variable-level is stored in `meta_pool`.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -103,6 +246,31 @@ pub struct StructuredBody<'a> {
     pub variables: InlineVec<Variable<'a>, MAX_VARIABLES>,
     /// Shared metadata pool. Variables and body-level metadata reference ranges within this pool.
     pub meta_pool: InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
+    /// Unrecognized body-level modifiers, in input order. Only populated with
+    /// `ParseOptions::allow_unknown_modifiers`.
+    pub unknown_modifiers: InlineVec<UnknownModifier<'a>, MAX_UNKNOWN_MODIFIERS>,
+}
+
+/// An unrecognized body-level modifier kept by `ParseOptions::allow_unknown_modifiers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownModifier<'a> {
+    /// Raw text, including the leading character.
+    pub raw: &'a str,
+    /// Where it sat relative to the known modifiers; the builder writes it back there.
+    pub slot: ModifierSlot,
+}
+
+/// Position of an unknown body modifier relative to `@ts^group{meta}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierSlot {
+    /// Before any known modifier.
+    Leading,
+    /// After `@ts`.
+    AfterTimestamp,
+    /// After `^group`.
+    AfterGroup,
+    /// After `{meta}`.
+    AfterMeta,
 }
 
 impl<'a> StructuredBody<'a> {
@@ -131,6 +299,152 @@ impl<'a> StructuredBody<'a> {
             None => &[],
         }
     }
+
+    /// Every metadata pair in the body, tagged with its owner: `None` for
+    /// body-level metadata, `Some(i)` for `variables[i]`.
+    ///
+    /// Body-level pairs come first, then each variable's in variable order.
+    pub fn all_metadata(&self) -> impl Iterator<Item = (Option<usize>, &MetaPair<'a>)> + '_ {
+        let body = self.body_metadata().iter().map(|m| (None, m));
+        let vars = self.variables.iter().enumerate().flat_map(move |(i, var)| {
+            self.variable_metadata(var)
+                .iter()
+                .map(move |m| (Some(i), m))
+        });
+        body.chain(vars)
+    }
+
+    /// The group that applies to `var`: its own `^group`, else the body default.
+    #[must_use]
+    pub fn effective_group(&self, var: &Variable<'a>) -> Option<&'a str> {
+        var.group.or(self.group)
+    }
+
+    /// The timestamp that applies to `var`: its own `@ts`, else the body default.
+    #[must_use]
+    pub fn effective_timestamp(&self, var: &Variable<'a>) -> Option<&'a str> {
+        var.timestamp.or(self.timestamp)
+    }
+
+    /// Whether the body sets a default group or timestamp for its variables.
+    #[must_use]
+    pub fn has_body_defaults(&self) -> bool {
+        self.group.is_some() || self.timestamp.is_some()
+    }
+
+    /// Push the body-level group and timestamp down into every variable that
+    /// does not set its own, then clear them from the body.
+    ///
+    /// This is lossy: the rebuilt frame carries per-variable modifiers and it
+    /// is no longer possible to tell which values were inherited. Body-level
+    /// metadata is left in place.
+    pub fn resolve_defaults(&mut self) {
+        let (group, timestamp) = (self.group.take(), self.timestamp.take());
+        for var in self.variables.as_mut_slice() {
+            var.group = var.group.or(group);
+            var.timestamp = var.timestamp.or(timestamp);
+        }
+    }
+
+    /// Compare two bodies ignoring metadata order and `meta_pool` layout.
+    ///
+    /// Variables are compared in order; each metadata block (body-level and
+    /// per-variable) is compared as an unordered set of pairs.
+    #[must_use]
+    pub fn semantic_eq(&self, other: &StructuredBody<'_>) -> bool {
+        self.group == other.group
+            && self.timestamp == other.timestamp
+            && self.unknown_modifiers == other.unknown_modifiers
+            && same_meta_set(self.body_metadata(), other.body_metadata())
+            && self.variables.len() == other.variables.len()
+            && self
+                .variables
+                .iter()
+                .zip(other.variables.iter())
+                .all(|(a, b)| {
+                    a.name == b.name
+                        && a.operator == b.operator
+                        && a.value == b.value
+                        && a.unit == b.unit
+                        && a.timestamp == b.timestamp
+                        && a.group == b.group
+                        && same_meta_set(self.variable_metadata(a), other.variable_metadata(b))
+                })
+    }
+
+    /// Remove the variable at `index` along with its metadata.
+    ///
+    /// The variable's pairs are dropped from `meta_pool` and every range
+    /// after them (other variables and `body_meta`) is shifted down, so the
+    /// remaining metadata still resolves. Assumes ranges do not overlap, as
+    /// in frames produced by the parser or [`PushFrameBuilder`](crate::build::PushFrameBuilder).
+    /// The removed variable is returned with `meta` cleared, or `None` if
+    /// `index` is out of bounds.
+    pub fn remove_variable(&mut self, index: usize) -> Option<Variable<'a>> {
+        let mut var = self.variables.remove(index)?;
+        if let Some(removed) = var.meta.take() {
+            for _ in 0..removed.len {
+                self.meta_pool.remove(usize::from(removed.start));
+            }
+            let ranges = self
+                .variables
+                .as_mut_slice()
+                .iter_mut()
+                .filter_map(|v| v.meta.as_mut())
+                .chain(self.body_meta.as_mut());
+            for range in ranges {
+                if range.start > removed.start {
+                    range.start -= removed.len;
+                }
+            }
+        }
+        Some(var)
+    }
+
+    /// Append `other`'s variables and their metadata to this body.
+    ///
+    /// Metadata pairs are copied into this body's pool and each variable's
+    /// `MetaRange` is rebased onto it. Body-level modifiers of `other`
+    /// (group, timestamp, metadata) are not merged. Fails with `TooManyItems`
+    /// (position 0) if the variables or the pool would overflow, leaving
+    /// `self` unchanged.
+    pub fn try_append(&mut self, other: &StructuredBody<'a>) -> Result<(), ParseError> {
+        let too_many = || ParseError::new(ParseErrorKind::TooManyItems, 0);
+        let pairs: usize = other
+            .variables
+            .iter()
+            .map(|v| other.variable_metadata(v).len())
+            .sum();
+        if self.variables.len() + other.variables.len() > MAX_VARIABLES
+            || self.meta_pool.len() + pairs > MAX_TOTAL_META
+        {
+            return Err(too_many());
+        }
+        for (var, meta) in other.variables_iter() {
+            let mut var = *var;
+            var.meta = if meta.is_empty() {
+                None
+            } else {
+                let start = u16::try_from(self.meta_pool.len()).map_err(|_| too_many())?;
+                self.meta_pool
+                    .try_extend_from_slice(meta)
+                    .map_err(|_| too_many())?;
+                Some(MetaRange {
+                    start,
+                    len: u16::try_from(meta.len()).map_err(|_| too_many())?,
+                })
+            };
+            self.variables.push(var).map_err(|_| too_many())?;
+        }
+        Ok(())
+    }
+
+    /// Iterate over the variables together with their metadata pairs.
+    pub fn variables_iter(&self) -> impl Iterator<Item = (&Variable<'a>, &[MetaPair<'a>])> + '_ {
+        self.variables
+            .iter()
+            .map(move |var| (var, self.variable_metadata(var)))
+    }
 }
 
 /// PUSH body — either structured or passthrough.
@@ -141,12 +455,67 @@ pub enum PushBody<'a> {
     Passthrough(PassthroughBody<'a>),
 }
 
+impl PushBody<'_> {
+    /// Compare two bodies ignoring metadata order and `meta_pool` layout.
+    #[must_use]
+    pub fn semantic_eq(&self, other: &PushBody<'_>) -> bool {
+        match (self, other) {
+            (PushBody::Structured(a), PushBody::Structured(b)) => a.semantic_eq(b),
+            (PushBody::Passthrough(a), PushBody::Passthrough(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
 /// PULL body: list of variable names to retrieve.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PullBody<'a> {
     pub variables: InlineVec<&'a str, MAX_VARIABLES>,
 }
 
+impl<'a> PullBody<'a> {
+    /// Build a PULL body from variable names, validating each like the parser.
+    ///
+    /// Errors carry the index of the offending name as their position: an
+    /// invalid name is `InvalidField`, a name past `MAX_VARIABLES` is
+    /// `TooManyItems`, and an empty list is `InvalidVariableBlock`.
+    pub fn from_names(names: &[&'a str]) -> Result<Self, ParseError> {
+        if names.is_empty() {
+            return Err(ParseError::new(ParseErrorKind::InvalidVariableBlock, 0));
+        }
+        let mut variables = InlineVec::new();
+        for (i, &name) in names.iter().enumerate() {
+            validate::validate_varname(name, i)?;
+            variables
+                .push(name)
+                .map_err(|_| ParseError::new(ParseErrorKind::TooManyItems, i))?;
+        }
+        Ok(Self { variables })
+    }
+
+    /// Whether `name` is among the requested variables.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.variables.iter().any(|v| *v == name)
+    }
+
+    /// Remove repeated names in place, keeping the first occurrence of each.
+    ///
+    /// The protocol gives duplicates no extra meaning: asking for a variable
+    /// twice returns the same value, so deduplicating is always safe.
+    pub fn dedup(&mut self) {
+        let names = self.variables.as_mut_slice();
+        let mut kept = 0;
+        for i in 0..names.len() {
+            if !names[..kept].contains(&names[i]) {
+                names[kept] = names[i];
+                kept += 1;
+            }
+        }
+        self.variables.truncate(kept);
+    }
+}
+
 /// A fully parsed uplink frame.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UplinkFrame<'a> {
@@ -158,6 +527,128 @@ pub struct UplinkFrame<'a> {
     pub pull_body: Option<PullBody<'a>>,
 }
 
+impl UplinkFrame<'_> {
+    /// Compare two frames ignoring metadata order and `meta_pool` layout.
+    ///
+    /// Useful for deduplication, where `==` would treat `{a=1,b=2}` and
+    /// `{b=2,a=1}` as different frames.
+    #[must_use]
+    pub fn semantic_eq(&self, other: &UplinkFrame<'_>) -> bool {
+        self.method == other.method
+            && self.seq == other.seq
+            && self.auth == other.auth
+            && self.serial == other.serial
+            && self.pull_body == other.pull_body
+            && match (&self.push_body, &other.push_body) {
+                (Some(a), Some(b)) => a.semantic_eq(b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+
+    /// Whether the frame carried a `!seq` counter and so must be acknowledged
+    /// with that same seq. True for `!0` too; only an absent counter returns
+    /// false.
+    #[must_use]
+    pub fn requires_seq_ack(&self) -> bool {
+        self.seq.is_some()
+    }
+}
+
+/// Human-readable, indented view for debugging and logs (not wire format).
+///
+/// Metadata ranges are resolved into `key=value` pairs, shown on their own
+/// line under the body or variable they belong to:
+///
+/// ```text
+/// PUSH !7 sensor-01 (auth 4deedd7bab8817ec)
+///   group: g1
+///   meta: site=lab
+///   temp := 21.5 #C @1700000000
+///     meta: source=dht22
+/// ```
+impl fmt::Display for UplinkFrame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method = match self.method {
+            Method::Push => "PUSH",
+            Method::Pull => "PULL",
+            Method::Ping => "PING",
+        };
+        f.write_str(method)?;
+        if let Some(seq) = self.seq {
+            write!(f, " !{seq}")?;
+        }
+        write!(f, " {} (auth {})", self.serial, self.auth)?;
+
+        match &self.push_body {
+            Some(PushBody::Structured(body)) => {
+                if let Some(group) = body.group {
+                    write!(f, "\n  group: {group}")?;
+                }
+                if let Some(ts) = body.timestamp {
+                    write!(f, "\n  timestamp: {ts}")?;
+                }
+                write_meta_line(f, "  ", body.body_metadata())?;
+                for (var, meta) in body.variables_iter() {
+                    write!(f, "\n  {}", var.name)?;
+                    match var.value {
+                        Value::Number(n) => write!(f, " := {n}")?,
+                        Value::String(s) => write!(f, " = {s}")?,
+                        Value::Boolean(b) => write!(f, " ?= {b}")?,
+                        Value::Location { lat, lng, alt } => {
+                            write!(f, " @= {lat},{lng}")?;
+                            if let Some(alt) = alt {
+                                write!(f, ",{alt}")?;
+                            }
+                        }
+                    }
+                    if let Some(unit) = var.unit {
+                        write!(f, " #{unit}")?;
+                    }
+                    if let Some(ts) = var.timestamp {
+                        write!(f, " @{ts}")?;
+                    }
+                    if let Some(group) = var.group {
+                        write!(f, " ^{group}")?;
+                    }
+                    write_meta_line(f, "    ", meta)?;
+                }
+            }
+            Some(PushBody::Passthrough(p)) => {
+                let encoding = match p.encoding {
+                    PassthroughEncoding::Hex => "hex",
+                    PassthroughEncoding::Base64 => "base64",
+                    PassthroughEncoding::Base64Url => "base64url",
+                };
+                write!(f, "\n  passthrough ({encoding}): {}", p.data)?;
+            }
+            None => {}
+        }
+
+        if let Some(pull) = &self.pull_body {
+            f.write_str("\n  pull:")?;
+            for (i, name) in pull.variables.iter().enumerate() {
+                f.write_str(if i == 0 { " " } else { ", " })?;
+                f.write_str(name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write `meta: k=v, k=v` on a new line, or nothing for an empty block.
+fn write_meta_line(f: &mut fmt::Formatter<'_>, indent: &str, meta: &[MetaPair<'_>]) -> fmt::Result {
+    for (i, pair) in meta.iter().enumerate() {
+        if i == 0 {
+            write!(f, "\n{indent}meta: ")?;
+        } else {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}={}", pair.key, pair.value)?;
+    }
+    Ok(())
+}
+
 /// A headless inner frame (for TagoTiP/S). No method/auth — those come from the envelope.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HeadlessFrame<'a> {
@@ -204,10 +695,50 @@ pub enum AckDetail<'a> {
     Command(&'a str),
     /// Error code + raw text.
     Error { code: ErrorCode, text: &'a str },
+    /// Several errors in one ACK, written `ERR|code;code;...`.
+    ///
+    /// Only produced when `ParseOptions::allow_error_list` is set and the
+    /// detail contains a `;`; a single code always parses as `Error`.
+    Errors(AckErrorList<'a>),
     /// Raw detail text that doesn't match the above patterns.
     Raw(&'a str),
 }
 
+/// A `;`-separated `ERR` list (`code;code;...`), borrowed from the frame.
+///
+/// Entries are split on demand by [`iter`](Self::iter), so the list costs no
+/// more than the `&str` it wraps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AckErrorList<'a>(&'a str);
+
+impl<'a> AckErrorList<'a> {
+    /// Validate `s` as an error list: one or more non-empty entries separated
+    /// by `;`. The error position is the byte offset of the empty entry.
+    pub fn parse(s: &'a str) -> Result<Self, ParseError> {
+        let mut offset = 0;
+        for text in s.split(';') {
+            if text.is_empty() {
+                return Err(ParseError::new(ParseErrorKind::InvalidAck, offset));
+            }
+            offset += text.len() + 1;
+        }
+        Ok(Self(s))
+    }
+
+    /// The list as written, e.g. `invalid_seq;rate_limited`.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// Each entry's code and text, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (ErrorCode, &'a str)> + 'a {
+        self.0
+            .split(';')
+            .map(|text| (ErrorCode::from_wire(text), text))
+    }
+}
+
 /// A parsed ACK (downlink) frame.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AckFrame<'a> {
@@ -216,6 +747,151 @@ pub struct AckFrame<'a> {
     pub detail: Option<AckDetail<'a>>,
 }
 
+impl<'a> AckDetail<'a> {
+    /// Error detail that keeps `code` but carries custom `text`, e.g. when a
+    /// proxy rewrites the message forwarded to a device.
+    ///
+    /// `build_ack` emits `text`; use `build_ack_with` and
+    /// `AckErrorText::Canonical` to emit the code's canonical text instead.
+    #[must_use]
+    pub fn error_with_text(code: ErrorCode, text: &'a str) -> Self {
+        AckDetail::Error { code, text }
+    }
+}
+
+impl ErrorCode {
+    /// Map `ERR` detail text to its spec code; anything else is `Unknown`.
+    pub(crate) fn from_wire(s: &str) -> Self {
+        match s {
+            "invalid_token" => ErrorCode::InvalidToken,
+            "invalid_method" => ErrorCode::InvalidMethod,
+            "invalid_payload" => ErrorCode::InvalidPayload,
+            "invalid_seq" => ErrorCode::InvalidSeq,
+            "device_not_found" => ErrorCode::DeviceNotFound,
+            "variable_not_found" => ErrorCode::VariableNotFound,
+            "rate_limited" => ErrorCode::RateLimited,
+            "auth_failed" => ErrorCode::AuthFailed,
+            "unsupported_version" => ErrorCode::UnsupportedVersion,
+            "payload_too_large" => ErrorCode::PayloadTooLarge,
+            "server_error" => ErrorCode::ServerError,
+            _ => ErrorCode::Unknown,
+        }
+    }
+
+    /// Every variant, in declaration order (`Unknown` last).
+    #[must_use]
+    pub fn all() -> &'static [ErrorCode] {
+        &[
+            ErrorCode::InvalidToken,
+            ErrorCode::InvalidMethod,
+            ErrorCode::InvalidPayload,
+            ErrorCode::InvalidSeq,
+            ErrorCode::DeviceNotFound,
+            ErrorCode::VariableNotFound,
+            ErrorCode::RateLimited,
+            ErrorCode::AuthFailed,
+            ErrorCode::UnsupportedVersion,
+            ErrorCode::PayloadTooLarge,
+            ErrorCode::ServerError,
+            ErrorCode::Unknown,
+        ]
+    }
+
+    /// Canonical wire text for this code, as it appears after `ERR|`.
+    ///
+    /// `None` for `Unknown`, which stands for any unrecognized text and has
+    /// no canonical form of its own.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            ErrorCode::InvalidToken => Some("invalid_token"),
+            ErrorCode::InvalidMethod => Some("invalid_method"),
+            ErrorCode::InvalidPayload => Some("invalid_payload"),
+            ErrorCode::InvalidSeq => Some("invalid_seq"),
+            ErrorCode::DeviceNotFound => Some("device_not_found"),
+            ErrorCode::VariableNotFound => Some("variable_not_found"),
+            ErrorCode::RateLimited => Some("rate_limited"),
+            ErrorCode::AuthFailed => Some("auth_failed"),
+            ErrorCode::UnsupportedVersion => Some("unsupported_version"),
+            ErrorCode::PayloadTooLarge => Some("payload_too_large"),
+            ErrorCode::ServerError => Some("server_error"),
+            ErrorCode::Unknown => None,
+        }
+    }
+}
+
+impl<'a> AckFrame<'a> {
+    /// `OK` acknowledgement carrying the number of accepted data points.
+    #[must_use]
+    pub fn ok_count(seq: Option<u32>, n: u32) -> Self {
+        Self {
+            seq,
+            status: AckStatus::Ok,
+            detail: Some(AckDetail::Count(n)),
+        }
+    }
+
+    /// `PONG` reply to a PING.
+    #[must_use]
+    pub fn pong(seq: Option<u32>) -> Self {
+        Self {
+            seq,
+            status: AckStatus::Pong,
+            detail: None,
+        }
+    }
+
+    /// `ERR` acknowledgement whose text is derived from `code`.
+    ///
+    /// `ErrorCode::Unknown` has no canonical text and gets an empty one; use
+    /// [`AckDetail::error_with_text`] to carry the original text instead.
+    #[must_use]
+    pub fn error(seq: Option<u32>, code: ErrorCode) -> Self {
+        Self {
+            seq,
+            status: AckStatus::Err,
+            detail: Some(AckDetail::Error {
+                code,
+                text: code.as_str().unwrap_or(""),
+            }),
+        }
+    }
+
+    /// `CMD` downlink carrying a command string.
+    #[must_use]
+    pub fn command(seq: Option<u32>, cmd: &'a str) -> Self {
+        Self {
+            seq,
+            status: AckStatus::Cmd,
+            detail: Some(AckDetail::Command(cmd)),
+        }
+    }
+}
+
+/// Whether two metadata blocks hold the same pairs, regardless of order.
+fn same_meta_set(a: &[MetaPair<'_>], b: &[MetaPair<'_>]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|p| a.iter().filter(|q| *q == p).count() == b.iter().filter(|q| *q == p).count())
+}
+
+/// Check that a wire-form value has every structural character escaped, using
+/// the same set as [`crate::escape::escape_into`].
+pub(crate) fn is_escaped_value(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 1 < bytes.len() {
+            i += 2;
+        } else if crate::escape::needs_escape(bytes[i]) {
+            return false;
+        } else {
+            i += 1;
+        }
+    }
+    true
+}
+
 /// Parse a decimal string to u64 (`no_std` helper).
 fn parse_u64(s: &str) -> Option<u64> {
     if s.is_empty() {
@@ -1,8 +1,40 @@
 use crate::consts;
 use crate::error::{ParseError, ParseErrorKind};
 
+/// Whether an all-digit string fits in a `u64` (e.g. a millisecond epoch timestamp).
+#[must_use]
+pub fn digits_fit_u64(s: &str) -> bool {
+    s.bytes()
+        .try_fold(0u64, |acc, b| {
+            acc.checked_mul(10)?
+                .checked_add(u64::from(b.wrapping_sub(b'0')))
+        })
+        .is_some()
+}
+
+/// Offset of the first ASCII whitespace byte in `s`, if any.
+///
+/// Header validators check this first so a stray space is reported where it
+/// is rather than at the start of the field.
+pub(crate) fn whitespace_offset(s: &str) -> Option<usize> {
+    s.bytes().position(|b| b.is_ascii_whitespace())
+}
+
+/// Offset of the first non-ASCII byte in `s`, if any.
+///
+/// Identifier validators check this first so a multibyte character is
+/// reported where it starts rather than at the start of the field.
+pub(crate) fn non_ascii_offset(s: &str) -> Option<usize> {
+    s.bytes().position(|b| !b.is_ascii())
+}
+
 /// Validate a variable name: lowercase a-z, digits, underscore. Max 100 bytes.
+///
+/// Non-ASCII bytes are reported at their own offset; other problems at `pos`.
 pub fn validate_varname(name: &str, pos: usize) -> Result<(), ParseError> {
+    if let Some(offset) = non_ascii_offset(name) {
+        return Err(ParseError::new(ParseErrorKind::InvalidField, pos + offset));
+    }
     if name.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidField, pos));
     }
@@ -18,7 +50,12 @@ pub fn validate_varname(name: &str, pos: usize) -> Result<(), ParseError> {
 }
 
 /// Validate a serial number: alphanumeric, hyphen, underscore. Max 100 bytes.
+///
+/// Whitespace is reported at its own offset; other problems at `pos`.
 pub fn validate_serial(serial: &str, pos: usize) -> Result<(), ParseError> {
+    if let Some(offset) = whitespace_offset(serial) {
+        return Err(ParseError::new(ParseErrorKind::InvalidSerial, pos + offset));
+    }
     if serial.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidSerial, pos));
     }
@@ -34,7 +71,12 @@ pub fn validate_serial(serial: &str, pos: usize) -> Result<(), ParseError> {
 }
 
 /// Validate a group name: same rules as variable name. Max 100 bytes.
+///
+/// Non-ASCII bytes are reported at their own offset; other problems at `pos`.
 pub fn validate_group(group: &str, pos: usize) -> Result<(), ParseError> {
+    if let Some(offset) = non_ascii_offset(group) {
+        return Err(ParseError::new(ParseErrorKind::InvalidField, pos + offset));
+    }
     if group.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidField, pos));
     }
@@ -50,7 +92,15 @@ pub fn validate_group(group: &str, pos: usize) -> Result<(), ParseError> {
 }
 
 /// Validate a metadata key: same rules as variable name. Max 100 bytes.
+///
+/// Non-ASCII bytes are reported at their own offset; other problems at `pos`.
 pub fn validate_meta_key(key: &str, pos: usize) -> Result<(), ParseError> {
+    if let Some(offset) = non_ascii_offset(key) {
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidMetadata,
+            pos + offset,
+        ));
+    }
     if key.is_empty() {
         return Err(ParseError::new(ParseErrorKind::InvalidMetadata, pos));
     }
@@ -77,6 +127,9 @@ pub fn validate_unit(unit: &str, pos: usize) -> Result<(), ParseError> {
 }
 
 /// Validate number format per spec: `-?(0|[1-9][0-9]*)(\.[0-9]+)?`
+///
+/// With the `scientific-numbers` feature, an optional exponent
+/// `[eE][+-]?[0-9]+` is also accepted after the mantissa.
 pub fn validate_number(s: &str, pos: usize) -> Result<(), ParseError> {
     let bytes = s.as_bytes();
     let mut i = 0;
@@ -112,6 +165,21 @@ pub fn validate_number(s: &str, pos: usize) -> Result<(), ParseError> {
         }
     }
 
+    // Optional exponent
+    #[cfg(feature = "scientific-numbers")]
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        i += 1;
+        if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+            i += 1;
+        }
+        if i >= bytes.len() || !bytes[i].is_ascii_digit() {
+            return Err(ParseError::new(ParseErrorKind::InvalidVariable, pos));
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+
     if i != bytes.len() {
         return Err(ParseError::new(ParseErrorKind::InvalidVariable, pos));
     }
@@ -1,6 +1,10 @@
-use tagotip_codec::build::{build_ack, build_headless, build_uplink};
+use tagotip_codec::build::{
+    AckErrorText, PushFrameBuilder, build_ack, build_ack_with, build_headless, build_uplink,
+};
 use tagotip_codec::inline_vec::InlineVec;
-use tagotip_codec::parse::{parse_ack, parse_headless, parse_uplink};
+use tagotip_codec::parse::{
+    ParseOptions, parse_ack, parse_headless, parse_uplink, parse_uplink_with,
+};
 use tagotip_codec::types::*;
 
 const AUTH: &str = "4deedd7bab8817ec";
@@ -38,6 +42,7 @@ fn build_simple_push() {
             body_meta: None,
             variables: vars,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     };
@@ -71,6 +76,7 @@ fn build_push_with_seq() {
             body_meta: None,
             variables: vars,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     };
@@ -152,6 +158,159 @@ fn build_ack_err() {
     assert_eq!(output, "ACK|!5|ERR|invalid_token");
 }
 
+#[test]
+fn ack_helpers_build_canonical_frames() {
+    let output = build_to_string(|buf| build_ack(&AckFrame::ok_count(None, 3), buf));
+    assert_eq!(output, "ACK|OK|3");
+    let output = build_to_string(|buf| build_ack(&AckFrame::pong(Some(1)), buf));
+    assert_eq!(output, "ACK|!1|PONG");
+    let output = build_to_string(|buf| build_ack(&AckFrame::command(Some(2), "reboot"), buf));
+    assert_eq!(output, "ACK|!2|CMD|reboot");
+}
+
+#[test]
+fn ack_error_helper_derives_text() {
+    let ack = AckFrame::error(None, ErrorCode::RateLimited);
+    let output = build_to_string(|buf| build_ack(&ack, buf));
+    assert_eq!(output, "ACK|ERR|rate_limited");
+
+    let parsed = parse_ack(&output).unwrap();
+    assert_eq!(parsed, ack);
+}
+
+#[test]
+fn ack_error_with_custom_text_emission_modes() {
+    let ack = AckFrame {
+        seq: None,
+        status: AckStatus::Err,
+        detail: Some(AckDetail::error_with_text(
+            ErrorCode::InvalidToken,
+            "token expired",
+        )),
+    };
+    let output = build_to_string(|buf| build_ack(&ack, buf));
+    assert_eq!(output, "ACK|ERR|token expired");
+    let output = build_to_string(|buf| build_ack_with(&ack, buf, AckErrorText::AsGiven));
+    assert_eq!(output, "ACK|ERR|token expired");
+    let output = build_to_string(|buf| build_ack_with(&ack, buf, AckErrorText::Canonical));
+    assert_eq!(output, "ACK|ERR|invalid_token");
+
+    // `Unknown` has no canonical text, so its own text is kept.
+    let ack = AckFrame {
+        seq: None,
+        status: AckStatus::Err,
+        detail: Some(AckDetail::error_with_text(
+            ErrorCode::Unknown,
+            "quota_spent",
+        )),
+    };
+    let output = build_to_string(|buf| build_ack_with(&ack, buf, AckErrorText::Canonical));
+    assert_eq!(output, "ACK|ERR|quota_spent");
+}
+
+#[test]
+fn body_modifiers_build_in_spec_order() {
+    let canonical = format!("PUSH|{AUTH}|dev1|@1694567890000^batch_42{{k=v}}[temp:=32]");
+    let swapped = format!("PUSH|{AUTH}|dev1|^batch_42@1694567890000{{k=v}}[temp:=32]");
+    let opts = ParseOptions {
+        allow_any_modifier_order: true,
+        ..ParseOptions::default()
+    };
+
+    for input in [&canonical, &swapped] {
+        let frame = parse_uplink_with(input, &opts).unwrap();
+        let output = build_to_string(|buf| build_uplink(&frame, buf));
+        assert_eq!(output, canonical);
+    }
+}
+
+#[test]
+fn variable_modifiers_build_in_spec_order() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32#C@1694567890000^grp{{k=v}}]");
+    let frame = parse_uplink(&input).unwrap();
+    let output = build_to_string(|buf| build_uplink(&frame, buf));
+    assert_eq!(output, input);
+}
+
+#[test]
+fn swapped_body_modifiers_need_option() {
+    let swapped = format!("PUSH|{AUTH}|dev1|^batch_42@1694567890000[temp:=32]");
+    assert!(parse_uplink(&swapped).is_err());
+
+    let opts = ParseOptions {
+        allow_any_modifier_order: true,
+        ..ParseOptions::default()
+    };
+    for bad in [
+        format!("PUSH|{AUTH}|dev1|@1^g@2[temp:=32]"),
+        format!("PUSH|{AUTH}|dev1|^g@1^h[temp:=32]"),
+        format!("PUSH|{AUTH}|dev1|^g{{k=v}}@1[temp:=32]"),
+    ] {
+        assert!(parse_uplink_with(&bad, &opts).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn unknown_body_modifiers_preserved_when_allowed() {
+    let input = format!("PUSH|{AUTH}|dev1|@1694567890000^batch_42~tag{{k=v}}[temp:=32]");
+    assert!(parse_uplink(&input).is_err());
+    assert!(parse_uplink_with(&input, &ParseOptions::strict()).is_err());
+    let leading = format!("PUSH|{AUTH}|dev1|~tag[temp:=32]");
+    assert_eq!(
+        parse_uplink(&leading).unwrap_err().kind,
+        tagotip_codec::ParseErrorKind::InvalidModifier
+    );
+
+    let opts = ParseOptions {
+        allow_unknown_modifiers: true,
+        ..ParseOptions::default()
+    };
+    let frame = parse_uplink_with(&input, &opts).unwrap();
+    let Some(PushBody::Structured(body)) = &frame.push_body else {
+        panic!("expected structured body");
+    };
+    assert_eq!(body.group, Some("batch_42"));
+    assert_eq!(body.timestamp, Some("1694567890000"));
+    assert_eq!(
+        body.unknown_modifiers.as_slice(),
+        &[UnknownModifier {
+            raw: "~tag",
+            slot: ModifierSlot::AfterGroup,
+        }]
+    );
+    assert_eq!(body.body_metadata().len(), 1);
+
+    // Re-emitted where it was parsed, so the frame round-trips byte for byte.
+    let output = build_to_string(|buf| build_uplink(&frame, buf));
+    assert_eq!(output, input);
+    assert!(
+        parse_uplink_with(&output, &opts)
+            .unwrap()
+            .semantic_eq(&frame)
+    );
+}
+
+#[test]
+fn unknown_body_modifiers_round_trip_in_every_slot() {
+    let opts = ParseOptions {
+        allow_unknown_modifiers: true,
+        ..ParseOptions::default()
+    };
+    for body in [
+        "~a[temp:=32]",
+        "~a@1694567890000[temp:=32]",
+        "@1694567890000~a^batch_42[temp:=32]",
+        "^batch_42~a{k=v}[temp:=32]",
+        "{k=v}~a[temp:=32]",
+        "~a@1694567890000~b^batch_42~c{k=v}~d[temp:=32]",
+    ] {
+        let input = format!("PUSH|{AUTH}|dev1|{body}");
+        let frame = parse_uplink_with(&input, &opts).unwrap();
+        let output = build_to_string(|buf| build_uplink(&frame, buf));
+        assert_eq!(output, input);
+    }
+}
+
 #[test]
 fn build_passthrough_hex() {
     let frame = UplinkFrame {
@@ -192,6 +351,7 @@ fn build_headless_push() {
             body_meta: None,
             variables: vars,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     };
@@ -326,3 +486,352 @@ fn buffer_too_small_error() {
     let result = build_uplink(&frame, &mut buf);
     assert!(result.is_err());
 }
+
+// --- Validating Variable constructors ---
+
+#[test]
+fn variable_constructors_build_parseable_frame() {
+    let mut vars = InlineVec::new();
+    vars.push(Variable::new_number("temp", "-2.5", Some("C")).unwrap())
+        .unwrap();
+    vars.push(Variable::new_string("msg", r"a\;b", None).unwrap())
+        .unwrap();
+    vars.push(Variable::new_boolean("active", true).unwrap())
+        .unwrap();
+    vars.push(Variable::new_location("pos", "39.74", "-104.99", Some("1609")).unwrap())
+        .unwrap();
+
+    let frame = UplinkFrame {
+        method: Method::Push,
+        seq: None,
+        auth: AUTH,
+        serial: "dev1",
+        push_body: Some(PushBody::Structured(StructuredBody {
+            group: None,
+            timestamp: None,
+            body_meta: None,
+            variables: vars,
+            meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
+        })),
+        pull_body: None,
+    };
+    let output = build_to_string(|buf| build_uplink(&frame, buf));
+    assert_eq!(
+        output,
+        format!(r"PUSH|{AUTH}|dev1|[temp:=-2.5#C;msg=a\;b;active?=true;pos@=39.74,-104.99,1609]")
+    );
+    assert_eq!(parse_uplink(&output).unwrap(), frame);
+}
+
+#[test]
+fn variable_constructor_rejects_invalid_name() {
+    let err = Variable::new_number("my-temp", "32", None).unwrap_err();
+    assert_eq!(err.kind, tagotip_codec::error::BuildErrorKind::InvalidInput);
+    assert!(Variable::new_boolean("Temp", true).is_err());
+    assert!(Variable::new_string("", "x", None).is_err());
+}
+
+#[test]
+fn variable_constructor_rejects_invalid_values() {
+    assert!(Variable::new_number("n", "032", None).is_err());
+    assert!(Variable::new_number("n", "1", Some("")).is_err());
+    assert!(Variable::new_string("s", "", None).is_err());
+    assert!(Variable::new_string("s", "a;b", None).is_err());
+    assert!(Variable::new_string("s", "trailing\\", None).is_err());
+    assert!(Variable::new_string("s", "a,b", None).is_err());
+    assert!(Variable::new_string("s", r"a\,b", None).is_ok());
+    assert!(Variable::new_location("p", "abc", "1", None).is_err());
+    assert!(Variable::new_location("p", "1", "2", Some("")).is_err());
+}
+
+// --- PushFrameBuilder ---
+
+#[test]
+fn push_builder_matches_hand_written_frame() {
+    let mut meta_pool = InlineVec::new();
+    meta_pool
+        .push(MetaPair {
+            key: "site",
+            value: "lab",
+        })
+        .unwrap();
+    meta_pool
+        .push(MetaPair {
+            key: "source",
+            value: "dht22",
+        })
+        .unwrap();
+
+    let mut vars = InlineVec::new();
+    vars.push(Variable {
+        name: "temp",
+        operator: Operator::Number,
+        value: Value::Number("21.5"),
+        unit: Some("C"),
+        timestamp: Some("1700000000"),
+        group: None,
+        meta: Some(MetaRange { start: 1, len: 1 }),
+    })
+    .unwrap();
+    vars.push(Variable {
+        name: "active",
+        operator: Operator::Boolean,
+        value: Value::Boolean(true),
+        unit: None,
+        timestamp: None,
+        group: Some("g2"),
+        meta: None,
+    })
+    .unwrap();
+
+    let hand_written = UplinkFrame {
+        method: Method::Push,
+        seq: Some(7),
+        auth: AUTH,
+        serial: "sensor-01",
+        push_body: Some(PushBody::Structured(StructuredBody {
+            group: Some("g1"),
+            timestamp: None,
+            body_meta: Some(MetaRange { start: 0, len: 1 }),
+            variables: vars,
+            meta_pool,
+            unknown_modifiers: InlineVec::new(),
+        })),
+        pull_body: None,
+    };
+
+    let built = PushFrameBuilder::new(AUTH)
+        .serial("sensor-01")
+        .seq(7)
+        .body_group("g1")
+        .body_meta("site", "lab")
+        .number("temp", "21.5")
+        .unit("C")
+        .timestamp("1700000000")
+        .meta("source", "dht22")
+        .boolean("active", true)
+        .group("g2")
+        .build()
+        .unwrap();
+
+    assert_eq!(built, hand_written);
+    let output = build_to_string(|buf| build_uplink(&built, buf));
+    assert_eq!(
+        output,
+        build_to_string(|buf| build_uplink(&hand_written, buf))
+    );
+    assert_eq!(parse_uplink(&output).unwrap(), built);
+}
+
+#[test]
+fn push_builder_keeps_meta_ranges_per_variable() {
+    let frame = PushFrameBuilder::new(AUTH)
+        .serial("dev1")
+        .number("a", "1")
+        .meta("k1", "v1")
+        .meta("k2", "v2")
+        .string("b", "x")
+        .meta("k3", "v3")
+        .build()
+        .unwrap();
+
+    let output = build_to_string(|buf| build_uplink(&frame, buf));
+    assert_eq!(
+        output,
+        format!("PUSH|{AUTH}|dev1|[a:=1{{k1=v1,k2=v2}};b=x{{k3=v3}}]")
+    );
+}
+
+#[test]
+fn push_builder_reports_first_error() {
+    use tagotip_codec::error::BuildErrorKind;
+
+    // Modifier before any variable.
+    let err = PushFrameBuilder::new(AUTH)
+        .serial("dev1")
+        .unit("C")
+        .number("a", "1")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind, BuildErrorKind::InvalidInput);
+
+    assert!(PushFrameBuilder::new(AUTH).serial("dev1").build().is_err());
+    assert!(
+        PushFrameBuilder::new(AUTH)
+            .number("a", "1")
+            .build()
+            .is_err()
+    );
+    assert!(
+        PushFrameBuilder::new("nothex")
+            .serial("d")
+            .number("a", "1")
+            .build()
+            .is_err()
+    );
+    assert!(
+        PushFrameBuilder::new(AUTH)
+            .serial("d")
+            .number("a", "01")
+            .build()
+            .is_err()
+    );
+    assert!(
+        PushFrameBuilder::new(AUTH)
+            .serial("d")
+            .number("a", "1")
+            .meta("k", "a,b")
+            .build()
+            .is_err()
+    );
+    assert!(
+        PushFrameBuilder::new(AUTH)
+            .serial("d")
+            .location("p", "1", "2", None)
+            .unit("m")
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn push_builder_for_device_uses_validated_newtypes() {
+    use tagotip_codec::error::ParseErrorKind;
+
+    let err = Serial::parse("my.device").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidSerial);
+    assert!(AuthToken::parse("nothex").is_err());
+
+    let auth = AuthToken::parse(AUTH).unwrap();
+    let serial = Serial::parse("sensor-01").unwrap();
+    assert_eq!(serial.as_str(), "sensor-01");
+
+    let typed = build_to_string(|buf| {
+        let frame = PushFrameBuilder::for_device(auth, serial)
+            .number("temp", "1")
+            .build()?;
+        build_uplink(&frame, buf)
+    });
+    let raw = build_to_string(|buf| {
+        let frame = PushFrameBuilder::new(AUTH)
+            .serial("sensor-01")
+            .number("temp", "1")
+            .build()?;
+        build_uplink(&frame, buf)
+    });
+    assert_eq!(typed, raw);
+}
+
+#[test]
+fn build_uplink_checked_rejects_unparseable_output() {
+    use tagotip_codec::build::build_uplink_checked;
+    use tagotip_codec::error::{BuildErrorKind, ParseErrorKind};
+
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=1]");
+    let mut frame = parse_uplink(&input).unwrap();
+    assert_eq!(
+        build_to_string(|buf| build_uplink_checked(&frame, buf)),
+        input
+    );
+
+    let Some(PushBody::Structured(body)) = frame.push_body.as_mut() else {
+        panic!("expected structured body");
+    };
+    body.variables[0].value = Value::Number("abc");
+    let mut buf = [0u8; 64];
+    // The unchecked builder writes it anyway.
+    assert!(build_uplink(&frame, &mut buf).is_ok());
+    let err = build_uplink_checked(&frame, &mut buf).unwrap_err();
+    assert_eq!(err.kind, BuildErrorKind::InvalidInput);
+    let cause = err.cause.unwrap();
+    assert_eq!(cause.kind, ParseErrorKind::InvalidVariable);
+    assert!(
+        err.to_string()
+            .starts_with("invalid input data: invalid variable")
+    );
+
+    frame.serial = "dev 1";
+    let err = build_uplink_checked(&frame, &mut buf).unwrap_err();
+    assert_eq!(err.cause.unwrap().kind, ParseErrorKind::InvalidSerial);
+}
+
+// --- Output sinks ---
+
+#[test]
+fn slice_sink_advances_and_fails_without_writing() {
+    use tagotip_codec::build::ByteSink;
+
+    let mut buf = [0u8; 4];
+    let mut sink: &mut [u8] = &mut buf;
+    sink.write_bytes(b"ab").unwrap();
+    assert!(sink.write_bytes(b"cde").is_err());
+    sink.write_byte(b'c').unwrap();
+    assert_eq!(sink.len(), 1);
+    assert_eq!(&buf[..3], b"abc");
+}
+
+#[test]
+fn frame_writer_reset_reuses_buffer() {
+    use tagotip_codec::build::{FrameWriter, build_uplink_into_writer};
+
+    let inputs = [
+        format!("PUSH|!1|{AUTH}|dev1|[temp:=21.5#C]"),
+        format!("PING|{AUTH}|dev2"),
+        format!("PULL|{AUTH}|dev3|[temp;hum]"),
+    ];
+    let mut buf = [0u8; 128];
+    let mut w = FrameWriter::from_slice(&mut buf);
+    for input in &inputs {
+        w.reset();
+        let frame = parse_uplink(input).unwrap();
+        let n = build_uplink_into_writer(&frame, &mut w).unwrap();
+        assert_eq!(n, input.len());
+        assert_eq!(w.written(), input.len());
+        assert_eq!(w.as_bytes(), input.as_bytes());
+    }
+
+    // Without a reset, frames are appended.
+    let frame = parse_uplink(&inputs[1]).unwrap();
+    build_uplink_into_writer(&frame, &mut w).unwrap();
+    assert_eq!(
+        w.as_bytes(),
+        format!("{}{}", inputs[2], inputs[1]).as_bytes()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn build_into_slice_and_vec_match() {
+    use tagotip_codec::build::{IoSink, build_ack_to, build_headless_to, build_uplink_to};
+
+    let input = format!("PUSH|!4|{AUTH}|dev1|@1700000000[temp:=21.5#C{{k=v}};on?=true]");
+    let frame = parse_uplink(&input).unwrap();
+
+    let slice_out = build_to_string(|buf| build_uplink(&frame, buf));
+    let mut vec = Vec::new();
+    let n = build_uplink_to(&frame, &mut vec).unwrap();
+    assert_eq!(n, vec.len());
+    assert_eq!(String::from_utf8(vec).unwrap(), slice_out);
+
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    build_uplink_to(&frame, IoSink(&mut cursor)).unwrap();
+    assert_eq!(cursor.into_inner(), slice_out.as_bytes());
+
+    let ack = AckFrame::ok_count(Some(4), 2);
+    let mut vec = Vec::new();
+    build_ack_to(&ack, &mut vec).unwrap();
+    assert_eq!(vec, build_to_string(|buf| build_ack(&ack, buf)).as_bytes());
+
+    let headless = HeadlessFrame {
+        serial: frame.serial,
+        push_body: frame.push_body.clone(),
+        pull_body: None,
+    };
+    let mut vec = Vec::new();
+    build_headless_to(Method::Push, &headless, &mut vec).unwrap();
+    assert_eq!(
+        vec,
+        build_to_string(|buf| build_headless(Method::Push, &headless, buf)).as_bytes()
+    );
+}
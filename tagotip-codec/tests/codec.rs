@@ -0,0 +1,147 @@
+use tagotip_codec::build::build_uplink;
+use tagotip_codec::codec::{
+    base64_decode, base64_encode, base64_encoded_len, hex_decode, hex_encode, hex_encoded_len,
+};
+use tagotip_codec::parse::parse_uplink;
+use tagotip_codec::{PassthroughEncoding, PushBody};
+
+const SAMPLES: &[&[u8]] = &[
+    b"",
+    b"f",
+    b"fo",
+    b"foo",
+    b"foob",
+    b"fooba",
+    b"foobar",
+    b"\x00\xff\x10",
+];
+
+#[test]
+fn hex_round_trip() {
+    for &data in SAMPLES {
+        let mut enc = [0u8; 32];
+        let n = hex_encode(data, &mut enc).unwrap();
+        assert_eq!(n, hex_encoded_len(data.len()));
+        let mut dec = [0u8; 16];
+        let m = hex_decode(core::str::from_utf8(&enc[..n]).unwrap(), &mut dec).unwrap();
+        assert_eq!(&dec[..m], data);
+    }
+}
+
+#[test]
+fn hex_encode_uppercase() {
+    let mut out = [0u8; 8];
+    let n = hex_encode(&[0xDE, 0xAD, 0xBE, 0xEF], &mut out).unwrap();
+    assert_eq!(&out[..n], b"DEADBEEF");
+}
+
+#[test]
+fn hex_decode_either_case() {
+    let mut out = [0u8; 4];
+    assert_eq!(hex_decode("deADbeEF", &mut out), Some(4));
+    assert_eq!(out, [0xDE, 0xAD, 0xBE, 0xEF]);
+}
+
+#[test]
+fn hex_decode_rejects_odd_length() {
+    let mut out = [0u8; 4];
+    assert_eq!(hex_decode("ABC", &mut out), None);
+}
+
+#[test]
+fn hex_decode_rejects_non_hex() {
+    let mut out = [0u8; 4];
+    assert_eq!(hex_decode("AG", &mut out), None);
+}
+
+#[test]
+fn hex_buffer_too_small() {
+    let mut out = [0u8; 1];
+    assert_eq!(hex_decode("ABCD", &mut out), None);
+    assert_eq!(hex_encode(&[1, 2], &mut [0u8; 3]), None);
+}
+
+#[test]
+fn base64_known_vectors() {
+    let expected = [
+        "", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy",
+    ];
+    for (&data, want) in SAMPLES.iter().zip(expected) {
+        let mut out = [0u8; 16];
+        let n = base64_encode(data, &mut out).unwrap();
+        assert_eq!(n, base64_encoded_len(data.len()));
+        assert_eq!(&out[..n], want.as_bytes());
+    }
+}
+
+#[test]
+fn base64_round_trip() {
+    for &data in SAMPLES {
+        let mut enc = [0u8; 16];
+        let n = base64_encode(data, &mut enc).unwrap();
+        let mut dec = [0u8; 16];
+        let m = base64_decode(core::str::from_utf8(&enc[..n]).unwrap(), &mut dec).unwrap();
+        assert_eq!(&dec[..m], data);
+    }
+}
+
+#[test]
+fn base64_decode_unpadded() {
+    let mut out = [0u8; 8];
+    let n = base64_decode("Zm9vYg", &mut out).unwrap();
+    assert_eq!(&out[..n], b"foob");
+}
+
+#[test]
+fn base64_decode_rejects_malformed() {
+    let mut out = [0u8; 8];
+    assert_eq!(base64_decode("Zm9vY", &mut out), None);
+    assert_eq!(base64_decode("Zg=", &mut out), None);
+    assert_eq!(base64_decode("Z=g=", &mut out), None);
+    assert_eq!(base64_decode("Zm9v!", &mut out), None);
+}
+
+#[test]
+fn passthrough_decode_hex() {
+    let frame = parse_uplink("PUSH|4deedd7bab8817ec|sensor-01|>xDEADBEEF").unwrap();
+    let Some(PushBody::Passthrough(pt)) = frame.push_body else {
+        panic!("expected passthrough body");
+    };
+    assert_eq!(pt.encoding, PassthroughEncoding::Hex);
+    let mut out = [0u8; 8];
+    let n = pt.decode(&mut out).unwrap();
+    assert_eq!(&out[..n], &[0xDE, 0xAD, 0xBE, 0xEF]);
+}
+
+#[test]
+fn passthrough_decode_base64() {
+    let frame = parse_uplink("PUSH|4deedd7bab8817ec|sensor-01|>bZm9vYmFy").unwrap();
+    let Some(PushBody::Passthrough(pt)) = frame.push_body else {
+        panic!("expected passthrough body");
+    };
+    assert_eq!(pt.encoding, PassthroughEncoding::Base64);
+    let mut out = [0u8; 8];
+    let n = pt.decode(&mut out).unwrap();
+    assert_eq!(&out[..n], b"foobar");
+}
+
+#[test]
+fn passthrough_base64url_round_trip() {
+    let input = "PUSH|4deedd7bab8817ec|sensor-01|>u3q2-7__-";
+    let frame = parse_uplink(input).unwrap();
+    let Some(PushBody::Passthrough(pt)) = frame.push_body else {
+        panic!("expected passthrough body");
+    };
+    assert_eq!(pt.encoding, PassthroughEncoding::Base64Url);
+    let mut out = [0u8; 8];
+    let n = pt.decode(&mut out).unwrap();
+    assert_eq!(&out[..n], &[0xDE, 0xAD, 0xBE, 0xEF, 0xFF, 0xFE]);
+
+    let mut buf = [0u8; 64];
+    let n = build_uplink(&frame, &mut buf).unwrap();
+    assert_eq!(&buf[..n], input.as_bytes());
+
+    // Each alphabet rejects the other's characters.
+    assert!(parse_uplink("PUSH|4deedd7bab8817ec|sensor-01|>u3q2+7w==").is_err());
+    assert!(parse_uplink("PUSH|4deedd7bab8817ec|sensor-01|>b3q2-7w==").is_err());
+}
@@ -0,0 +1,72 @@
+use tagotip_codec::build::{build_uplink, build_uplink_with_crc};
+use tagotip_codec::crc::crc16_ccitt;
+use tagotip_codec::error::ParseErrorKind;
+use tagotip_codec::parse::{parse_uplink, parse_uplink_with_crc};
+
+const FRAME: &str = "PUSH|4deedd7bab8817ec|sensor-01|>xDEADBEEF";
+
+fn with_crc(frame: &str) -> String {
+    let parsed = parse_uplink(frame).unwrap();
+    let mut buf = [0u8; 256];
+    let n = build_uplink_with_crc(&parsed, &mut buf).unwrap();
+    String::from_utf8(buf[..n].to_vec()).unwrap()
+}
+
+#[test]
+fn crc16_check_value() {
+    assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+    assert_eq!(crc16_ccitt(b""), 0xFFFF);
+}
+
+#[test]
+fn build_appends_crc_suffix() {
+    let framed = with_crc(FRAME);
+    let (body, suffix) = framed.split_at(FRAME.len());
+    assert_eq!(body, FRAME);
+    assert_eq!(suffix, format!("*{:04X}", crc16_ccitt(FRAME.as_bytes())));
+}
+
+#[test]
+fn good_crc_round_trips() {
+    let framed = with_crc(FRAME);
+    let frame = parse_uplink_with_crc(&framed).unwrap();
+    assert_eq!(frame, parse_uplink(FRAME).unwrap());
+
+    // Lowercase hex and a trailing newline are accepted.
+    let (head, digits) = framed.split_at(framed.len() - 4);
+    let lower = format!("{head}{}", digits.to_lowercase());
+    assert_eq!(parse_uplink_with_crc(&lower).unwrap(), frame);
+    let newline = format!("{framed}\n");
+    assert_eq!(parse_uplink_with_crc(&newline).unwrap(), frame);
+}
+
+#[test]
+fn corrupted_payload_is_rejected() {
+    let framed = with_crc(FRAME).replace("DEADBEEF", "DEADBEEE");
+    let err = parse_uplink_with_crc(&framed).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::ChecksumMismatch);
+    assert_eq!(err.position, FRAME.len());
+}
+
+#[test]
+fn malformed_checksum_field_is_rejected() {
+    for input in [
+        FRAME.to_string(),
+        format!("{FRAME}*12"),
+        format!("{FRAME}*12G4"),
+        format!("{FRAME}#1234"),
+        "*1".to_string(),
+    ] {
+        let err = parse_uplink_with_crc(&input).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::ChecksumMismatch, "{input}");
+    }
+}
+
+#[test]
+fn crc_build_needs_room_for_suffix() {
+    let frame = parse_uplink(FRAME).unwrap();
+    let mut buf = [0u8; 64];
+    let n = build_uplink(&frame, &mut buf).unwrap();
+    assert!(build_uplink_with_crc(&frame, &mut buf[..n + 4]).is_err());
+    assert!(build_uplink_with_crc(&frame, &mut buf[..n + 5]).is_ok());
+}
@@ -6,7 +6,10 @@
 
 use tagotip_codec::build::{build_ack, build_headless, build_uplink};
 use tagotip_codec::error::ParseErrorKind;
-use tagotip_codec::escape::{escape_into, needs_unescape, unescape_into};
+use tagotip_codec::escape::{
+    escape_into, escaped_len, needs_unescape, unescape_in_place, unescape_into,
+};
+use tagotip_codec::parse::frame::split_fields;
 use tagotip_codec::parse::{parse_ack, parse_headless, parse_uplink};
 use tagotip_codec::types::*;
 
@@ -43,6 +46,27 @@ fn escape_pipe_in_string_value() {
     roundtrip(&input);
 }
 
+#[test]
+fn split_fields_honors_escaped_pipe() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=a\\|b]");
+    let fields = split_fields(&input);
+    assert_eq!(
+        fields.as_slice(),
+        &["PUSH", AUTH, "sensor_01", "[msg=a\\|b]"]
+    );
+
+    // An escaped backslash does not escape the pipe after it.
+    let fields = split_fields("a\\\\|b");
+    assert_eq!(fields.as_slice(), &["a\\\\", "b"]);
+
+    // The headless path frames the same body identically.
+    let frame = parse_headless(Method::Push, "sensor_01|[msg=a\\|b]").unwrap();
+    let Some(PushBody::Structured(body)) = frame.push_body else {
+        panic!("expected structured body");
+    };
+    assert_eq!(body.variables[0].value, Value::String("a\\|b"));
+}
+
 #[test]
 fn escape_semicolon_in_string_value() {
     let input = format!("PUSH|{AUTH}|sensor_01|[msg=a\\;b]");
@@ -150,6 +174,29 @@ fn escape_at_end_of_input() {
     assert_eq!(result, "abc\\");
 }
 
+#[test]
+fn unescape_in_place_matches_unescape_into() {
+    let inputs = [
+        "a\\|b\\[c\\]d\\;e\\,f\\{g\\}h\\#i\\@j\\^k\\\\l\\nm",
+        "a\\zb",
+        "abc\\",
+        "\\",
+        "\\\\\\",
+        "\\\\|",
+        "café\\;€\\é",
+        "plain",
+        "",
+    ];
+    for input in inputs {
+        let mut expected = [0u8; 64];
+        let n = unescape_into(input, &mut expected).unwrap();
+
+        let mut buf = input.as_bytes().to_vec();
+        let m = unescape_in_place(&mut buf);
+        assert_eq!(&buf[..m], &expected[..n], "input: {input:?}");
+    }
+}
+
 #[test]
 fn unescape_into_buffer_too_small() {
     let input = "hello\\|world";
@@ -164,6 +211,31 @@ fn escape_into_buffer_too_small() {
     assert!(escape_into(input, &mut buf).is_none());
 }
 
+#[test]
+fn escaped_len_matches_escape_into() {
+    assert_eq!(escaped_len("a|b"), 4);
+    for input in [
+        "a|b",
+        "plain",
+        "",
+        "line\nbreak",
+        "a|b[c]d;e,f{g}h#i@j^k\\l",
+        "café€",
+    ] {
+        let mut buf = [0u8; 64];
+        let n = escape_into(input, &mut buf).unwrap();
+        assert_eq!(escaped_len(input), n, "input: {input:?}");
+    }
+}
+
+#[test]
+fn escape_into_exact_size_buffer() {
+    let mut buf = [0u8; 4];
+    assert_eq!(escape_into("a|b", &mut buf), Some(4));
+    assert_eq!(&buf, b"a\\|b");
+    assert!(escape_into("a|b", &mut buf[..3]).is_none());
+}
+
 #[test]
 fn needs_unescape_true() {
     assert!(needs_unescape("hello\\|world"));
@@ -174,6 +246,119 @@ fn needs_unescape_false() {
     assert!(!needs_unescape("hello world"));
 }
 
+#[test]
+fn multibyte_string_value_slices_on_char_boundaries() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=café€#ünit{{k=ñ}};t:=1]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured"),
+    };
+    assert_eq!(body.variables[0].value, Value::String("café€"));
+    assert_eq!(body.variables[0].unit, Some("ünit"));
+    assert_eq!(body.variable_metadata(&body.variables[0])[0].value, "ñ");
+    assert_eq!(body.variables[1].name, "t");
+    roundtrip(&input);
+}
+
+#[test]
+fn escape_before_multibyte_char() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=a\\é;t:=1]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured"),
+    };
+    assert_eq!(body.variables[0].value, Value::String("a\\é"));
+    assert_eq!(body.variables.len(), 2);
+}
+
+#[cfg(not(feature = "quoted-strings"))]
+#[test]
+fn quoted_value_is_plain_text_by_default() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=\"hi\"]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured"),
+    };
+    assert_eq!(body.variables[0].value, Value::String("\"hi\""));
+}
+
+#[cfg(feature = "quoted-strings")]
+#[test]
+fn quoted_value_keeps_delimiters_and_rebuilds() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=\"a|b;c\"#u;n:=1]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.as_ref().unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured"),
+    };
+    assert_eq!(body.variables[0].value, Value::String("a|b;c"));
+    assert_eq!(body.variables[0].unit, Some("u"));
+    assert_eq!(body.variables[1].value, Value::Number("1"));
+
+    let mut buf = [0u8; 256];
+    let n = build_uplink(&frame, &mut buf).unwrap();
+    assert_eq!(core::str::from_utf8(&buf[..n]).unwrap(), input);
+}
+
+#[cfg(feature = "quoted-strings")]
+#[test]
+fn quoted_value_escaped_quote_and_errors() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=\"say \\\"hi\\\"\"]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured"),
+    };
+    assert_eq!(body.variables[0].value, Value::String("say \\\"hi\\\""));
+
+    for bad in ["[msg=\"open]", "[msg=\"a\"b]"] {
+        let input = format!("PUSH|{AUTH}|sensor_01|{bad}");
+        assert!(parse_uplink(&input).is_err(), "{bad}");
+    }
+}
+
+#[cfg(feature = "quoted-strings")]
+#[test]
+fn quote_only_opens_at_value_start() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[msg=a=\"b#c\"]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured"),
+    };
+    assert_eq!(body.variables[0].value, Value::String("a=\"b"));
+    assert_eq!(body.variables[0].unit, Some("c\""));
+}
+
+#[cfg(feature = "quoted-strings")]
+#[test]
+fn escaped_quotes_round_trip_through_builder() {
+    let raw = "\"x\"";
+    let mut escaped = [0u8; 16];
+    let n = escape_into(raw, &mut escaped).unwrap();
+    let wire = core::str::from_utf8(&escaped[..n]).unwrap();
+    assert_eq!(wire, "\\\"x\\\"");
+
+    let var = Variable::new_string("s", wire, None).unwrap();
+    let mut buf = [0u8; 64];
+    let n = tagotip_codec::build::build_variable(&var, &[], &mut buf).unwrap();
+    let built = core::str::from_utf8(&buf[..n]).unwrap();
+    assert_eq!(built, "s=\\\"x\\\"");
+
+    let parsed = tagotip_codec::parse::parse_variable(built)
+        .unwrap()
+        .variable;
+    let Value::String(value) = parsed.value else {
+        panic!("expected string");
+    };
+    let mut out = [0u8; 16];
+    let n = unescape_into(value, &mut out).unwrap();
+    assert_eq!(&out[..n], raw.as_bytes());
+}
+
 // =========================================================================
 // 1B. Validation Boundaries
 // =========================================================================
@@ -316,6 +501,23 @@ fn over_max_meta_pairs_rejected() {
     assert_parse_err(&input, ParseErrorKind::TooManyItems);
 }
 
+#[test]
+fn meta_pool_overflow_reports_offending_variable() {
+    // 16 variables with 32 pairs each fill the 512-pair pool exactly; the
+    // 17th is within its own block limit but overflows the shared pool.
+    let pairs: Vec<String> = (0..32).map(|i| format!("k{i}=v")).collect();
+    let vars: Vec<String> = (0..17)
+        .map(|i| format!("v{i}:=0{{{}}}", pairs.join(",")))
+        .collect();
+    let input = format!("PUSH|{}|sensor_01|[{}]", AUTH, vars.join(";"));
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::TooManyItems);
+    assert_eq!(err.position, input.find("v16:=").unwrap());
+
+    let input = format!("PUSH|{}|sensor_01|[{}]", AUTH, vars[..16].join(";"));
+    assert!(parse_uplink(&input).is_ok());
+}
+
 #[test]
 fn frame_exactly_max_size() {
     // Build a frame that's exactly 16384 bytes
@@ -328,6 +530,27 @@ fn frame_exactly_max_size() {
     assert!(parse_uplink(&input).is_ok());
 }
 
+#[test]
+fn max_size_frame_error_position_in_bounds() {
+    // Maximum-size frame whose error sits at the very end.
+    let header = format!("PUSH|{AUTH}|sensor_01|[msg=");
+    let trailer = ";bad]";
+    let value = "x".repeat(16384 - header.len() - trailer.len());
+    let input = format!("{header}{value}{trailer}");
+    assert_eq!(input.len(), 16384);
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidVariable);
+    assert!(err.position < input.len());
+}
+
+#[test]
+fn next_field_pos_saturates() {
+    use tagotip_codec::parse::frame::next_field_pos;
+    assert_eq!(next_field_pos(0, "PUSH"), 5);
+    assert_eq!(next_field_pos(usize::MAX - 2, "abc"), usize::MAX);
+    assert_eq!(next_field_pos(usize::MAX, ""), usize::MAX);
+}
+
 #[test]
 fn frame_over_max_size() {
     let header = format!("PUSH|{AUTH}|sensor_01|[msg=");
@@ -419,6 +642,36 @@ fn number_alpha_rejected() {
     assert_parse_err(&input, ParseErrorKind::InvalidVariable);
 }
 
+#[cfg(not(feature = "scientific-numbers"))]
+#[test]
+fn number_exponent_rejected_by_default() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[n:=1e3]");
+    assert_parse_err(&input, ParseErrorKind::InvalidVariable);
+}
+
+#[cfg(feature = "scientific-numbers")]
+#[test]
+fn number_exponent_accepted() {
+    for value in ["1e3", "-2.5E-4", "6.022e+23"] {
+        let input = format!("PUSH|{AUTH}|sensor_01|[n:={value}]");
+        let frame = parse_uplink(&input).unwrap();
+        let body = match frame.push_body.unwrap() {
+            PushBody::Structured(s) => s,
+            _ => panic!("expected structured"),
+        };
+        assert_eq!(body.variables[0].value, Value::Number(value));
+    }
+}
+
+#[cfg(feature = "scientific-numbers")]
+#[test]
+fn number_malformed_exponent_rejected() {
+    for value in ["e5", "1e", "1e+", "1.e3", "1e3.5"] {
+        let input = format!("PUSH|{AUTH}|sensor_01|[n:={value}]");
+        assert_parse_err(&input, ParseErrorKind::InvalidVariable);
+    }
+}
+
 #[test]
 fn number_large_integer() {
     let input = format!("PUSH|{AUTH}|sensor_01|[n:=999999999999]");
@@ -895,11 +1148,17 @@ fn ack_err_all_12_codes_roundtrip() {
 
 #[test]
 fn ack_nul_byte_rejected() {
-    // NUL in ACK input — depends on implementation
-    let input = "ACK\0OK|3";
-    // parse_ack may or may not check for NUL (it's checked in parse_uplink)
-    // Just verify the parser doesn't panic
-    let _ = parse_ack(input);
+    let err = parse_ack("ACK\0OK|3").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::NulByte);
+    let err = parse_ack("ACK|OK|3\0").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::NulByte);
+}
+
+#[test]
+fn ack_oversized_frame_rejected() {
+    let input = format!("ACK|CMD|{}", "a".repeat(16384));
+    let err = parse_ack(&input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::FrameTooLarge);
 }
 
 #[test]
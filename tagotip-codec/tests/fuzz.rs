@@ -0,0 +1,185 @@
+//! Fuzz-style robustness tests: malformed input must never panic.
+
+use tagotip_codec::parse::{
+    parse_ack, parse_ack_inner, parse_headless, parse_metadata, parse_pull_body, parse_push_body,
+    parse_uplink, parse_variable,
+};
+use tagotip_codec::testutil::XorShift;
+use tagotip_codec::types::Method;
+use tagotip_codec::{ParseError, ParseErrorKind};
+
+/// Fragments biased towards protocol syntax, including multi-byte chars.
+const FRAGMENTS: &[&str] = &[
+    "PUSH",
+    "PULL",
+    "PING",
+    "ACK",
+    "OK",
+    "PONG",
+    "CMD",
+    "ERR",
+    "|",
+    "[",
+    "]",
+    ";",
+    "{",
+    "}",
+    "#",
+    "@",
+    "^",
+    "!",
+    "=",
+    ":=",
+    "?=",
+    "@=",
+    ",",
+    "\\",
+    "\n",
+    ">x",
+    ">b",
+    "4deedd7bab8817ec",
+    "dev1",
+    "temp",
+    "32.5",
+    "-",
+    ".",
+    "0",
+    "1",
+    "é",
+    "€",
+    "😀",
+    "a",
+    "=",
+    " ",
+    "\\|",
+    "\\é",
+];
+
+fn random_input(rng: &mut XorShift) -> String {
+    let len = rng.below(32);
+    (0..len)
+        .map(|_| FRAGMENTS[rng.below(FRAGMENTS.len())])
+        .collect()
+}
+
+/// Error positions must never point into the middle of a multi-byte char.
+fn assert_char_boundary(input: &str, err: Result<(), ParseError>) {
+    if let Err(e) = err {
+        assert!(
+            e.position > input.len() || input.is_char_boundary(e.position),
+            "{input:?}: {e:?}"
+        );
+    }
+}
+
+fn exercise(input: &str) {
+    assert_char_boundary(input, parse_uplink(input).map(drop));
+    assert_char_boundary(input, parse_ack(input).map(drop));
+    let _ = parse_ack_inner(input);
+    for method in [Method::Push, Method::Pull, Method::Ping] {
+        let _ = parse_headless(method, input);
+    }
+    let _ = parse_push_body(input);
+    let _ = parse_pull_body(input);
+    let _ = parse_variable(input);
+    let _ = parse_metadata(input);
+}
+
+/// Every char-boundary prefix of `input`.
+fn prefixes(input: &str) -> impl Iterator<Item = &str> {
+    (0..=input.len())
+        .filter(|&i| input.is_char_boundary(i))
+        .map(|i| &input[..i])
+}
+
+#[test]
+fn random_inputs_never_panic() {
+    let mut rng = XorShift::new(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..20_000 {
+        exercise(&random_input(&mut rng));
+    }
+}
+
+#[test]
+fn xorshift_zero_seed_still_advances() {
+    let mut rng = XorShift::new(0);
+    assert_ne!(rng.next_u64(), 0);
+}
+
+#[test]
+#[should_panic(expected = "n = 0")]
+fn xorshift_below_zero_panics() {
+    XorShift::new(1).below(0);
+}
+
+#[test]
+fn truncated_frames_never_panic() {
+    let frames = [
+        "PUSH|!7|4deedd7bab8817ec|dev1|@1694567890000^grp{fw=1.2}[temp:=32.5#C{k=v};ok?=true]",
+        "PUSH|4deedd7bab8817ec|dev1|[msg=café€;loc@=-23.5,-46.6,760]",
+        "PUSH|4deedd7bab8817ec|dev1|>xDEADBEEF",
+        "PUSH|4deedd7bab8817ec|dev1|>bAQID",
+        "PULL|!3|4deedd7bab8817ec|dev1|[temp;hum]",
+        "PING|4deedd7bab8817ec|dev1",
+        "ACK|!5|ERR|invalid_token",
+        "ACK|OK|[temp:=1;hum:=2]",
+        "ACK|CMD|reboot é",
+    ];
+    for frame in frames {
+        for prefix in prefixes(frame) {
+            exercise(prefix);
+        }
+    }
+}
+
+#[test]
+fn regression_inputs_return_err() {
+    let ack_cases = [
+        ("ACK|!", ParseErrorKind::InvalidSeq),
+        ("ACK|!1", ParseErrorKind::InvalidAck),
+        ("ACK|!é|OK", ParseErrorKind::InvalidSeq),
+    ];
+    for (input, kind) in ack_cases {
+        assert_eq!(parse_ack(input).unwrap_err().kind, kind, "{input}");
+        exercise(input);
+    }
+
+    let uplink_cases = [
+        (
+            "PUSH|4deedd7bab8817ec|dev1|[é:=1]",
+            ParseErrorKind::InvalidField,
+        ),
+        (
+            "PUSH|4deedd7bab8817ec|dev1|[a:=1{é}]",
+            ParseErrorKind::InvalidMetadata,
+        ),
+        (
+            "PUSH|4deedd7bab8817ec|dev1|[a=\\",
+            ParseErrorKind::InvalidVariableBlock,
+        ),
+        (
+            "PUSH|4deedd7bab8817ec|é|[a:=1]",
+            ParseErrorKind::InvalidSerial,
+        ),
+        (
+            "PUSH|!é|4deedd7bab8817ec|dev1|[a:=1]",
+            ParseErrorKind::InvalidSeq,
+        ),
+        (
+            "PUSH|4deedd7bab8817ec|dev1|é[a:=1]",
+            ParseErrorKind::InvalidModifier,
+        ),
+        (
+            "PUSH|4deedd7bab8817ec|dev1|>x€",
+            ParseErrorKind::InvalidPassthrough,
+        ),
+        (
+            "PUSH|4deedd7bab8817ec|dev1|>b😀",
+            ParseErrorKind::InvalidPassthrough,
+        ),
+    ];
+    for (input, kind) in uplink_cases {
+        assert_eq!(parse_uplink(input).unwrap_err().kind, kind, "{input}");
+        exercise(input);
+    }
+}
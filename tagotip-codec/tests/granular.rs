@@ -1,7 +1,11 @@
-use tagotip_codec::build::{build_metadata, build_pull_body, build_push_body, build_variable};
+use tagotip_codec::ParseErrorKind;
+use tagotip_codec::build::{
+    build_metadata, build_pull_body, build_push_body, build_uplink, build_variable,
+};
 use tagotip_codec::parse::{
     ParsedVariable, extract_serial, parse_metadata, parse_method, parse_pull_body, parse_push_body,
-    parse_seq, parse_variable, validate_auth,
+    parse_push_body_truncating, parse_seq, parse_uplink, parse_variable, parse_variables_streaming,
+    peek_auth, peek_serial, validate_auth, validate_uplink,
 };
 use tagotip_codec::types::*;
 
@@ -331,6 +335,7 @@ fn build_push_body_structured() {
         body_meta: None,
         variables,
         meta_pool: tagotip_codec::inline_vec::InlineVec::new(),
+        unknown_modifiers: tagotip_codec::inline_vec::InlineVec::new(),
     });
     let mut buf = [0u8; 256];
     let n = build_push_body(&body, &mut buf).unwrap();
@@ -387,12 +392,268 @@ fn roundtrip_push_body_with_modifiers() {
 #[test]
 fn roundtrip_variable() {
     let input = "temperature:=32.5#C@1694567890000^batch_01";
-    let ParsedVariable {
-        variable: var,
-        meta_pairs: _,
-    } = parse_variable(input).unwrap();
+    let ParsedVariable { variable: var, .. } = parse_variable(input).unwrap();
     let mut buf = [0u8; 256];
     let n = build_variable(&var, &[], &mut buf).unwrap();
     let output = core::str::from_utf8(&buf[..n]).unwrap();
     assert_eq!(output, input);
 }
+
+// =========================================================================
+// Streaming variables
+// =========================================================================
+
+#[test]
+fn streaming_exceeds_max_variables() {
+    use std::fmt::Write;
+
+    let mut body = String::from("[");
+    for i in 0..150 {
+        if i > 0 {
+            body.push(';');
+        }
+        write!(body, "v{i}:={i}").unwrap();
+    }
+    body.push(']');
+
+    assert!(parse_push_body(&body).is_err());
+
+    let mut count = 0;
+    for (i, result) in parse_variables_streaming(&body).enumerate() {
+        let parsed = result.unwrap();
+        assert_eq!(parsed.variable.name, format!("v{i}"));
+        count += 1;
+    }
+    assert_eq!(count, 150);
+}
+
+#[test]
+fn streaming_keeps_metadata_and_escapes() {
+    let vars: Vec<_> = parse_variables_streaming(r"msg=a\;b;temp:=1{k=v}")
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(vars.len(), 2);
+    assert_eq!(vars[0].variable.name, "msg");
+    assert_eq!(vars[1].meta_pairs.as_ref().unwrap()[0].key, "k");
+}
+
+#[test]
+fn streaming_stops_after_error() {
+    let mut stream = parse_variables_streaming("[a:=1;b?=x;c:=3]");
+    assert!(stream.next().unwrap().is_ok());
+    let err = stream.next().unwrap().unwrap_err();
+    assert_eq!(err.position, 9);
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn variables_iter_pairs_metadata() {
+    let body = match parse_push_body("[a:=1{k=v};b:=2]").unwrap() {
+        PushBody::Structured(s) => s,
+        PushBody::Passthrough(_) => panic!("expected structured body"),
+    };
+    let items: Vec<_> = body.variables_iter().collect();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].0.name, "a");
+    assert_eq!(items[0].1.len(), 1);
+    assert!(items[1].1.is_empty());
+}
+
+#[test]
+fn remove_variable_keeps_remaining_metadata() {
+    let mut body = match parse_push_body("{src=gw}[a:=1{k=a};b:=2{k=b,x=y};c:=3{k=c}]").unwrap() {
+        PushBody::Structured(s) => s,
+        PushBody::Passthrough(_) => panic!("expected structured body"),
+    };
+    let removed = body.remove_variable(1).unwrap();
+    assert_eq!(removed.name, "b");
+    assert!(removed.meta.is_none());
+    assert_eq!(body.meta_pool.len(), 3);
+
+    let names: Vec<_> = body
+        .variables_iter()
+        .map(|(v, meta)| (v.name, meta[0].value))
+        .collect();
+    assert_eq!(names, [("a", "a"), ("c", "c")]);
+    assert_eq!(body.body_metadata()[0].value, "gw");
+    assert!(body.remove_variable(2).is_none());
+}
+
+fn structured(body: &str) -> StructuredBody<'_> {
+    match parse_push_body(body).unwrap() {
+        PushBody::Structured(s) => s,
+        PushBody::Passthrough(_) => panic!("expected structured body"),
+    }
+}
+
+#[test]
+fn effective_group_and_timestamp_inherit_body_defaults() {
+    let body = structured("@1700000000^batch_42[a:=1@1700000001^group1;b:=2]");
+    let (a, b) = (&body.variables[0], &body.variables[1]);
+    assert_eq!(body.effective_group(a), Some("group1"));
+    assert_eq!(body.effective_group(b), Some("batch_42"));
+    assert_eq!(body.effective_timestamp(a), Some("1700000001"));
+    assert_eq!(body.effective_timestamp(b), Some("1700000000"));
+
+    let bare = structured("[a:=1]");
+    assert_eq!(bare.effective_group(&bare.variables[0]), None);
+    assert_eq!(bare.effective_timestamp(&bare.variables[0]), None);
+}
+
+#[test]
+fn resolve_defaults_moves_modifiers_to_variables() {
+    let input = "PUSH|4deedd7bab8817ec|sensor_01|@1700000000^batch_42[a:=1^group1;b:=2]";
+    let mut frame = parse_uplink(input).unwrap();
+    let Some(PushBody::Structured(body)) = frame.push_body.as_mut() else {
+        panic!("expected structured body");
+    };
+    assert!(body.has_body_defaults());
+    body.resolve_defaults();
+    assert!(!body.has_body_defaults());
+
+    let mut buf = [0u8; 128];
+    let n = build_uplink(&frame, &mut buf).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buf[..n]).unwrap(),
+        "PUSH|4deedd7bab8817ec|sensor_01|[a:=1@1700000000^group1;b:=2@1700000000^batch_42]"
+    );
+    assert!(!structured("[a:=1]").has_body_defaults());
+}
+
+#[test]
+fn truncating_parse_keeps_first_variables() {
+    let vars: Vec<String> = (0..105).map(|i| format!("v{i}:=0{{k=v}}")).collect();
+    let input = format!("[{}]", vars.join(";"));
+    assert_eq!(
+        parse_push_body(&input).unwrap_err().kind,
+        ParseErrorKind::TooManyItems
+    );
+
+    let (body, dropped) = parse_push_body_truncating(&input).unwrap();
+    assert_eq!(dropped, 5);
+    let PushBody::Structured(body) = body else {
+        panic!("expected structured body");
+    };
+    assert_eq!(body.variables.len(), 100);
+    assert_eq!(body.variables[99].name, "v99");
+    // Dropped variables leave no metadata behind.
+    assert_eq!(body.meta_pool.len(), 100);
+}
+
+#[test]
+fn try_append_rebases_metadata() {
+    let mut a = structured("{src=gw}[t:=1{k=t};u:=2]");
+    let b = structured("{src=other}[h:=3{k=h,x=y};p:=4{k=p}]");
+    a.try_append(&b).unwrap();
+
+    let merged: Vec<_> = a
+        .variables_iter()
+        .map(|(v, meta)| (v.name, meta.iter().map(|p| p.value).collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        merged,
+        [
+            ("t", vec!["t"]),
+            ("u", vec![]),
+            ("h", vec!["h", "y"]),
+            ("p", vec!["p"]),
+        ]
+    );
+    assert_eq!(a.body_metadata()[0].value, "gw");
+    assert_eq!(a.meta_pool.len(), 5);
+}
+
+#[test]
+fn try_append_overflow_leaves_body_unchanged() {
+    let vars: Vec<String> = (0..60).map(|i| format!("v{i}:=0")).collect();
+    let body = format!("[{}]", vars.join(";"));
+    let mut a = structured(&body);
+    let b = structured(&body);
+    let before = a.clone();
+    let err = a.try_append(&b).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::TooManyItems);
+    assert_eq!(a, before);
+}
+
+// =========================================================================
+// Peek serial
+// =========================================================================
+
+#[test]
+fn peek_serial_push() {
+    let serial = peek_serial("PUSH|4deedd7bab8817ec|sensor_01|[temp:=32]").unwrap();
+    assert_eq!(serial, "sensor_01");
+}
+
+#[test]
+fn peek_serial_push_with_seq() {
+    let serial = peek_serial("PUSH|!42|4deedd7bab8817ec|sensor_01|[temp:=32]\n").unwrap();
+    assert_eq!(serial, "sensor_01");
+}
+
+#[test]
+fn peek_serial_ping() {
+    assert_eq!(peek_serial("PING|4deedd7bab8817ec|dev-1").unwrap(), "dev-1");
+}
+
+#[test]
+fn peek_serial_ignores_body() {
+    // The body is invalid, but only the header is inspected.
+    let serial = peek_serial("PUSH|4deedd7bab8817ec|sensor_01|[temp?=maybe]").unwrap();
+    assert_eq!(serial, "sensor_01");
+}
+
+#[test]
+fn peek_serial_rejects_bad_header() {
+    let err = peek_serial("PUSH|nothex|sensor_01|[temp:=32]").unwrap_err();
+    assert_eq!(err.kind, tagotip_codec::ParseErrorKind::InvalidAuth);
+    assert!(peek_serial("PING|4deedd7bab8817ec").is_err());
+}
+
+#[test]
+fn peek_auth_with_seq() {
+    let auth = peek_auth("PUSH|!42|4deedd7bab8817ec|sensor_01|[temp:=32]").unwrap();
+    assert_eq!(auth, "4deedd7bab8817ec");
+}
+
+#[test]
+fn peek_auth_without_seq() {
+    assert_eq!(
+        peek_auth("PING|4DEEDD7BAB8817EC|dev-1").unwrap(),
+        "4DEEDD7BAB8817EC"
+    );
+}
+
+#[test]
+fn peek_auth_rejects_bad_auth() {
+    let err = peek_auth("PUSH|!42|4deedd7bab8817e|sensor_01|[temp:=32]").unwrap_err();
+    assert_eq!(err.kind, tagotip_codec::ParseErrorKind::InvalidAuth);
+    assert_eq!(err.position, 9);
+}
+
+// =========================================================================
+// validate_uplink
+// =========================================================================
+
+#[test]
+fn validate_uplink_matches_parse_uplink() {
+    assert_eq!(
+        validate_uplink("PUSH|4deedd7bab8817ec|dev|[temp:=32#C{k=v}]"),
+        Ok(())
+    );
+    assert_eq!(validate_uplink("PING|4deedd7bab8817ec|dev"), Ok(()));
+
+    for input in [
+        "",
+        "POST|4deedd7bab8817ec|dev|[a:=1]",
+        "PUSH|!01|4deedd7bab8817ec|dev|[a:=1]",
+        "PUSH|nothex|dev|[a:=1]",
+        "PUSH|4deedd7bab8817ec|dev",
+        "PUSH|4deedd7bab8817ec|dev|[a:=01]",
+        "PUSH|4deedd7bab8817ec|dev|[a:=1{}]",
+        "PULL|4deedd7bab8817ec|dev|[]",
+    ] {
+        let expected = parse_uplink(input).unwrap_err();
+        assert_eq!(validate_uplink(input), Err(expected), "{input:?}");
+    }
+}
@@ -0,0 +1,113 @@
+use tagotip_codec::inline_vec::InlineVec;
+use tagotip_codec::types::Value;
+
+#[test]
+fn try_extend_from_slice_fits() {
+    let mut v: InlineVec<u8, 4> = InlineVec::new();
+    v.push(1).unwrap();
+    assert_eq!(v.try_extend_from_slice(&[2, 3, 4]), Ok(()));
+    assert_eq!(v.as_slice(), &[1, 2, 3, 4]);
+}
+
+#[test]
+fn try_extend_from_slice_reports_overflow_index() {
+    let mut v: InlineVec<u8, 4> = InlineVec::new();
+    v.push(1).unwrap();
+    assert_eq!(v.try_extend_from_slice(&[2, 3, 4, 5, 6]), Err(3));
+    assert_eq!(v.len(), v.capacity());
+    assert_eq!(v.as_slice(), &[1, 2, 3, 4]);
+}
+
+#[test]
+fn try_extend_from_empty_slice() {
+    let mut v: InlineVec<u8, 0> = InlineVec::new();
+    assert_eq!(v.try_extend_from_slice(&[]), Ok(()));
+    assert_eq!(v.try_extend_from_slice(&[1]), Err(0));
+}
+
+#[test]
+fn for_loop_over_reference() {
+    let v: InlineVec<u8, 4> = InlineVec::from_slice(&[1, 2, 3]).unwrap();
+    let mut sum = 0;
+    for x in &v {
+        sum += *x;
+    }
+    assert_eq!(sum, 6);
+}
+
+#[test]
+fn from_slice_fits() {
+    let v: InlineVec<&str, 3> = InlineVec::from_slice(&["a", "b", "c"]).unwrap();
+    assert_eq!(v.as_slice(), &["a", "b", "c"]);
+    assert!(InlineVec::<u8, 3>::from_slice(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn from_slice_too_long() {
+    assert!(InlineVec::<u8, 2>::from_slice(&[1, 2, 3]).is_err());
+}
+
+#[test]
+fn remove_middle_element() {
+    let mut v: InlineVec<u8, 4> = InlineVec::from_slice(&[1, 2, 3]).unwrap();
+    assert_eq!(v.remove(1), Some(2));
+    assert_eq!(v.as_slice(), &[1, 3]);
+    assert_eq!(v.remove(2), None);
+    assert_eq!(v.remove(1), Some(3));
+    assert_eq!(v.as_slice(), &[1]);
+}
+
+#[test]
+fn retain_keeps_only_numbers() {
+    let mut v: InlineVec<Value<'_>, 8> = InlineVec::new();
+    v.push(Value::Number("1")).unwrap();
+    v.push(Value::Boolean(true)).unwrap();
+    v.push(Value::String("x")).unwrap();
+    v.push(Value::Number("2.5")).unwrap();
+    v.push(Value::Boolean(false)).unwrap();
+    v.retain(|val| matches!(val, Value::Number(_)));
+    assert_eq!(v.as_slice(), &[Value::Number("1"), Value::Number("2.5")]);
+}
+
+#[test]
+fn insert_shifts_elements() {
+    let mut v: InlineVec<u8, 4> = InlineVec::from_slice(&[1, 3]).unwrap();
+    v.insert(1, 2).unwrap();
+    v.insert(3, 4).unwrap();
+    assert_eq!(v.as_slice(), &[1, 2, 3, 4]);
+    assert_eq!(v.insert(0, 0), Err(0));
+}
+
+#[test]
+fn insert_past_end_rejected() {
+    let mut v: InlineVec<u8, 4> = InlineVec::from_slice(&[1]).unwrap();
+    assert_eq!(v.insert(2, 9), Err(9));
+    assert_eq!(v.as_slice(), &[1]);
+}
+
+#[test]
+fn push_truncating_drops_when_full() {
+    let mut v: InlineVec<u8, 2> = InlineVec::new();
+    assert!(v.push_truncating(1));
+    assert!(v.push_truncating(2));
+    assert!(!v.push_truncating(3));
+    assert_eq!(v.as_slice(), &[1, 2]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip_and_capacity_check() {
+    let v: InlineVec<u32, 4> = InlineVec::from_slice(&[1, 2, 3]).unwrap();
+    let json = serde_json::to_string(&v).unwrap();
+    assert_eq!(json, "[1,2,3]");
+    let back: InlineVec<u32, 4> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, v);
+
+    assert!(
+        serde_json::from_str::<InlineVec<u32, 4>>("[]")
+            .unwrap()
+            .is_empty()
+    );
+    let err = serde_json::from_str::<InlineVec<u32, 4>>("[1,2,3,4,5]").unwrap_err();
+    assert!(err.to_string().contains("at most 4"));
+}
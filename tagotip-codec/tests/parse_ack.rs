@@ -1,5 +1,8 @@
-use tagotip_codec::parse::parse_ack;
+use tagotip_codec::parse::{
+    ParseOptions, parse_ack, parse_ack_inner_with, parse_ack_many, parse_ack_with,
+};
 use tagotip_codec::types::*;
+use tagotip_codec::{ParseError, ParseErrorKind};
 
 #[test]
 fn ack_ok_count() {
@@ -34,6 +37,28 @@ fn ack_pong() {
     assert!(frame.detail.is_none());
 }
 
+#[test]
+fn ack_pong_detail_lenient_is_raw() {
+    let frame = parse_ack("ACK|PONG|oops").unwrap();
+    assert_eq!(frame.status, AckStatus::Pong);
+    assert_eq!(frame.detail, Some(AckDetail::Raw("oops")));
+}
+
+#[test]
+fn ack_pong_detail_strict_rejected() {
+    let err = parse_ack_with("ACK|PONG|oops", &ParseOptions::strict()).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAck);
+    assert_eq!(err.position, 9);
+
+    let err = parse_ack_with("ACK|!3|PONG|oops", &ParseOptions::strict()).unwrap_err();
+    assert_eq!(err.position, 12);
+
+    let err = parse_ack_inner_with("PONG|oops", &ParseOptions::strict()).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAck);
+
+    assert!(parse_ack_with("ACK|PONG", &ParseOptions::strict()).is_ok());
+}
+
 #[test]
 fn ack_cmd() {
     let frame = parse_ack("ACK|CMD|reboot").unwrap();
@@ -104,6 +129,26 @@ fn ack_err_all_error_codes() {
     }
 }
 
+#[test]
+fn error_code_as_str_round_trips() {
+    assert_eq!(ErrorCode::all().len(), 12);
+    assert_eq!(ErrorCode::all().last(), Some(&ErrorCode::Unknown));
+
+    assert_eq!(ErrorCode::Unknown.as_str(), None);
+    for &code in &ErrorCode::all()[..ErrorCode::all().len() - 1] {
+        let canonical = code.as_str().unwrap();
+        let input = format!("ACK|ERR|{canonical}");
+        let frame = parse_ack(&input).unwrap();
+        match frame.detail {
+            Some(AckDetail::Error { code: parsed, text }) => {
+                assert_eq!(parsed, code);
+                assert_eq!(text, canonical);
+            }
+            _ => panic!("expected Error detail for: {code:?}"),
+        }
+    }
+}
+
 // --- With sequence counter ---
 
 #[test]
@@ -161,3 +206,131 @@ fn ack_trailing_newline() {
     let frame = parse_ack("ACK|OK|3\n").unwrap();
     assert_eq!(frame.detail, Some(AckDetail::Count(3)));
 }
+
+#[test]
+fn ack_error_list_needs_option() {
+    let input = "ACK|ERR|invalid_seq;rate_limited";
+
+    let frame = parse_ack(input).unwrap();
+    assert!(matches!(
+        frame.detail,
+        Some(AckDetail::Error {
+            code: ErrorCode::Unknown,
+            text: "invalid_seq;rate_limited"
+        })
+    ));
+
+    let opts = ParseOptions {
+        allow_error_list: true,
+        ..ParseOptions::default()
+    };
+    let frame = parse_ack_with(input, &opts).unwrap();
+    let Some(AckDetail::Errors(errors)) = &frame.detail else {
+        panic!("expected error list, got {:?}", frame.detail);
+    };
+    assert_eq!(errors.as_str(), "invalid_seq;rate_limited");
+    assert_eq!(
+        errors.iter().collect::<Vec<_>>(),
+        [
+            (ErrorCode::InvalidSeq, "invalid_seq"),
+            (ErrorCode::RateLimited, "rate_limited"),
+        ]
+    );
+
+    let mut buf = [0u8; 64];
+    let n = tagotip_codec::build::build_ack(&frame, &mut buf).unwrap();
+    assert_eq!(&buf[..n], input.as_bytes());
+
+    // The list borrows the detail text, so it does not bloat the frame.
+    assert!(core::mem::size_of::<AckFrame<'_>>() <= 48);
+
+    // A single code is still a plain `Error` under the option.
+    let frame = parse_ack_with("ACK|ERR|invalid_seq", &opts).unwrap();
+    assert!(matches!(frame.detail, Some(AckDetail::Error { .. })));
+}
+
+#[test]
+fn ack_error_list_rejects_empty_entries() {
+    let opts = ParseOptions {
+        allow_error_list: true,
+        ..ParseOptions::default()
+    };
+    let err = parse_ack_with("ACK|ERR|invalid_seq;", &opts).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAck);
+    assert_eq!(err.position, 20);
+    let err = parse_ack_inner_with("ERR|;invalid_seq", &opts).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAck);
+    assert_eq!(err.position, 4);
+}
+
+#[test]
+fn parse_ack_many_mixed_batch() {
+    let input = "ACK|!1|OK|3\nACK|ERR|invalid_token\nACK|!3x|OK\nACK|PONG\n";
+    let mut out = [(); 8].map(|()| Err(ParseError::new(ParseErrorKind::EmptyFrame, 0)));
+    let n = parse_ack_many(input, &mut out);
+    assert_eq!(n, 4);
+
+    let first = out[0].as_ref().unwrap();
+    assert_eq!(first.seq, Some(1));
+    assert_eq!(first.detail, Some(AckDetail::Count(3)));
+    assert!(matches!(
+        out[1].as_ref().unwrap().detail,
+        Some(AckDetail::Error {
+            code: ErrorCode::InvalidToken,
+            ..
+        })
+    ));
+    // Same error, at the same position, as parsing the line on its own.
+    let err = out[2].as_ref().unwrap_err();
+    assert_eq!(*err, parse_ack("ACK|!3x|OK").unwrap_err());
+    assert_eq!(err.position, 4);
+    assert_eq!(out[3].as_ref().unwrap().status, AckStatus::Pong);
+}
+
+#[test]
+fn parse_ack_many_stops_when_full() {
+    let mut out = [(); 2].map(|()| Err(ParseError::new(ParseErrorKind::EmptyFrame, 0)));
+    assert_eq!(parse_ack_many("ACK|OK\nACK|OK\nACK|OK", &mut out), 2);
+    assert!(out.iter().all(Result::is_ok));
+}
+
+#[test]
+fn ack_detail_keeps_embedded_pipes() {
+    let frame = parse_ack("ACK|CMD|a|b").unwrap();
+    assert_eq!(frame.detail, Some(AckDetail::Command("a|b")));
+
+    let frame = parse_ack("ACK|!4|CMD|ota=https://host/fw?v=2|sig=ab|c").unwrap();
+    assert_eq!(
+        frame.detail,
+        Some(AckDetail::Command("ota=https://host/fw?v=2|sig=ab|c"))
+    );
+
+    let inner = parse_ack_inner_with("CMD|a|b", &ParseOptions::default()).unwrap();
+    assert_eq!(inner.detail, Some(AckDetail::Command("a|b")));
+}
+
+#[test]
+fn ack_ok_variables_keep_escaped_pipe() {
+    let input = r"ACK|!2|OK|[msg=a\|b;temp:=21]";
+    let frame = parse_ack(input).unwrap();
+    assert_eq!(
+        frame.detail,
+        Some(AckDetail::Variables(r"[msg=a\|b;temp:=21]"))
+    );
+
+    let inner = parse_ack_inner_with(r"OK|[msg=a\|b]", &ParseOptions::default()).unwrap();
+    assert_eq!(inner.detail, Some(AckDetail::Variables(r"[msg=a\|b]")));
+}
+
+#[test]
+fn truncated_ack_points_past_last_field() {
+    let err = parse_ack("ACK|!5").unwrap_err();
+    assert_eq!(err, ParseError::new(ParseErrorKind::InvalidAck, 6));
+    let err = parse_ack("ACK").unwrap_err();
+    assert_eq!(err, ParseError::new(ParseErrorKind::InvalidAck, 3));
+    // An unknown status is reported where the status field starts.
+    let err = parse_ack("ACK|!5|NOPE").unwrap_err();
+    assert_eq!(err, ParseError::new(ParseErrorKind::InvalidAck, 7));
+    let err = parse_ack("ACK|!5|").unwrap_err();
+    assert_eq!(err, ParseError::new(ParseErrorKind::InvalidAck, 7));
+}
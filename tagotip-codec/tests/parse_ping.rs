@@ -1,4 +1,5 @@
-use tagotip_codec::parse::parse_uplink;
+use tagotip_codec::ParseErrorKind;
+use tagotip_codec::parse::{ParseOptions, parse_uplink, parse_uplink_with};
 use tagotip_codec::types::*;
 
 const AUTH: &str = "4deedd7bab8817ec";
@@ -34,3 +35,25 @@ fn ping_invalid_auth_rejected() {
     let input = "PING|invalid_auth|sensor_01";
     assert!(parse_uplink(input).is_err());
 }
+
+#[test]
+fn ping_empty_auth_needs_option() {
+    let input = "PING||sensor_01";
+    let err = parse_uplink(input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAuth);
+
+    let opts = ParseOptions {
+        allow_empty_auth_on_ping: true,
+        ..ParseOptions::default()
+    };
+    let frame = parse_uplink_with(input, &opts).unwrap();
+    assert_eq!(frame.method, Method::Ping);
+    assert_eq!(frame.auth, "");
+    assert_eq!(frame.serial, "sensor_01");
+
+    // PUSH and PULL still need a real auth hash.
+    let err = parse_uplink_with("PUSH||sensor_01|[temp:=1]", &opts).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidAuth);
+    assert!(parse_uplink_with("PULL||sensor_01|[temp]", &opts).is_err());
+    assert!(parse_uplink_with("PING|nothex|sensor_01", &opts).is_err());
+}
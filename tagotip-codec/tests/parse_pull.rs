@@ -50,3 +50,31 @@ fn pull_missing_brackets_rejected() {
     let input = format!("PULL|{AUTH}|sensor_01|temperature");
     assert!(parse_uplink(&input).is_err());
 }
+
+#[test]
+fn pull_dedup_keeps_first_occurrence() {
+    let input = format!("PULL|{AUTH}|sensor_01|[temp;temp;humidity;temp]");
+    let mut pull = parse_uplink(&input).unwrap().pull_body.unwrap();
+    assert_eq!(pull.variables.len(), 4);
+    pull.dedup();
+    assert_eq!(pull.variables.as_slice(), &["temp", "humidity"]);
+}
+
+#[test]
+fn pull_contains() {
+    let input = format!("PULL|{AUTH}|sensor_01|[temp;humidity]");
+    let pull = parse_uplink(&input).unwrap().pull_body.unwrap();
+    assert!(pull.contains("humidity"));
+    assert!(!pull.contains("hum"));
+}
+
+#[test]
+fn pull_from_names_validates() {
+    let pull = PullBody::from_names(&["temp", "humidity"]).unwrap();
+    assert_eq!(pull.variables.as_slice(), &["temp", "humidity"]);
+
+    let err = PullBody::from_names(&["temp", "Humidity"]).unwrap_err();
+    assert_eq!(err.kind, tagotip_codec::ParseErrorKind::InvalidField);
+    assert_eq!(err.position, 1);
+    assert!(PullBody::from_names(&[]).is_err());
+}
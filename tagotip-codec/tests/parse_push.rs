@@ -1,5 +1,11 @@
-use tagotip_codec::parse::parse_uplink;
+use tagotip_codec::build::build_uplink_line;
+use tagotip_codec::inline_vec::InlineVec;
+use tagotip_codec::parse::{
+    ParseOptions, SerialPolicy, parse_headless_with, parse_uplink, parse_uplink_diagnostics,
+    parse_uplink_line, parse_uplink_prefix, parse_uplink_trimmed, parse_uplink_with,
+};
 use tagotip_codec::types::*;
+use tagotip_codec::{ParseError, ParseErrorKind};
 
 const AUTH: &str = "4deedd7bab8817ec";
 
@@ -24,6 +30,22 @@ fn simple_push_two_variables() {
     assert_eq!(body.variables[1].value, Value::Number("65"));
 }
 
+#[cfg(feature = "source-spans")]
+#[test]
+fn variable_source_spans_point_into_input() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[a:=1;bb:=2]");
+    let open = input.find('[').unwrap();
+    let close = input.rfind(']').unwrap();
+    let spans: Vec<&str> =
+        tagotip_codec::parse::VariablesStream::new(&input[open + 1..close], open + 1)
+            .map(|v| {
+                let (start, end) = v.unwrap().source_span;
+                &input[start..end]
+            })
+            .collect();
+    assert_eq!(spans, ["a:=1", "bb:=2"]);
+}
+
 #[test]
 fn push_with_seq() {
     let input = format!("PUSH|!42|{AUTH}|sensor_01|[temp:=25]");
@@ -32,6 +54,18 @@ fn push_with_seq() {
     assert_eq!(frame.serial, "sensor_01");
 }
 
+#[test]
+fn requires_seq_ack_distinguishes_zero_from_absent() {
+    let input = format!("PUSH|!0|{AUTH}|sensor_01|[temp:=25]");
+    let zero = parse_uplink(&input).unwrap();
+    assert_eq!(zero.seq, Some(0));
+    assert!(zero.requires_seq_ack());
+
+    let input = format!("PUSH|{AUTH}|sensor_01|[temp:=25]");
+    let none = parse_uplink(&input).unwrap();
+    assert!(!none.requires_seq_ack());
+}
+
 #[test]
 fn push_typed_values() {
     let input = format!("PUSH|{AUTH}|sensor_0a1f|[temperature:=32.5#C;status=online;active?=true]");
@@ -255,3 +289,452 @@ fn push_trailing_newline_accepted() {
     let frame = parse_uplink(&input).unwrap();
     assert_eq!(frame.method, Method::Push);
 }
+
+#[test]
+fn push_crlf_needs_trimmed_parse() {
+    // The `\r` lands in the serial or passthrough field without trimming.
+    let ping = format!("PING|{AUTH}|sensor_01\r\n");
+    assert!(parse_uplink(&ping).is_err());
+    assert_eq!(parse_uplink_trimmed(&ping).unwrap().serial, "sensor_01");
+    let hex = format!("PUSH|{AUTH}|sensor_01|>xBEEF\r\n");
+    assert!(parse_uplink(&hex).is_err());
+    assert!(parse_uplink_trimmed(&hex).is_ok());
+
+    let input = format!("PUSH|{AUTH}|sensor_01|[temp:=32]\r\n");
+    let frame = parse_uplink_trimmed(&input).unwrap();
+    let bare = format!("PUSH|{AUTH}|sensor_01|[temp:=32]");
+    let expected = parse_uplink(&bare).unwrap();
+    assert_eq!(frame, expected);
+
+    for terminator in ["", "\n", "\r", "\n\n", "\r\n\r\n"] {
+        let input = format!("PUSH|{AUTH}|sensor_01|[temp:=32]{terminator}");
+        assert_eq!(parse_uplink_trimmed(&input).unwrap(), expected);
+    }
+}
+
+#[test]
+fn header_whitespace_reported_at_its_offset() {
+    // Serial field starts at 22.
+    let input = format!("PUSH|{AUTH}| dev1|[temp:=32]");
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidSerial);
+    assert_eq!(err.position, 22);
+
+    let input = format!("PUSH|{AUTH}|dev1 |[temp:=32]");
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!(
+        (err.kind, err.position),
+        (ParseErrorKind::InvalidSerial, 26)
+    );
+
+    let err = parse_uplink("PUSH| 4deedd7bab8817ec|dev1|[temp:=32]").unwrap_err();
+    assert_eq!((err.kind, err.position), (ParseErrorKind::InvalidAuth, 5));
+    let err = parse_uplink("PUSH|4deedd7bab8817\tc|dev1|[temp:=32]").unwrap_err();
+    assert_eq!((err.kind, err.position), (ParseErrorKind::InvalidAuth, 19));
+
+    // Other character-class errors still point at the field start.
+    let input = format!("PUSH|{AUTH}|de.v1|[temp:=32]");
+    assert_eq!(parse_uplink(&input).unwrap_err().position, 22);
+}
+
+// --- Diagnostics mode ---
+
+#[test]
+fn diagnostics_collects_all_bad_variables() {
+    let input = format!("PUSH|{AUTH}|dev1|[x?=maybe;temp:=32;y:=01]");
+    let mut errors: InlineVec<ParseError, 8> = InlineVec::new();
+    let frame = parse_uplink_diagnostics(&input, &mut errors).unwrap();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidVariable);
+    assert_eq!(errors[1].kind, ParseErrorKind::InvalidVariable);
+    assert!(errors[0].position < errors[1].position);
+
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured body"),
+    };
+    assert_eq!(body.variables.len(), 1);
+    assert_eq!(body.variables[0].name, "temp");
+}
+
+#[test]
+fn diagnostics_clean_frame_has_no_errors() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32;hum:=65]");
+    let mut errors: InlineVec<ParseError, 4> = InlineVec::new();
+    let frame = parse_uplink_diagnostics(&input, &mut errors).unwrap();
+    assert!(errors.is_empty());
+    assert_eq!(frame, parse_uplink(&input).unwrap());
+}
+
+#[test]
+fn diagnostics_header_error_is_fatal() {
+    let input = "PUSH|badauth|dev1|[temp:=32;x?=maybe]";
+    let mut errors: InlineVec<ParseError, 4> = InlineVec::new();
+    assert!(parse_uplink_diagnostics(input, &mut errors).is_none());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidAuth);
+}
+
+#[test]
+fn diagnostics_all_variables_bad_returns_none() {
+    let input = format!("PUSH|{AUTH}|dev1|[x?=maybe;y:=01]");
+    let mut errors: InlineVec<ParseError, 4> = InlineVec::new();
+    assert!(parse_uplink_diagnostics(&input, &mut errors).is_none());
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn diagnostics_error_buffer_full_drops_extra() {
+    let input = format!("PUSH|{AUTH}|dev1|[a?=x;b?=x;c?=x;ok:=1]");
+    let mut errors: InlineVec<ParseError, 2> = InlineVec::new();
+    let frame = parse_uplink_diagnostics(&input, &mut errors);
+    assert!(frame.is_some());
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn diagnostics_reused_buffer_still_records_block_error() {
+    let mut errors: InlineVec<ParseError, 4> = InlineVec::new();
+    errors
+        .push(ParseError::new(ParseErrorKind::InvalidVariable, 0))
+        .unwrap();
+    let input = format!("PUSH|{AUTH}|dev1|[]");
+    assert!(parse_uplink_diagnostics(&input, &mut errors).is_none());
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[1].kind, ParseErrorKind::InvalidVariableBlock);
+}
+
+// --- Strict parse options ---
+
+#[test]
+fn strict_distinct_meta_keys_ok() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1,b=2}}]");
+    let frame = parse_uplink_with(&input, &ParseOptions::strict()).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured body"),
+    };
+    assert_eq!(body.variable_metadata(&body.variables[0]).len(), 2);
+}
+
+#[test]
+fn strict_duplicate_meta_key_rejected() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1,a=2}}]");
+    let err = parse_uplink_with(&input, &ParseOptions::strict()).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidMetadata);
+    assert_eq!(err.position, input.find("a=2").unwrap());
+}
+
+#[test]
+fn lenient_duplicate_meta_key_accepted() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1,a=2}}]");
+    assert!(parse_uplink(&input).is_ok());
+    assert!(parse_uplink_with(&input, &ParseOptions::default()).is_ok());
+}
+
+#[test]
+fn strict_duplicate_body_meta_key_rejected() {
+    let err = parse_headless_with(
+        Method::Push,
+        "dev1|{a=1,a=2}[temp:=32]",
+        &ParseOptions::strict(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidMetadata);
+    assert_eq!(err.position, 10);
+}
+
+// --- Semantic equality ---
+
+#[test]
+fn semantic_eq_ignores_meta_order() {
+    let a_input = format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1,b=2}}]");
+    let b_input = format!("PUSH|{AUTH}|dev1|[temp:=32{{b=2,a=1}}]");
+    let a = parse_uplink(&a_input).unwrap();
+    let b = parse_uplink(&b_input).unwrap();
+    assert_ne!(a, b);
+    assert!(a.semantic_eq(&b));
+}
+
+#[test]
+fn semantic_eq_ignores_pool_layout() {
+    // Reordering the pool changes the ranges but not the meaning.
+    let input = format!("PUSH|{AUTH}|dev1|{{s=x}}[t:=1{{k=v}}]");
+    let a = parse_uplink(&input).unwrap();
+    let mut b = a.clone();
+    if let Some(PushBody::Structured(body)) = &mut b.push_body {
+        body.meta_pool.as_mut_slice().swap(0, 1);
+        body.body_meta = Some(MetaRange { start: 1, len: 1 });
+        body.variables[0].meta = Some(MetaRange { start: 0, len: 1 });
+    }
+    assert_ne!(a, b);
+    assert!(a.semantic_eq(&b));
+}
+
+#[test]
+fn semantic_eq_detects_differences() {
+    let base = format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1,b=2}}]");
+    let base = parse_uplink(&base).unwrap();
+    for other in [
+        format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1,b=3}}]"),
+        format!("PUSH|{AUTH}|dev1|[temp:=32{{a=1}}]"),
+        format!("PUSH|{AUTH}|dev1|[temp:=33{{a=1,b=2}}]"),
+        format!("PUSH|{AUTH}|dev2|[temp:=32{{a=1,b=2}}]"),
+    ] {
+        assert!(!base.semantic_eq(&parse_uplink(&other).unwrap()), "{other}");
+    }
+}
+
+// --- Timestamp range ---
+
+#[test]
+fn oversized_timestamp_lenient_by_default() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32@99999999999999999999]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured body"),
+    };
+    assert_eq!(body.variables[0].timestamp_u64(), None);
+}
+
+#[test]
+fn oversized_variable_timestamp_rejected_in_strict_mode() {
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=32@99999999999999999999]");
+    let err = parse_uplink_with(&input, &ParseOptions::strict()).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidVariable);
+    assert_eq!(err.position, input.find("999").unwrap());
+}
+
+#[test]
+fn oversized_body_timestamp_rejected_in_strict_mode() {
+    let input = format!("PUSH|{AUTH}|dev1|@99999999999999999999[temp:=32]");
+    let err = parse_uplink_with(&input, &ParseOptions::strict()).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidModifier);
+    assert_eq!(err.position, input.find("999").unwrap());
+}
+
+#[test]
+fn u64_max_timestamp_accepted_in_strict_mode() {
+    let input = format!("PUSH|{AUTH}|dev1|@18446744073709551615[temp:=32@18446744073709551615]");
+    assert!(parse_uplink_with(&input, &ParseOptions::strict()).is_ok());
+}
+
+// --- Empty string values ---
+
+#[test]
+fn empty_string_value_needs_option() {
+    let input = format!("PUSH|{AUTH}|dev1|[status=]");
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidVariable);
+
+    let opts = ParseOptions {
+        allow_empty_string: true,
+        ..ParseOptions::default()
+    };
+    let frame = parse_uplink_with(&input, &opts).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured body"),
+    };
+    assert_eq!(body.variables[0].value, Value::String(""));
+}
+
+#[test]
+fn empty_string_value_round_trips_with_option() {
+    let opts = ParseOptions {
+        allow_empty_string: true,
+        ..ParseOptions::default()
+    };
+    for input in [
+        format!("PUSH|{AUTH}|dev1|[status=;temp:=1]"),
+        format!("PUSH|{AUTH}|dev1|[status=#u@1^g{{k=v}}]"),
+    ] {
+        let frame = parse_uplink_with(&input, &opts).unwrap();
+        let mut buf = [0u8; 256];
+        let n = tagotip_codec::build::build_uplink(&frame, &mut buf).unwrap();
+        assert_eq!(core::str::from_utf8(&buf[..n]).unwrap(), input);
+    }
+}
+
+#[test]
+fn empty_number_still_rejected_with_option() {
+    let opts = ParseOptions {
+        allow_empty_string: true,
+        ..ParseOptions::default()
+    };
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=]");
+    assert!(parse_uplink_with(&input, &opts).is_err());
+}
+
+#[test]
+fn display_resolves_metadata_inline() {
+    let frame = parse_uplink(
+        "PUSH|!7|4deedd7bab8817ec|sensor-01|^g1{site=lab}[temp:=21.5#C@1700000000{source=dht22,rev=2};on?=true^g2;pos@=1,2]",
+    )
+    .unwrap();
+    let text = frame.to_string();
+    assert_eq!(
+        text,
+        "PUSH !7 sensor-01 (auth 4deedd7bab8817ec)\n\
+         \x20 group: g1\n\
+         \x20 meta: site=lab\n\
+         \x20 temp := 21.5 #C @1700000000\n\
+         \x20   meta: source=dht22, rev=2\n\
+         \x20 on ?= true ^g2\n\
+         \x20 pos @= 1,2"
+    );
+    assert!(!text.contains("MetaRange"));
+    assert!(format!("{frame:?}").contains("MetaRange"));
+}
+
+#[test]
+fn display_pull_and_passthrough() {
+    let pull = parse_uplink("PULL|4deedd7bab8817ec|dev|[a;b]").unwrap();
+    assert_eq!(
+        pull.to_string(),
+        "PULL dev (auth 4deedd7bab8817ec)\n  pull: a, b"
+    );
+    let hex = parse_uplink("PUSH|4deedd7bab8817ec|dev|>xBEEF").unwrap();
+    assert_eq!(
+        hex.to_string(),
+        "PUSH dev (auth 4deedd7bab8817ec)\n  passthrough (hex): BEEF"
+    );
+}
+
+#[test]
+fn push_line_round_trip_keeps_newline() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[temp:=32]\n");
+    let (frame, terminated) = parse_uplink_line(&input).unwrap();
+    assert!(terminated);
+    let mut buf = [0u8; 128];
+    let n = build_uplink_line(&frame, &mut buf).unwrap();
+    assert_eq!(&buf[..n], input.as_bytes());
+
+    let (_, terminated) = parse_uplink_line(&input[..input.len() - 1]).unwrap();
+    assert!(!terminated);
+}
+
+#[test]
+fn uplink_prefix_reports_bytes_consumed() {
+    let ping = format!("PING|{AUTH}|s");
+    let push = format!("PUSH|{AUTH}|s|[temp:=32]");
+    let input = format!("{ping}\n{push}");
+
+    let (frame, consumed) = parse_uplink_prefix(&input).unwrap();
+    assert_eq!(frame.method, Method::Ping);
+    assert_eq!(consumed, ping.len() + 1);
+
+    let (frame, rest) = parse_uplink_prefix(&input[consumed..]).unwrap();
+    assert_eq!(frame.method, Method::Push);
+    assert_eq!(rest, push.len());
+}
+
+#[test]
+fn build_uplink_line_needs_room_for_newline() {
+    let input = format!("PUSH|{AUTH}|sensor_01|[temp:=32]");
+    let frame = parse_uplink(&input).unwrap();
+    let mut buf = vec![0u8; input.len()];
+    assert!(build_uplink_line(&frame, &mut buf).is_err());
+}
+
+#[test]
+fn serial_policy_can_reject_uppercase() {
+    let input = format!("PUSH|{AUTH}|Sensor01|[temp:=1]");
+    assert_eq!(parse_uplink(&input).unwrap().serial, "Sensor01");
+
+    let opts = ParseOptions {
+        serial_policy: SerialPolicy {
+            allow_uppercase: false,
+            ..SerialPolicy::default()
+        },
+        ..ParseOptions::default()
+    };
+    let err = parse_uplink_with(&input, &opts).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidSerial);
+    assert_eq!(err.position, 22);
+
+    let err = parse_headless_with(Method::Ping, "Sensor01", &opts).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidSerial);
+    assert!(parse_uplink_with(&format!("PUSH|{AUTH}|sensor01|[temp:=1]"), &opts).is_ok());
+}
+
+#[test]
+fn serial_policy_enforces_length_bounds() {
+    let opts = ParseOptions {
+        serial_policy: SerialPolicy {
+            min_len: 4,
+            max_len: 6,
+            ..SerialPolicy::default()
+        },
+        ..ParseOptions::default()
+    };
+    for (serial, ok) in [
+        ("abc", false),
+        ("abcd", true),
+        ("abcdef", true),
+        ("abcdefg", false),
+    ] {
+        let input = format!("PING|{AUTH}|{serial}");
+        assert_eq!(parse_uplink_with(&input, &opts).is_ok(), ok, "{serial}");
+    }
+}
+
+#[test]
+fn all_metadata_tags_owner() {
+    let input =
+        format!("PUSH|{AUTH}|dev1|{{fw=2.1}}[temp:=32{{src=dht}};rssi:=-70;hum:=65{{a=1,b=2}}]");
+    let frame = parse_uplink(&input).unwrap();
+    let body = match frame.push_body.unwrap() {
+        PushBody::Structured(s) => s,
+        _ => panic!("expected structured body"),
+    };
+    let pairs: Vec<_> = body
+        .all_metadata()
+        .map(|(owner, m)| (owner, m.key, m.value))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            (None, "fw", "2.1"),
+            (Some(0), "src", "dht"),
+            (Some(2), "a", "1"),
+            (Some(2), "b", "2"),
+        ]
+    );
+}
+
+#[test]
+fn non_ascii_identifier_reported_at_its_offset() {
+    // Body starts at 27; the `ë` in `tëmp` is at 29.
+    let input = format!("PUSH|{AUTH}|dev1|[tëmp:=1]");
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!((err.kind, err.position), (ParseErrorKind::InvalidField, 29));
+    assert!(input.is_char_boundary(err.position));
+
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=1^grüp]");
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!((err.kind, err.position), (ParseErrorKind::InvalidField, 38));
+
+    let input = format!("PUSH|{AUTH}|dev1|[temp:=1{{kéy=v}}]");
+    let err = parse_uplink(&input).unwrap_err();
+    assert_eq!(
+        (err.kind, err.position),
+        (ParseErrorKind::InvalidMetadata, 37)
+    );
+}
+
+#[test]
+fn backslash_before_newline_still_ends_frame() {
+    // `\` + LF is not an escape: the uplink and ACK splitters both end the
+    // frame there.
+    let input = format!("PUSH|{AUTH}|s|[msg=a\\\nb]");
+    let err = parse_uplink_prefix(&input).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::InvalidVariableBlock);
+
+    let acks = "ACK|CMD|reboot\\\nACK|PONG";
+    let mut out = [(); 3].map(|()| Err(ParseError::new(ParseErrorKind::EmptyFrame, 0)));
+    assert_eq!(tagotip_codec::parse::parse_ack_many(acks, &mut out), 2);
+    assert_eq!(out[1].as_ref().unwrap().status, AckStatus::Pong);
+}
@@ -256,3 +256,23 @@ fn spec_ack_examples() {
         assert_eq!(output, input, "ACK roundtrip failed for: {input}");
     }
 }
+
+// --- testutil::roundtrip_uplink ---
+
+#[test]
+fn testutil_roundtrip_uplink() {
+    use tagotip_codec::testutil::roundtrip_uplink;
+
+    let spec = format!("PUSH|{AUTH}|weather_denver|[temperature:=32;humidity:=65]");
+    assert_eq!(roundtrip_uplink(&spec), Ok(true));
+    assert_eq!(roundtrip_uplink(&format!("{spec}\n")), Ok(true));
+
+    // Bytes after the closing `]` are ignored by the parser, so they are lost.
+    assert_eq!(roundtrip_uplink(&format!("{spec}x")), Ok(false));
+
+    let err = roundtrip_uplink(&format!("PUSH|{AUTH}|weather_denver|[]")).unwrap_err();
+    assert_eq!(
+        err.kind,
+        tagotip_codec::ParseErrorKind::InvalidVariableBlock
+    );
+}
@@ -1,17 +1,30 @@
-//! C ABI bridge for tagotip-codec.
+//! C ABI bridge for tagotip-codec and tagotip-secure.
 //!
-//! Exposes parse/build functions through `extern "C"` so that every language
-//! binding (Node, Go, Python, Arduino) can call a single shared implementation.
+//! Exposes parse/build functions and the TagoTiP/S envelope primitives through
+//! `extern "C"` so that every language binding (Node, Go, Python, Arduino) can
+//! call a single shared implementation.
 
 use std::slice;
 use std::str;
 
 use tagotip_codec::consts::MAX_VARIABLES;
+use tagotip_codec::error::BuildErrorKind;
+use tagotip_codec::inline_vec::InlineVec;
 use tagotip_codec::types::{
-    AckDetail, AckFrame, AckStatus, ErrorCode, MAX_TOTAL_META, Method, Operator,
-    PassthroughEncoding, PushBody, UplinkFrame, Value,
+    AckDetail, AckFrame, AckStatus, ErrorCode, HeadlessFrame, MAX_TOTAL_META, MetaPair, MetaRange,
+    Method, Operator, PassthroughBody, PassthroughEncoding, PullBody, PushBody, StructuredBody,
+    UplinkFrame, Value, Variable,
 };
-use tagotip_codec::{ParseError, ParseErrorKind};
+use tagotip_codec::{BuildError, ParseError, ParseErrorKind};
+use tagotip_secure::{CipherSuite, CryptoError, CryptoErrorKind, EnvelopeHeader, EnvelopeMethod};
+
+// ---------------------------------------------------------------------------
+// Versioning
+// ---------------------------------------------------------------------------
+
+/// ABI version of the C structs and function signatures.
+/// Bumped whenever the layout of any `#[repr(C)]` type changes.
+pub const TAGOTIP_ABI_VERSION: u32 = 2;
 
 // ---------------------------------------------------------------------------
 // Error codes (negative = error, 0 = success, positive = bytes written)
@@ -37,6 +50,34 @@ pub const TAGOTIP_ERR_FRAME_TOO_LARGE: i32 = -16;
 pub const TAGOTIP_ERR_BUFFER_TOO_SMALL: i32 = -17;
 pub const TAGOTIP_ERR_INVALID_INPUT: i32 = -18;
 
+// TagoTiP/S envelope errors
+pub const TAGOTIP_ERR_ENVELOPE_TOO_SHORT: i32 = -19;
+pub const TAGOTIP_ERR_UNSUPPORTED_CIPHER: i32 = -20;
+pub const TAGOTIP_ERR_UNSUPPORTED_VERSION: i32 = -21;
+pub const TAGOTIP_ERR_INVALID_ENVELOPE_METHOD: i32 = -22;
+pub const TAGOTIP_ERR_CIPHER_NOT_ENABLED: i32 = -23;
+pub const TAGOTIP_ERR_DECRYPTION_FAILED: i32 = -24;
+pub const TAGOTIP_ERR_INVALID_KEY_SIZE: i32 = -25;
+pub const TAGOTIP_ERR_INNER_FRAME_TOO_LARGE: i32 = -26;
+pub const TAGOTIP_ERR_ENVELOPE_TOO_LARGE: i32 = -27;
+pub const TAGOTIP_ERR_RESERVED_FLAGS_VALUE: i32 = -28;
+pub const TAGOTIP_ERR_COUNTER_REUSED: i32 = -29;
+
+// Checksummed plaintext frames
+pub const TAGOTIP_ERR_CHECKSUM_MISMATCH: i32 = -30;
+
+// Text input that is not valid UTF-8
+pub const TAGOTIP_ERR_INVALID_UTF8: i32 = -31;
+
+// Device or auth hash that is not 8 bytes
+pub const TAGOTIP_ERR_INVALID_HASH_SIZE: i32 = -32;
+
+// Key lookup found no key for the envelope
+pub const TAGOTIP_ERR_KEY_NOT_FOUND: i32 = -33;
+
+// Output sink failed for a reason other than running out of space
+pub const TAGOTIP_ERR_SINK_FAILED: i32 = -34;
+
 // ---------------------------------------------------------------------------
 // C-compatible enums
 // ---------------------------------------------------------------------------
@@ -102,6 +143,7 @@ pub enum TagotipErrorCode {
 pub enum TagotipPassthroughEncoding {
     Hex = 0,
     Base64 = 1,
+    Base64Url = 2,
 }
 
 #[repr(u8)]
@@ -161,6 +203,10 @@ pub struct TagotipValue {
     pub lat: TagotipStr,
     pub lng: TagotipStr,
     pub alt: TagotipStr,
+    /// For Number: the value as an `f64`, valid when `num_valid` is 1.
+    /// `str_val` stays the exact form.
+    pub num_val: f64,
+    pub num_valid: u8,
 }
 
 #[repr(C)]
@@ -231,6 +277,15 @@ pub struct TagotipAckFrame {
     pub detail: TagotipAckDetail,
 }
 
+/// Parsed TagoTiP/S envelope header (21 bytes on the wire).
+#[repr(C)]
+pub struct TagotipEnvelopeHeader {
+    pub flags: u8,
+    pub counter: u32,
+    pub auth_hash: [u8; 8],
+    pub device_hash: [u8; 8],
+}
+
 // ---------------------------------------------------------------------------
 // Conversion helpers
 // ---------------------------------------------------------------------------
@@ -253,6 +308,36 @@ fn parse_error_to_code(e: &ParseError) -> i32 {
         ParseErrorKind::InvalidAck => TAGOTIP_ERR_INVALID_ACK,
         ParseErrorKind::TooManyItems => TAGOTIP_ERR_TOO_MANY_ITEMS,
         ParseErrorKind::FrameTooLarge => TAGOTIP_ERR_FRAME_TOO_LARGE,
+        ParseErrorKind::ChecksumMismatch => TAGOTIP_ERR_CHECKSUM_MISMATCH,
+    }
+}
+
+fn crypto_error_to_code(e: &CryptoError) -> i32 {
+    match e.kind {
+        CryptoErrorKind::EnvelopeTooShort => TAGOTIP_ERR_ENVELOPE_TOO_SHORT,
+        CryptoErrorKind::UnsupportedCipher => TAGOTIP_ERR_UNSUPPORTED_CIPHER,
+        CryptoErrorKind::UnsupportedVersion => TAGOTIP_ERR_UNSUPPORTED_VERSION,
+        CryptoErrorKind::InvalidMethod => TAGOTIP_ERR_INVALID_ENVELOPE_METHOD,
+        CryptoErrorKind::CipherNotEnabled => TAGOTIP_ERR_CIPHER_NOT_ENABLED,
+        CryptoErrorKind::DecryptionFailed => TAGOTIP_ERR_DECRYPTION_FAILED,
+        CryptoErrorKind::InvalidKeySize => TAGOTIP_ERR_INVALID_KEY_SIZE,
+        CryptoErrorKind::InnerFrameTooLarge => TAGOTIP_ERR_INNER_FRAME_TOO_LARGE,
+        CryptoErrorKind::EnvelopeTooLarge => TAGOTIP_ERR_ENVELOPE_TOO_LARGE,
+        CryptoErrorKind::BufferTooSmall => TAGOTIP_ERR_BUFFER_TOO_SMALL,
+        CryptoErrorKind::ReservedFlagsValue => TAGOTIP_ERR_RESERVED_FLAGS_VALUE,
+        CryptoErrorKind::CounterReused => TAGOTIP_ERR_COUNTER_REUSED,
+        CryptoErrorKind::InvalidHashSize => TAGOTIP_ERR_INVALID_HASH_SIZE,
+        CryptoErrorKind::KeyNotFound => TAGOTIP_ERR_KEY_NOT_FOUND,
+        CryptoErrorKind::InvalidSerial => TAGOTIP_ERR_INVALID_SERIAL,
+    }
+}
+
+fn convert_envelope_header(h: &EnvelopeHeader) -> TagotipEnvelopeHeader {
+    TagotipEnvelopeHeader {
+        flags: h.flags,
+        counter: h.counter,
+        auth_hash: h.auth_hash,
+        device_hash: h.device_hash,
     }
 }
 
@@ -275,14 +360,19 @@ fn convert_operator(o: &Operator) -> TagotipOperator {
 
 fn convert_value(v: &Value<'_>) -> TagotipValue {
     match v {
-        Value::Number(s) => TagotipValue {
-            tag: TagotipValueTag::Number,
-            str_val: TagotipStr::from_str(s),
-            bool_val: 0,
-            lat: TagotipStr::empty(),
-            lng: TagotipStr::empty(),
-            alt: TagotipStr::empty(),
-        },
+        Value::Number(s) => {
+            let num = s.parse::<f64>().ok();
+            TagotipValue {
+                tag: TagotipValueTag::Number,
+                str_val: TagotipStr::from_str(s),
+                bool_val: 0,
+                lat: TagotipStr::empty(),
+                lng: TagotipStr::empty(),
+                alt: TagotipStr::empty(),
+                num_val: num.unwrap_or(0.0),
+                num_valid: u8::from(num.is_some()),
+            }
+        }
         Value::String(s) => TagotipValue {
             tag: TagotipValueTag::String,
             str_val: TagotipStr::from_str(s),
@@ -290,6 +380,8 @@ fn convert_value(v: &Value<'_>) -> TagotipValue {
             lat: TagotipStr::empty(),
             lng: TagotipStr::empty(),
             alt: TagotipStr::empty(),
+            num_val: 0.0,
+            num_valid: 0,
         },
         Value::Boolean(b) => TagotipValue {
             tag: TagotipValueTag::Boolean,
@@ -298,6 +390,8 @@ fn convert_value(v: &Value<'_>) -> TagotipValue {
             lat: TagotipStr::empty(),
             lng: TagotipStr::empty(),
             alt: TagotipStr::empty(),
+            num_val: 0.0,
+            num_valid: 0,
         },
         Value::Location { lat, lng, alt } => TagotipValue {
             tag: TagotipValueTag::Location,
@@ -306,6 +400,8 @@ fn convert_value(v: &Value<'_>) -> TagotipValue {
             lat: TagotipStr::from_str(lat),
             lng: TagotipStr::from_str(lng),
             alt: TagotipStr::from_option(*alt),
+            num_val: 0.0,
+            num_valid: 0,
         },
     }
 }
@@ -327,6 +423,23 @@ fn convert_error_code(c: &ErrorCode) -> TagotipErrorCode {
     }
 }
 
+fn error_code_from_c(c: &TagotipErrorCode) -> ErrorCode {
+    match c {
+        TagotipErrorCode::InvalidToken => ErrorCode::InvalidToken,
+        TagotipErrorCode::InvalidMethod => ErrorCode::InvalidMethod,
+        TagotipErrorCode::InvalidPayload => ErrorCode::InvalidPayload,
+        TagotipErrorCode::InvalidSeq => ErrorCode::InvalidSeq,
+        TagotipErrorCode::DeviceNotFound => ErrorCode::DeviceNotFound,
+        TagotipErrorCode::VariableNotFound => ErrorCode::VariableNotFound,
+        TagotipErrorCode::RateLimited => ErrorCode::RateLimited,
+        TagotipErrorCode::AuthFailed => ErrorCode::AuthFailed,
+        TagotipErrorCode::UnsupportedVersion => ErrorCode::UnsupportedVersion,
+        TagotipErrorCode::PayloadTooLarge => ErrorCode::PayloadTooLarge,
+        TagotipErrorCode::ServerError => ErrorCode::ServerError,
+        TagotipErrorCode::Unknown => ErrorCode::Unknown,
+    }
+}
+
 fn convert_ack_status(s: &AckStatus) -> TagotipAckStatus {
     match s {
         AckStatus::Ok => TagotipAckStatus::Ok,
@@ -336,46 +449,8 @@ fn convert_ack_status(s: &AckStatus) -> TagotipAckStatus {
     }
 }
 
-// ---------------------------------------------------------------------------
-// FFI functions
-// ---------------------------------------------------------------------------
-
-/// Parse an uplink frame.
-///
-/// # Safety
-/// - `input_ptr` must point to a valid UTF-8 byte array of `input_len` bytes.
-/// - `out` must point to a valid, writeable `TagotipUplinkFrame`.
-///
-/// Returns 0 on success, negative error code on failure.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn tagotip_parse_uplink(
-    input_ptr: *const u8,
-    input_len: usize,
-    out: *mut TagotipUplinkFrame,
-) -> i32 {
-    let input = unsafe {
-        let bytes = slice::from_raw_parts(input_ptr, input_len);
-        match str::from_utf8(bytes) {
-            Ok(s) => s,
-            Err(_) => return TAGOTIP_ERR_INVALID_INPUT,
-        }
-    };
-
-    let frame = match tagotip_codec::parse::parse_uplink(input) {
-        Ok(f) => f,
-        Err(e) => return parse_error_to_code(&e),
-    };
-
-    let out = unsafe { &mut *out };
-
-    out.method = convert_method(&frame.method);
-    out.has_seq = u8::from(frame.seq.is_some());
-    out.seq = frame.seq.unwrap_or(0);
-    out.auth = TagotipStr::from_str(frame.auth);
-    out.serial = TagotipStr::from_str(frame.serial);
-
-    // Push body
-    match &frame.push_body {
+fn fill_push_body(out: &mut TagotipUplinkFrame, push_body: Option<&PushBody<'_>>) {
+    match push_body {
         Some(PushBody::Structured(sb)) => {
             out.push_body_tag = TagotipPushBodyTag::Structured;
             out.body_group = TagotipStr::from_option(sb.group);
@@ -418,6 +493,7 @@ pub unsafe extern "C" fn tagotip_parse_uplink(
                 encoding: match pt.encoding {
                     PassthroughEncoding::Hex => TagotipPassthroughEncoding::Hex,
                     PassthroughEncoding::Base64 => TagotipPassthroughEncoding::Base64,
+                    PassthroughEncoding::Base64Url => TagotipPassthroughEncoding::Base64Url,
                 },
                 data: TagotipStr::from_str(pt.data),
             };
@@ -430,9 +506,10 @@ pub unsafe extern "C" fn tagotip_parse_uplink(
             out.meta_pool_len = 0;
         }
     }
+}
 
-    // Pull body
-    if let Some(pb) = &frame.pull_body {
+fn fill_pull_body(out: &mut TagotipUplinkFrame, pull_body: Option<&PullBody<'_>>) {
+    if let Some(pb) = pull_body {
         out.has_pull_body = 1;
         let count = pb.variables.len().min(MAX_VARIABLES);
         out.pull_variables_len = count as u16;
@@ -443,6 +520,225 @@ pub unsafe extern "C" fn tagotip_parse_uplink(
         out.has_pull_body = 0;
         out.pull_variables_len = 0;
     }
+}
+
+fn build_error_to_code(e: &BuildError) -> i32 {
+    match e.kind {
+        BuildErrorKind::BufferTooSmall => TAGOTIP_ERR_BUFFER_TOO_SMALL,
+        BuildErrorKind::InvalidInput => TAGOTIP_ERR_INVALID_INPUT,
+        // Only std sinks report this; the FFI always builds into caller buffers.
+        BuildErrorKind::SinkFailed => TAGOTIP_ERR_SINK_FAILED,
+    }
+}
+
+fn method_from_id(id: u8) -> Option<Method> {
+    match id {
+        0 => Some(Method::Push),
+        1 => Some(Method::Pull),
+        2 => Some(Method::Ping),
+        _ => None,
+    }
+}
+
+/// Convert a C `TagotipStr` into an optional field (`len == 0` means absent).
+///
+/// # Safety
+/// - The `TagotipStr` must point to valid UTF-8 data.
+unsafe fn tagotip_str_to_option<'a>(s: &TagotipStr) -> Option<&'a str> {
+    if s.ptr.is_null() || s.len == 0 {
+        None
+    } else {
+        Some(unsafe { tagotip_str_to_str(s) })
+    }
+}
+
+/// # Safety
+/// - All `TagotipStr` fields of `v` must point to valid UTF-8 data.
+unsafe fn value_from_c<'a>(v: &TagotipValue) -> Value<'a> {
+    unsafe {
+        match v.tag {
+            TagotipValueTag::Number => Value::Number(tagotip_str_to_str(&v.str_val)),
+            TagotipValueTag::String => Value::String(tagotip_str_to_str(&v.str_val)),
+            TagotipValueTag::Boolean => Value::Boolean(v.bool_val != 0),
+            TagotipValueTag::Location => Value::Location {
+                lat: tagotip_str_to_str(&v.lat),
+                lng: tagotip_str_to_str(&v.lng),
+                alt: tagotip_str_to_option(&v.alt),
+            },
+        }
+    }
+}
+
+/// Convert a C ACK detail. An `Error` detail with empty `text` is written
+/// using the canonical text of `error_code`; `Unknown` has none, so that
+/// combination fails with `TAGOTIP_ERR_INVALID_ACK`.
+///
+/// # Safety
+/// - The `text` field of `d` must point to valid UTF-8 data.
+unsafe fn ack_detail_from_c<'a>(d: &TagotipAckDetail) -> Result<Option<AckDetail<'a>>, i32> {
+    let text = unsafe { tagotip_str_to_option(&d.text) };
+    Ok(match d.tag {
+        TagotipAckDetailTag::None => None,
+        TagotipAckDetailTag::Count => Some(AckDetail::Count(d.count)),
+        TagotipAckDetailTag::Variables => Some(AckDetail::Variables(text.unwrap_or(""))),
+        TagotipAckDetailTag::Command => Some(AckDetail::Command(text.unwrap_or(""))),
+        TagotipAckDetailTag::Error => {
+            let code = error_code_from_c(&d.error_code);
+            let text = text.or(code.as_str()).ok_or(TAGOTIP_ERR_INVALID_ACK)?;
+            Some(AckDetail::Error { code, text })
+        }
+        TagotipAckDetailTag::Raw => Some(AckDetail::Raw(text.unwrap_or(""))),
+    })
+}
+
+fn meta_range_from_c(start: u16, len: u16) -> Option<MetaRange> {
+    if len == 0 {
+        None
+    } else {
+        Some(MetaRange { start, len })
+    }
+}
+
+/// Convert the push body fields of a C frame back into a `PushBody`.
+///
+/// # Safety
+/// - All `TagotipStr` fields referenced by the push body must point to valid UTF-8 data.
+unsafe fn push_body_from_c<'a>(frame: &TagotipUplinkFrame) -> Result<Option<PushBody<'a>>, i32> {
+    match frame.push_body_tag {
+        TagotipPushBodyTag::None => Ok(None),
+        TagotipPushBodyTag::Passthrough => Ok(Some(PushBody::Passthrough(PassthroughBody {
+            encoding: match frame.passthrough.encoding {
+                TagotipPassthroughEncoding::Hex => PassthroughEncoding::Hex,
+                TagotipPassthroughEncoding::Base64 => PassthroughEncoding::Base64,
+                TagotipPassthroughEncoding::Base64Url => PassthroughEncoding::Base64Url,
+            },
+            data: unsafe { tagotip_str_to_str(&frame.passthrough.data) },
+        }))),
+        TagotipPushBodyTag::Structured => {
+            let var_count = frame.variables_len as usize;
+            let meta_count = frame.meta_pool_len as usize;
+            if var_count > MAX_VARIABLES || meta_count > MAX_TOTAL_META {
+                return Err(TAGOTIP_ERR_TOO_MANY_ITEMS);
+            }
+
+            let mut meta_pool = InlineVec::new();
+            for mp in &frame.meta_pool[..meta_count] {
+                let pair = unsafe {
+                    MetaPair {
+                        key: tagotip_str_to_str(&mp.key),
+                        value: tagotip_str_to_str(&mp.value),
+                    }
+                };
+                meta_pool
+                    .push(pair)
+                    .map_err(|_| TAGOTIP_ERR_TOO_MANY_ITEMS)?;
+            }
+
+            let mut variables = InlineVec::new();
+            for v in &frame.variables[..var_count] {
+                let meta = meta_range_from_c(v.meta_start, v.meta_len);
+                if meta.is_some_and(|r| r.start as usize + r.len as usize > meta_count) {
+                    return Err(TAGOTIP_ERR_INVALID_INPUT);
+                }
+                let var = unsafe {
+                    Variable {
+                        name: tagotip_str_to_str(&v.name),
+                        operator: match v.operator {
+                            TagotipOperator::Number => Operator::Number,
+                            TagotipOperator::String => Operator::String,
+                            TagotipOperator::Boolean => Operator::Boolean,
+                            TagotipOperator::Location => Operator::Location,
+                        },
+                        value: value_from_c(&v.value),
+                        unit: tagotip_str_to_option(&v.unit),
+                        timestamp: tagotip_str_to_option(&v.timestamp),
+                        group: tagotip_str_to_option(&v.group),
+                        meta,
+                    }
+                };
+                variables
+                    .push(var)
+                    .map_err(|_| TAGOTIP_ERR_TOO_MANY_ITEMS)?;
+            }
+
+            let body_meta = meta_range_from_c(frame.body_meta_start, frame.body_meta_len);
+            if body_meta.is_some_and(|r| r.start as usize + r.len as usize > meta_count) {
+                return Err(TAGOTIP_ERR_INVALID_INPUT);
+            }
+
+            Ok(Some(PushBody::Structured(StructuredBody {
+                group: unsafe { tagotip_str_to_option(&frame.body_group) },
+                timestamp: unsafe { tagotip_str_to_option(&frame.body_timestamp) },
+                body_meta,
+                variables,
+                meta_pool,
+                unknown_modifiers: InlineVec::new(),
+            })))
+        }
+    }
+}
+
+/// Convert the pull body fields of a C frame back into a `PullBody`.
+///
+/// # Safety
+/// - All referenced `pull_variables` entries must point to valid UTF-8 data.
+unsafe fn pull_body_from_c<'a>(frame: &TagotipUplinkFrame) -> Result<Option<PullBody<'a>>, i32> {
+    if frame.has_pull_body == 0 {
+        return Ok(None);
+    }
+    let count = frame.pull_variables_len as usize;
+    if count > MAX_VARIABLES {
+        return Err(TAGOTIP_ERR_TOO_MANY_ITEMS);
+    }
+    let mut variables = InlineVec::new();
+    for name in &frame.pull_variables[..count] {
+        variables
+            .push(unsafe { tagotip_str_to_str(name) })
+            .map_err(|_| TAGOTIP_ERR_TOO_MANY_ITEMS)?;
+    }
+    Ok(Some(PullBody { variables }))
+}
+
+// ---------------------------------------------------------------------------
+// FFI functions
+// ---------------------------------------------------------------------------
+
+/// Parse an uplink frame.
+///
+/// # Safety
+/// - `input_ptr` must point to a valid UTF-8 byte array of `input_len` bytes.
+/// - `out` must point to a valid, writeable `TagotipUplinkFrame`.
+///
+/// Returns 0 on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_parse_uplink(
+    input_ptr: *const u8,
+    input_len: usize,
+    out: *mut TagotipUplinkFrame,
+) -> i32 {
+    let input = unsafe {
+        let bytes = slice::from_raw_parts(input_ptr, input_len);
+        match str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(_) => return TAGOTIP_ERR_INVALID_UTF8,
+        }
+    };
+
+    let frame = match tagotip_codec::parse::parse_uplink(input) {
+        Ok(f) => f,
+        Err(e) => return parse_error_to_code(&e),
+    };
+
+    let out = unsafe { &mut *out };
+
+    out.method = convert_method(&frame.method);
+    out.has_seq = u8::from(frame.seq.is_some());
+    out.seq = frame.seq.unwrap_or(0);
+    out.auth = TagotipStr::from_str(frame.auth);
+    out.serial = TagotipStr::from_str(frame.serial);
+
+    fill_push_body(out, frame.push_body.as_ref());
+    fill_pull_body(out, frame.pull_body.as_ref());
 
     TAGOTIP_OK
 }
@@ -478,25 +774,129 @@ pub unsafe extern "C" fn tagotip_build_uplink(
     let auth = unsafe { tagotip_str_to_str(&frame.auth) };
     let serial = unsafe { tagotip_str_to_str(&frame.serial) };
 
-    // TODO: Build full frame from C struct fields.
-    // For now, construct a minimal UplinkFrame and delegate to tagotip_codec::build::build_uplink.
+    let push_body = match unsafe { push_body_from_c(frame) } {
+        Ok(b) => b,
+        Err(code) => return code,
+    };
+    let pull_body = match unsafe { pull_body_from_c(frame) } {
+        Ok(b) => b,
+        Err(code) => return code,
+    };
+
     let rust_frame = UplinkFrame {
         method,
         seq,
         auth,
         serial,
-        push_body: None, // TODO: convert push body from C struct
-        pull_body: None, // TODO: convert pull body from C struct
+        push_body,
+        pull_body,
     };
 
     match tagotip_codec::build::build_uplink(&rust_frame, buf) {
         Ok(n) => n as i32,
-        Err(_) => TAGOTIP_ERR_BUFFER_TOO_SMALL,
+        Err(e) => build_error_to_code(&e),
+    }
+}
+
+/// Parse a headless inner frame (TagoTiP/S plaintext: `SERIAL[|BODY]`).
+///
+/// `method` is the method from the envelope (0=PUSH, 1=PULL, 2=PING).
+/// The result is written to a `TagotipUplinkFrame`; `method` is set from the
+/// argument, while `auth` and `seq` are left empty.
+///
+/// # Safety
+/// - `input_ptr` must point to a valid UTF-8 byte array of `input_len` bytes.
+/// - `out` must point to a valid, writeable `TagotipUplinkFrame`.
+///
+/// Returns 0 on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_parse_headless(
+    method: u8,
+    input_ptr: *const u8,
+    input_len: usize,
+    out: *mut TagotipUplinkFrame,
+) -> i32 {
+    let Some(method) = method_from_id(method) else {
+        return TAGOTIP_ERR_INVALID_METHOD;
+    };
+
+    let input = unsafe {
+        let bytes = slice::from_raw_parts(input_ptr, input_len);
+        match str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(_) => return TAGOTIP_ERR_INVALID_UTF8,
+        }
+    };
+
+    let frame = match tagotip_codec::parse::parse_headless(method, input) {
+        Ok(f) => f,
+        Err(e) => return parse_error_to_code(&e),
+    };
+
+    let out = unsafe { &mut *out };
+
+    out.method = convert_method(&method);
+    out.has_seq = 0;
+    out.seq = 0;
+    out.auth = TagotipStr::empty();
+    out.serial = TagotipStr::from_str(frame.serial);
+
+    fill_push_body(out, frame.push_body.as_ref());
+    fill_pull_body(out, frame.pull_body.as_ref());
+
+    TAGOTIP_OK
+}
+
+/// Build a headless inner frame (`SERIAL[|BODY]`) into a buffer.
+///
+/// The `method`, `auth` and `seq` fields of `frame` are ignored; the method is
+/// taken from the `method` argument (0=PUSH, 1=PULL, 2=PING).
+///
+/// # Safety
+/// - `frame` must point to a valid `TagotipUplinkFrame`.
+/// - `buf_ptr` must point to a writeable buffer of at least `buf_len` bytes.
+///
+/// Returns bytes written on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_build_headless(
+    method: u8,
+    frame: *const TagotipUplinkFrame,
+    buf_ptr: *mut u8,
+    buf_len: usize,
+) -> i32 {
+    let Some(method) = method_from_id(method) else {
+        return TAGOTIP_ERR_INVALID_METHOD;
+    };
+
+    let frame = unsafe { &*frame };
+    let buf = unsafe { slice::from_raw_parts_mut(buf_ptr, buf_len) };
+
+    let push_body = match unsafe { push_body_from_c(frame) } {
+        Ok(b) => b,
+        Err(code) => return code,
+    };
+    let pull_body = match unsafe { pull_body_from_c(frame) } {
+        Ok(b) => b,
+        Err(code) => return code,
+    };
+
+    let rust_frame = HeadlessFrame {
+        serial: unsafe { tagotip_str_to_str(&frame.serial) },
+        push_body,
+        pull_body,
+    };
+
+    match tagotip_codec::build::build_headless(method, &rust_frame, buf) {
+        Ok(n) => n as i32,
+        Err(e) => build_error_to_code(&e),
     }
 }
 
 /// Parse an ACK (downlink) frame.
 ///
+/// A `;`-separated ERR list is not split: `ACK|ERR|a;b` yields one `Error`
+/// detail with code `Unknown` and the full `a;b` as text.
+///
 /// # Safety
 /// - `input_ptr` must point to a valid UTF-8 byte array of `input_len` bytes.
 /// - `out` must point to a valid, writeable `TagotipAckFrame`.
@@ -512,7 +912,7 @@ pub unsafe extern "C" fn tagotip_parse_ack(
         let bytes = slice::from_raw_parts(input_ptr, input_len);
         match str::from_utf8(bytes) {
             Ok(s) => s,
-            Err(_) => return TAGOTIP_ERR_INVALID_INPUT,
+            Err(_) => return TAGOTIP_ERR_INVALID_UTF8,
         }
     };
 
@@ -560,6 +960,17 @@ pub unsafe extern "C" fn tagotip_parse_ack(
                 error_code: convert_error_code(code),
             };
         }
+        // The FFI parses with default options, which never produce a list;
+        // should one reach this point, report the whole list as one
+        // unrecognized error rather than dropping entries.
+        Some(AckDetail::Errors(errors)) => {
+            out.detail = TagotipAckDetail {
+                tag: TagotipAckDetailTag::Error,
+                count: 0,
+                text: TagotipStr::from_str(errors.as_str()),
+                error_code: TagotipErrorCode::Unknown,
+            };
+        }
         Some(AckDetail::Raw(s)) => {
             out.detail = TagotipAckDetail {
                 tag: TagotipAckDetailTag::Raw,
@@ -583,6 +994,9 @@ pub unsafe extern "C" fn tagotip_parse_ack(
 
 /// Build an ACK frame into a buffer.
 ///
+/// The `!N` field is written only when `has_seq` is non-zero. An ERR detail
+/// with an `Unknown` code and empty `text` fails with `TAGOTIP_ERR_INVALID_ACK`.
+///
 /// # Safety
 /// - `frame` must point to a valid `TagotipAckFrame`.
 /// - `buf_ptr` must point to a writeable buffer of at least `buf_len` bytes.
@@ -610,19 +1024,358 @@ pub unsafe extern "C" fn tagotip_build_ack(
         TagotipAckStatus::Err => AckStatus::Err,
     };
 
-    // TODO: convert detail from C struct
+    let detail = match unsafe { ack_detail_from_c(&frame.detail) } {
+        Ok(detail) => detail,
+        Err(code) => return code,
+    };
     let rust_frame = AckFrame {
         seq,
         status,
-        detail: None,
+        detail,
     };
 
     match tagotip_codec::build::build_ack(&rust_frame, buf) {
         Ok(n) => n as i32,
-        Err(_) => TAGOTIP_ERR_BUFFER_TOO_SMALL,
+        Err(e) => build_error_to_code(&e),
     }
 }
 
+// ---------------------------------------------------------------------------
+// TagoTiP/S crypto functions
+// ---------------------------------------------------------------------------
+
+/// Derive the 8-byte authorization hash from a token.
+///
+/// # Safety
+/// - `token_ptr` must point to a valid UTF-8 byte array of `token_len` bytes.
+/// - `out` must point to a writeable buffer of at least 8 bytes.
+///
+/// Returns 0 on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_derive_auth_hash(
+    token_ptr: *const u8,
+    token_len: usize,
+    out: *mut u8,
+) -> i32 {
+    let token = unsafe {
+        let bytes = slice::from_raw_parts(token_ptr, token_len);
+        match str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(_) => return TAGOTIP_ERR_INVALID_UTF8,
+        }
+    };
+
+    let hash = tagotip_secure::derive_auth_hash(token);
+    let out = unsafe { slice::from_raw_parts_mut(out, hash.len()) };
+    out.copy_from_slice(&hash);
+    TAGOTIP_OK
+}
+
+/// Derive the 8-byte device hash from a serial.
+///
+/// # Safety
+/// - `serial_ptr` must point to a valid UTF-8 byte array of `serial_len` bytes.
+/// - `out` must point to a writeable buffer of at least 8 bytes.
+///
+/// Returns 0 on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_derive_device_hash(
+    serial_ptr: *const u8,
+    serial_len: usize,
+    out: *mut u8,
+) -> i32 {
+    let serial = unsafe {
+        let bytes = slice::from_raw_parts(serial_ptr, serial_len);
+        match str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(_) => return TAGOTIP_ERR_INVALID_UTF8,
+        }
+    };
+
+    let hash = tagotip_secure::derive_device_hash(serial);
+    let out = unsafe { slice::from_raw_parts_mut(out, hash.len()) };
+    out.copy_from_slice(&hash);
+    TAGOTIP_OK
+}
+
+/// Derive an encryption key from a token and serial (HMAC-SHA256).
+///
+/// Writes the first `key_len` bytes of the 32-byte HMAC output
+/// (16 for AES-128 suites, 32 for AES-256 / ChaCha20-Poly1305).
+///
+/// # Safety
+/// - `token_ptr`/`serial_ptr` must point to valid UTF-8 byte arrays of the given lengths.
+/// - `out_key` must point to a writeable buffer of at least `key_len` bytes.
+///
+/// Returns 0 on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_derive_key(
+    token_ptr: *const u8,
+    token_len: usize,
+    serial_ptr: *const u8,
+    serial_len: usize,
+    out_key: *mut u8,
+    key_len: usize,
+) -> i32 {
+    let (token, serial) = unsafe {
+        let token = slice::from_raw_parts(token_ptr, token_len);
+        let serial = slice::from_raw_parts(serial_ptr, serial_len);
+        match (str::from_utf8(token), str::from_utf8(serial)) {
+            (Ok(t), Ok(s)) => (t, s),
+            _ => return TAGOTIP_ERR_INVALID_UTF8,
+        }
+    };
+
+    let key = tagotip_secure::derive_key(token, serial);
+    if key_len > key.len() {
+        return TAGOTIP_ERR_INVALID_KEY_SIZE;
+    }
+    let out = unsafe { slice::from_raw_parts_mut(out_key, key_len) };
+    out.copy_from_slice(&key[..key_len]);
+    TAGOTIP_OK
+}
+
+/// Encrypt a raw inner frame into a TagoTiP/S envelope.
+///
+/// `method` is the envelope method ID (0=PUSH, 1=PULL, 2=PING, 3=ACK) and
+/// `cipher_suite` the suite ID (0=AES-128-CCM, ...).
+///
+/// # Safety
+/// - `inner_ptr` must point to `inner_len` readable bytes.
+/// - `auth_hash` and `device_hash` must each point to 8 readable bytes.
+/// - `key_ptr` must point to `key_len` readable bytes.
+/// - `buf_ptr` must point to a writeable buffer of at least `buf_len` bytes.
+///
+/// Returns bytes written on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_seal_raw(
+    inner_ptr: *const u8,
+    inner_len: usize,
+    method: u8,
+    counter: u32,
+    auth_hash: *const u8,
+    device_hash: *const u8,
+    key_ptr: *const u8,
+    key_len: usize,
+    cipher_suite: u8,
+    buf_ptr: *mut u8,
+    buf_len: usize,
+) -> i32 {
+    let inner = unsafe { slice::from_raw_parts(inner_ptr, inner_len) };
+    let key = unsafe { slice::from_raw_parts(key_ptr, key_len) };
+    let mut auth = [0u8; 8];
+    let mut device = [0u8; 8];
+    unsafe {
+        auth.copy_from_slice(slice::from_raw_parts(auth_hash, 8));
+        device.copy_from_slice(slice::from_raw_parts(device_hash, 8));
+    }
+
+    let method = match EnvelopeMethod::from_id(method) {
+        Ok(m) => m,
+        Err(e) => return crypto_error_to_code(&e),
+    };
+    let suite = match CipherSuite::from_id(cipher_suite) {
+        Ok(s) => s,
+        Err(e) => return crypto_error_to_code(&e),
+    };
+
+    let envelope = match tagotip_secure::seal_raw(inner, method, counter, auth, device, key, suite)
+    {
+        Ok(env) => env,
+        Err(e) => return crypto_error_to_code(&e),
+    };
+
+    if envelope.len() > buf_len {
+        return TAGOTIP_ERR_BUFFER_TOO_SMALL;
+    }
+    let buf = unsafe { slice::from_raw_parts_mut(buf_ptr, envelope.len()) };
+    buf.copy_from_slice(&envelope);
+    envelope.len() as i32
+}
+
+/// Decrypt a TagoTiP/S envelope.
+///
+/// On success the header and envelope method ID are written to `out_header`
+/// and `out_method`, and the decrypted inner frame is written to `buf_ptr`.
+///
+/// # Safety
+/// - `envelope_ptr` must point to `envelope_len` readable bytes.
+/// - `key_ptr` must point to `key_len` readable bytes.
+/// - `out_header` and `out_method` must be valid and writeable.
+/// - `buf_ptr` must point to a writeable buffer of at least `buf_len` bytes.
+///
+/// Returns inner frame length on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_open_envelope(
+    envelope_ptr: *const u8,
+    envelope_len: usize,
+    key_ptr: *const u8,
+    key_len: usize,
+    out_header: *mut TagotipEnvelopeHeader,
+    out_method: *mut u8,
+    buf_ptr: *mut u8,
+    buf_len: usize,
+) -> i32 {
+    let envelope = unsafe { slice::from_raw_parts(envelope_ptr, envelope_len) };
+    let key = unsafe { slice::from_raw_parts(key_ptr, key_len) };
+
+    let (header, method, inner) = match tagotip_secure::open_envelope(envelope, key) {
+        Ok(r) => r,
+        Err(e) => return crypto_error_to_code(&e),
+    };
+
+    if inner.len() > buf_len {
+        return TAGOTIP_ERR_BUFFER_TOO_SMALL;
+    }
+    let buf = unsafe { slice::from_raw_parts_mut(buf_ptr, inner.len()) };
+    buf.copy_from_slice(&inner);
+
+    unsafe {
+        *out_header = convert_envelope_header(&header);
+        *out_method = method.id();
+    }
+    inner.len() as i32
+}
+
+/// Parse the 21-byte envelope header without decrypting (for routing).
+///
+/// # Safety
+/// - `envelope_ptr` must point to `envelope_len` readable bytes.
+/// - `out` must point to a valid, writeable `TagotipEnvelopeHeader`.
+///
+/// Returns 0 on success, negative error code on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_parse_envelope_header(
+    envelope_ptr: *const u8,
+    envelope_len: usize,
+    out: *mut TagotipEnvelopeHeader,
+) -> i32 {
+    let envelope = unsafe { slice::from_raw_parts(envelope_ptr, envelope_len) };
+
+    let header = match tagotip_secure::parse_envelope_header(envelope) {
+        Ok(h) => h,
+        Err(e) => return crypto_error_to_code(&e),
+    };
+
+    unsafe { *out = convert_envelope_header(&header) };
+    TAGOTIP_OK
+}
+
+/// Check whether received bytes are a TagoTiP/S envelope (vs. a plaintext ACK).
+///
+/// # Safety
+/// - `data_ptr` must point to `data_len` readable bytes.
+///
+/// Returns 1 for an envelope, 0 for plaintext or empty input.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tagotip_is_envelope(data_ptr: *const u8, data_len: usize) -> i32 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+    i32::from(tagotip_secure::is_envelope(data))
+}
+
+/// Return the library version string (e.g. `"0.1.0-beta.4"`).
+///
+/// The returned slice points to static data and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_version() -> TagotipStr {
+    TagotipStr::from_str(env!("CARGO_PKG_VERSION"))
+}
+
+/// Return the ABI version (`TAGOTIP_ABI_VERSION`) the library was built with.
+///
+/// Bindings should compare this against the value from their header at load time.
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_abi_version() -> u32 {
+    TAGOTIP_ABI_VERSION
+}
+
+// ---------------------------------------------------------------------------
+// Limits
+// ---------------------------------------------------------------------------
+
+/// Maximum plaintext frame size in bytes (`TAGOTIP_MAX_FRAME_SIZE`).
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_max_frame_size() -> usize {
+    tagotip_codec::MAX_FRAME_SIZE
+}
+
+/// Maximum number of variables in a body (`TAGOTIP_MAX_VARIABLES`).
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_max_variables() -> usize {
+    tagotip_codec::MAX_VARIABLES
+}
+
+/// Maximum number of pairs in a single metadata block (`TAGOTIP_MAX_META_PAIRS`).
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_max_meta_pairs() -> usize {
+    tagotip_codec::MAX_META_PAIRS
+}
+
+/// Maximum number of metadata pairs across a whole body (`TAGOTIP_MAX_TOTAL_META`).
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_max_total_meta() -> usize {
+    tagotip_codec::MAX_TOTAL_META
+}
+
+/// Maximum variable name length in bytes.
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_max_varname_len() -> usize {
+    tagotip_codec::MAX_VARNAME_LEN
+}
+
+/// Maximum serial length in bytes.
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_max_serial_len() -> usize {
+    tagotip_codec::MAX_SERIAL_LEN
+}
+
+/// Return a human-readable description of a `TAGOTIP_OK`/`TAGOTIP_ERR_*` code.
+///
+/// The returned slice points to static data and must not be freed.
+/// Unrecognized codes yield `"unknown error"`.
+#[unsafe(no_mangle)]
+pub extern "C" fn tagotip_error_message(code: i32) -> TagotipStr {
+    let msg = match code {
+        TAGOTIP_OK => "ok",
+        TAGOTIP_ERR_EMPTY_FRAME => "empty frame",
+        TAGOTIP_ERR_NUL_BYTE => "frame contains NUL byte",
+        TAGOTIP_ERR_INVALID_METHOD => "invalid method",
+        TAGOTIP_ERR_INVALID_SEQ => "invalid sequence counter",
+        TAGOTIP_ERR_INVALID_AUTH => "invalid auth hash",
+        TAGOTIP_ERR_INVALID_SERIAL => "invalid serial",
+        TAGOTIP_ERR_MISSING_BODY => "missing body",
+        TAGOTIP_ERR_INVALID_MODIFIER => "invalid body modifier",
+        TAGOTIP_ERR_INVALID_VARIABLE_BLOCK => "invalid variable block",
+        TAGOTIP_ERR_INVALID_VARIABLE => "invalid variable",
+        TAGOTIP_ERR_INVALID_PASSTHROUGH => "invalid passthrough",
+        TAGOTIP_ERR_INVALID_METADATA => "invalid metadata",
+        TAGOTIP_ERR_INVALID_FIELD => "invalid field",
+        TAGOTIP_ERR_INVALID_ACK => "invalid ACK frame",
+        TAGOTIP_ERR_TOO_MANY_ITEMS => "too many items",
+        TAGOTIP_ERR_FRAME_TOO_LARGE => "frame too large",
+        TAGOTIP_ERR_BUFFER_TOO_SMALL => "output buffer too small",
+        TAGOTIP_ERR_INVALID_INPUT => "invalid input data",
+        TAGOTIP_ERR_ENVELOPE_TOO_SHORT => "envelope too short",
+        TAGOTIP_ERR_UNSUPPORTED_CIPHER => "unsupported cipher suite",
+        TAGOTIP_ERR_UNSUPPORTED_VERSION => "unsupported version",
+        TAGOTIP_ERR_INVALID_ENVELOPE_METHOD => "invalid envelope method",
+        TAGOTIP_ERR_CIPHER_NOT_ENABLED => "cipher suite not enabled",
+        TAGOTIP_ERR_DECRYPTION_FAILED => "AEAD decryption failed",
+        TAGOTIP_ERR_INVALID_KEY_SIZE => "invalid encryption key size",
+        TAGOTIP_ERR_INNER_FRAME_TOO_LARGE => "inner frame exceeds maximum size",
+        TAGOTIP_ERR_ENVELOPE_TOO_LARGE => "envelope exceeds maximum size",
+        TAGOTIP_ERR_RESERVED_FLAGS_VALUE => "flags byte 0x41 is reserved",
+        TAGOTIP_ERR_COUNTER_REUSED => "counter not greater than the last one sealed",
+        TAGOTIP_ERR_CHECKSUM_MISMATCH => "missing, malformed or mismatched checksum",
+        TAGOTIP_ERR_INVALID_UTF8 => "input is not valid UTF-8",
+        TAGOTIP_ERR_INVALID_HASH_SIZE => "hash must be 8 bytes",
+        TAGOTIP_ERR_KEY_NOT_FOUND => "no key found for envelope",
+        TAGOTIP_ERR_SINK_FAILED => "output sink failed",
+        _ => "unknown error",
+    };
+    TagotipStr::from_str(msg)
+}
+
 /// Helper to convert `TagotipStr` back to &str.
 ///
 /// # Safety
@@ -28,6 +28,9 @@ extern "C" {
 #define TAGOTIP_MAX_FRAME_SIZE   16384
 #define TAGOTIP_AUTH_HASH_LEN    16
 
+/** ABI version; compare against tagotip_abi_version() at load time. */
+#define TAGOTIP_ABI_VERSION      2
+
 /* -----------------------------------------------------------------------
  * Error codes (return values)
  * ----------------------------------------------------------------------- */
@@ -52,6 +55,34 @@ extern "C" {
 #define TAGOTIP_ERR_BUFFER_TOO_SMALL    -17
 #define TAGOTIP_ERR_INVALID_INPUT       -18
 
+/* TagoTiP/S envelope errors */
+#define TAGOTIP_ERR_ENVELOPE_TOO_SHORT      -19
+#define TAGOTIP_ERR_UNSUPPORTED_CIPHER      -20
+#define TAGOTIP_ERR_UNSUPPORTED_VERSION     -21
+#define TAGOTIP_ERR_INVALID_ENVELOPE_METHOD -22
+#define TAGOTIP_ERR_CIPHER_NOT_ENABLED      -23
+#define TAGOTIP_ERR_DECRYPTION_FAILED       -24
+#define TAGOTIP_ERR_INVALID_KEY_SIZE        -25
+#define TAGOTIP_ERR_INNER_FRAME_TOO_LARGE   -26
+#define TAGOTIP_ERR_ENVELOPE_TOO_LARGE      -27
+#define TAGOTIP_ERR_RESERVED_FLAGS_VALUE    -28
+#define TAGOTIP_ERR_COUNTER_REUSED          -29
+
+/* Checksummed plaintext frames */
+#define TAGOTIP_ERR_CHECKSUM_MISMATCH       -30
+
+/* Text input that is not valid UTF-8 */
+#define TAGOTIP_ERR_INVALID_UTF8            -31
+
+/* Device or auth hash that is not 8 bytes */
+#define TAGOTIP_ERR_INVALID_HASH_SIZE       -32
+
+/* Key lookup found no key for the envelope */
+#define TAGOTIP_ERR_KEY_NOT_FOUND           -33
+
+/* Output sink failed for a reason other than running out of space */
+#define TAGOTIP_ERR_SINK_FAILED             -34
+
 /* -----------------------------------------------------------------------
  * Enums
  * ----------------------------------------------------------------------- */
@@ -108,8 +139,9 @@ typedef enum {
 } TagotipErrorCode;
 
 typedef enum {
-    TAGOTIP_PASSTHROUGH_HEX    = 0,
-    TAGOTIP_PASSTHROUGH_BASE64 = 1,
+    TAGOTIP_PASSTHROUGH_HEX        = 0,
+    TAGOTIP_PASSTHROUGH_BASE64     = 1,
+    TAGOTIP_PASSTHROUGH_BASE64_URL = 2,
 } TagotipPassthroughEncoding;
 
 typedef enum {
@@ -140,6 +172,8 @@ typedef struct {
     TagotipStr lat;         /* Location latitude */
     TagotipStr lng;         /* Location longitude */
     TagotipStr alt;         /* Location altitude (optional, len=0 if absent) */
+    double num_val;         /* Number as a double, valid when num_valid is 1 */
+    uint8_t num_valid;      /* 1 if num_val holds the parsed number */
 } TagotipValue;
 
 typedef struct {
@@ -201,6 +235,14 @@ typedef struct {
     TagotipAckDetail detail;
 } TagotipAckFrame;
 
+/** TagoTiP/S envelope header (21 bytes on the wire). */
+typedef struct {
+    uint8_t flags;
+    uint32_t counter;
+    uint8_t auth_hash[8];
+    uint8_t device_hash[8];
+} TagotipEnvelopeHeader;
+
 /* -----------------------------------------------------------------------
  * Functions
  * ----------------------------------------------------------------------- */
@@ -227,9 +269,41 @@ int32_t tagotip_parse_uplink(const uint8_t *input_ptr, size_t input_len,
 int32_t tagotip_build_uplink(const TagotipUplinkFrame *frame,
                              uint8_t *buf_ptr, size_t buf_len);
 
+/**
+ * Parse a headless inner frame (TagoTiP/S plaintext: SERIAL[|BODY]).
+ *
+ * The result reuses TagotipUplinkFrame; `method` is set from the argument,
+ * `auth` and `seq` are left empty.
+ *
+ * @param method     Envelope method (0=PUSH, 1=PULL, 2=PING).
+ * @param input_ptr  Pointer to UTF-8 input bytes.
+ * @param input_len  Length of input in bytes.
+ * @param out        Pointer to output frame struct (caller-allocated).
+ * @return           0 on success, negative error code on failure.
+ */
+int32_t tagotip_parse_headless(uint8_t method, const uint8_t *input_ptr,
+                               size_t input_len, TagotipUplinkFrame *out);
+
+/**
+ * Build a headless inner frame (SERIAL[|BODY]) into a buffer.
+ *
+ * The `method`, `auth` and `seq` fields of the frame are ignored.
+ *
+ * @param method   Envelope method (0=PUSH, 1=PULL, 2=PING).
+ * @param frame    Pointer to a populated frame struct.
+ * @param buf_ptr  Pointer to output buffer.
+ * @param buf_len  Size of output buffer in bytes.
+ * @return         Bytes written on success, negative error code on failure.
+ */
+int32_t tagotip_build_headless(uint8_t method, const TagotipUplinkFrame *frame,
+                               uint8_t *buf_ptr, size_t buf_len);
+
 /**
  * Parse an ACK (downlink) frame.
  *
+ * A `;`-separated ERR list is not split: `ACK|ERR|a;b` yields one ERR
+ * detail with code TAGOTIP_ERROR_CODE_UNKNOWN and the full `a;b` as text.
+ *
  * @param input_ptr  Pointer to UTF-8 input bytes.
  * @param input_len  Length of input in bytes.
  * @param out        Pointer to output frame struct (caller-allocated).
@@ -241,6 +315,10 @@ int32_t tagotip_parse_ack(const uint8_t *input_ptr, size_t input_len,
 /**
  * Build an ACK frame into a buffer.
  *
+ * The `!N` field is written only when `has_seq` is non-zero. An ERR detail
+ * with an empty `text` is written using the canonical text of `error_code`;
+ * `TAGOTIP_ERROR_CODE_UNKNOWN` has none and fails with TAGOTIP_ERR_INVALID_ACK.
+ *
  * @param frame    Pointer to a populated frame struct.
  * @param buf_ptr  Pointer to output buffer.
  * @param buf_len  Size of output buffer in bytes.
@@ -249,6 +327,126 @@ int32_t tagotip_parse_ack(const uint8_t *input_ptr, size_t input_len,
 int32_t tagotip_build_ack(const TagotipAckFrame *frame,
                           uint8_t *buf_ptr, size_t buf_len);
 
+/* -----------------------------------------------------------------------
+ * TagoTiP/S crypto functions
+ * ----------------------------------------------------------------------- */
+
+/**
+ * Derive the 8-byte authorization hash from a token.
+ *
+ * @param token_ptr  Pointer to UTF-8 token bytes ("at" prefix optional).
+ * @param token_len  Length of token in bytes.
+ * @param out        Output buffer of at least 8 bytes.
+ * @return           0 on success, negative error code on failure.
+ */
+int32_t tagotip_derive_auth_hash(const uint8_t *token_ptr, size_t token_len,
+                                 uint8_t *out);
+
+/**
+ * Derive the 8-byte device hash from a serial.
+ *
+ * @param serial_ptr  Pointer to UTF-8 serial bytes.
+ * @param serial_len  Length of serial in bytes.
+ * @param out         Output buffer of at least 8 bytes.
+ * @return            0 on success, negative error code on failure.
+ */
+int32_t tagotip_derive_device_hash(const uint8_t *serial_ptr, size_t serial_len,
+                                   uint8_t *out);
+
+/**
+ * Derive an encryption key from a token and serial (HMAC-SHA256).
+ *
+ * @param out_key  Output buffer of at least key_len bytes.
+ * @param key_len  Key length (16 for AES-128, 32 for AES-256/ChaCha20; max 32).
+ * @return         0 on success, negative error code on failure.
+ */
+int32_t tagotip_derive_key(const uint8_t *token_ptr, size_t token_len,
+                           const uint8_t *serial_ptr, size_t serial_len,
+                           uint8_t *out_key, size_t key_len);
+
+/**
+ * Encrypt a raw inner frame into a TagoTiP/S envelope.
+ *
+ * @param method        Envelope method (0=PUSH, 1=PULL, 2=PING, 3=ACK).
+ * @param auth_hash     8-byte authorization hash.
+ * @param device_hash   8-byte device hash.
+ * @param cipher_suite  Cipher suite ID (0=AES-128-CCM).
+ * @param buf_ptr       Pointer to output buffer.
+ * @param buf_len       Size of output buffer in bytes.
+ * @return              Bytes written on success, negative error code on failure.
+ */
+int32_t tagotip_seal_raw(const uint8_t *inner_ptr, size_t inner_len,
+                         uint8_t method, uint32_t counter,
+                         const uint8_t *auth_hash, const uint8_t *device_hash,
+                         const uint8_t *key_ptr, size_t key_len,
+                         uint8_t cipher_suite,
+                         uint8_t *buf_ptr, size_t buf_len);
+
+/**
+ * Decrypt a TagoTiP/S envelope.
+ *
+ * @param out_header  Receives the parsed envelope header.
+ * @param out_method  Receives the envelope method ID.
+ * @param buf_ptr     Output buffer for the decrypted inner frame.
+ * @param buf_len     Size of output buffer in bytes.
+ * @return            Inner frame length on success, negative error code on failure.
+ */
+int32_t tagotip_open_envelope(const uint8_t *envelope_ptr, size_t envelope_len,
+                              const uint8_t *key_ptr, size_t key_len,
+                              TagotipEnvelopeHeader *out_header,
+                              uint8_t *out_method,
+                              uint8_t *buf_ptr, size_t buf_len);
+
+/**
+ * Parse the envelope header without decrypting (for routing).
+ *
+ * @return  0 on success, negative error code on failure.
+ */
+int32_t tagotip_parse_envelope_header(const uint8_t *envelope_ptr,
+                                      size_t envelope_len,
+                                      TagotipEnvelopeHeader *out);
+
+/**
+ * Check whether received bytes are a TagoTiP/S envelope.
+ *
+ * @return  1 for an envelope, 0 for a plaintext ACK or empty input.
+ */
+int32_t tagotip_is_envelope(const uint8_t *data_ptr, size_t data_len);
+
+/**
+ * Get the library version string (e.g. "0.1.0-beta.4").
+ *
+ * @return  Static string slice (NOT null-terminated, do not free).
+ */
+TagotipStr tagotip_version(void);
+
+/**
+ * Get the ABI version the library was built with.
+ *
+ * @return  Must equal TAGOTIP_ABI_VERSION for the header in use.
+ */
+uint32_t tagotip_abi_version(void);
+
+/**
+ * Runtime limits, matching the TAGOTIP_MAX_* constants the library was
+ * built with. Prefer these when sizing buffers in bindings.
+ */
+size_t tagotip_max_frame_size(void);
+size_t tagotip_max_variables(void);
+size_t tagotip_max_meta_pairs(void);
+size_t tagotip_max_total_meta(void);
+size_t tagotip_max_varname_len(void);
+size_t tagotip_max_serial_len(void);
+
+/**
+ * Get a human-readable description of a return code.
+ *
+ * @param code  TAGOTIP_OK or any TAGOTIP_ERR_* value.
+ * @return      Static string slice (NOT null-terminated, do not free).
+ *              Unrecognized codes yield "unknown error".
+ */
+TagotipStr tagotip_error_message(int32_t code);
+
 #ifdef __cplusplus
 }
 #endif
@@ -89,6 +89,9 @@ fn ffi_parse_uplink_typed_values() {
         unsafe { str_from_tagotip(&frame.variables[0].value.str_val) },
         "32.5"
     );
+    assert_eq!(frame.variables[0].value.num_valid, 1);
+    assert!((frame.variables[0].value.num_val - 32.5).abs() < f64::EPSILON);
+    assert_eq!(frame.variables[1].value.num_valid, 0);
 
     // String
     assert!(matches!(
@@ -227,6 +230,18 @@ fn ffi_parse_ack_err() {
     ));
 }
 
+#[test]
+fn ffi_parse_ack_err_list_keeps_full_text() {
+    let (rc, frame) = unsafe { ffi_parse_ack_helper("ACK|ERR|invalid_seq;rate_limited") };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert!(matches!(frame.detail.tag, TagotipAckDetailTag::Error));
+    assert!(matches!(frame.detail.error_code, TagotipErrorCode::Unknown));
+    assert_eq!(
+        unsafe { str_from_tagotip(&frame.detail.text) },
+        "invalid_seq;rate_limited"
+    );
+}
+
 #[test]
 fn ffi_parse_ack_cmd() {
     let (rc, frame) = unsafe { ffi_parse_ack_helper("ACK|CMD|reboot") };
@@ -245,3 +260,447 @@ fn ffi_parse_ack_with_seq() {
     assert!(matches!(frame.status, TagotipAckStatus::Ok));
     assert_eq!(frame.detail.count, 5);
 }
+
+/// Helper: build an ACK frame via FFI, returning the result code and output.
+unsafe fn ffi_build_ack_helper(frame: &TagotipAckFrame) -> (i32, String) {
+    let mut buf = [0u8; 128];
+    let rc = unsafe { tagotip_build_ack(frame, buf.as_mut_ptr(), buf.len()) };
+    let out = usize::try_from(rc).map_or_else(
+        |_| String::new(),
+        |n| String::from_utf8(buf[..n].to_vec()).unwrap(),
+    );
+    (rc, out)
+}
+
+fn ack_count_frame(has_seq: u8, seq: u32, count: u32) -> TagotipAckFrame {
+    TagotipAckFrame {
+        has_seq,
+        seq,
+        status: TagotipAckStatus::Ok,
+        detail: TagotipAckDetail {
+            tag: TagotipAckDetailTag::Count,
+            count,
+            text: TagotipStr {
+                ptr: std::ptr::null(),
+                len: 0,
+            },
+            error_code: TagotipErrorCode::Unknown,
+        },
+    }
+}
+
+#[test]
+fn ffi_build_ack_with_seq_and_count() {
+    let (rc, out) = unsafe { ffi_build_ack_helper(&ack_count_frame(1, 7, 5)) };
+    assert_eq!(out, "ACK|!7|OK|5");
+    assert_eq!(rc, 11);
+}
+
+#[test]
+fn ffi_build_ack_without_seq() {
+    // `seq` is ignored unless `has_seq` is set.
+    let (_, out) = unsafe { ffi_build_ack_helper(&ack_count_frame(0, 7, 5)) };
+    assert_eq!(out, "ACK|OK|5");
+}
+
+#[test]
+fn ffi_build_ack_text_details() {
+    let (_, parsed) = unsafe { ffi_parse_ack_helper("ACK|!3|CMD|reboot") };
+    let (_, out) = unsafe { ffi_build_ack_helper(&parsed) };
+    assert_eq!(out, "ACK|!3|CMD|reboot");
+
+    // An ERR detail without text falls back to the code's canonical text.
+    let mut frame = ack_count_frame(0, 0, 0);
+    frame.status = TagotipAckStatus::Err;
+    frame.detail.tag = TagotipAckDetailTag::Error;
+    frame.detail.error_code = TagotipErrorCode::RateLimited;
+    let (_, out) = unsafe { ffi_build_ack_helper(&frame) };
+    assert_eq!(out, "ACK|ERR|rate_limited");
+
+    // `Unknown` has no canonical text to fall back to.
+    frame.detail.error_code = TagotipErrorCode::Unknown;
+    let (rc, _) = unsafe { ffi_build_ack_helper(&frame) };
+    assert_eq!(rc, TAGOTIP_ERR_INVALID_ACK);
+}
+
+#[test]
+fn ffi_build_ack_buffer_too_small() {
+    let frame = ack_count_frame(1, 7, 5);
+    let mut buf = [0u8; 4];
+    let rc = unsafe { tagotip_build_ack(&raw const frame, buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(rc, TAGOTIP_ERR_BUFFER_TOO_SMALL);
+}
+
+#[test]
+fn ffi_parse_invalid_utf8() {
+    let input = b"PUSH|4deedd7bab8817ec|sensor_01|[msg=\xff]";
+    let mut frame = MaybeUninit::<TagotipUplinkFrame>::zeroed();
+    let rc = unsafe { tagotip_parse_uplink(input.as_ptr(), input.len(), frame.as_mut_ptr()) };
+    assert_eq!(rc, TAGOTIP_ERR_INVALID_UTF8);
+
+    let input = b"ACK|ERR|\xc3";
+    let mut ack = MaybeUninit::<TagotipAckFrame>::zeroed();
+    let rc = unsafe { tagotip_parse_ack(input.as_ptr(), input.len(), ack.as_mut_ptr()) };
+    assert_eq!(rc, TAGOTIP_ERR_INVALID_UTF8);
+}
+
+// =========================================================================
+// Headless frames via FFI
+// =========================================================================
+
+/// Helper: parse a headless inner frame via FFI.
+unsafe fn ffi_parse_headless_helper(method: u8, input: &str) -> (i32, Box<TagotipUplinkFrame>) {
+    let mut frame = Box::<TagotipUplinkFrame>::new_zeroed();
+    let rc =
+        unsafe { tagotip_parse_headless(method, input.as_ptr(), input.len(), frame.as_mut_ptr()) };
+    (rc, unsafe { frame.assume_init() })
+}
+
+#[test]
+fn ffi_headless_push_round_trip() {
+    let input = "sensor-01|@1694567890000^batch{fw=1.2}[temp:=32#C{src=a};name=foo;gps@=1,2]";
+    let (rc, frame) = unsafe { ffi_parse_headless_helper(0, input) };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert!(matches!(frame.method, TagotipMethod::Push));
+    assert_eq!(frame.auth.len, 0);
+    assert_eq!(unsafe { str_from_tagotip(&frame.serial) }, "sensor-01");
+    assert!(matches!(
+        frame.push_body_tag,
+        TagotipPushBodyTag::Structured
+    ));
+    assert_eq!(frame.variables_len, 3);
+    assert_eq!(frame.meta_pool_len, 2);
+
+    let mut buf = [0u8; 256];
+    let n = unsafe { tagotip_build_headless(0, &raw const *frame, buf.as_mut_ptr(), buf.len()) };
+    assert!(n > 0);
+    let built = std::str::from_utf8(&buf[..usize::try_from(n).unwrap()]).unwrap();
+    assert_eq!(built, input);
+}
+
+#[test]
+fn ffi_headless_pull_round_trip() {
+    let input = "sensor-01|[temp;humidity]";
+    let (rc, frame) = unsafe { ffi_parse_headless_helper(1, input) };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert_eq!(frame.has_pull_body, 1);
+    assert_eq!(frame.pull_variables_len, 2);
+
+    let mut buf = [0u8; 64];
+    let n = unsafe { tagotip_build_headless(1, &raw const *frame, buf.as_mut_ptr(), buf.len()) };
+    let built = std::str::from_utf8(&buf[..usize::try_from(n).unwrap()]).unwrap();
+    assert_eq!(built, input);
+}
+
+#[test]
+fn ffi_headless_invalid_method() {
+    let (rc, _) = unsafe { ffi_parse_headless_helper(3, "OK|3") };
+    assert_eq!(rc, TAGOTIP_ERR_INVALID_METHOD);
+}
+
+#[test]
+fn ffi_headless_build_buffer_too_small() {
+    let (rc, frame) = unsafe { ffi_parse_headless_helper(0, "sensor-01|[temp:=32]") };
+    assert_eq!(rc, TAGOTIP_OK);
+    let mut buf = [0u8; 8];
+    let n = unsafe { tagotip_build_headless(0, &raw const *frame, buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(n, TAGOTIP_ERR_BUFFER_TOO_SMALL);
+}
+
+#[test]
+fn ffi_build_uplink_includes_body() {
+    let input = format!("PUSH|!7|{AUTH}|sensor-01|[temp:=32#C;ok?=true]");
+    let (rc, frame) = unsafe { ffi_parse_uplink_helper(&input) };
+    assert_eq!(rc, TAGOTIP_OK);
+
+    let mut buf = [0u8; 256];
+    let n = unsafe { tagotip_build_uplink(&raw const frame, buf.as_mut_ptr(), buf.len()) };
+    let built = std::str::from_utf8(&buf[..usize::try_from(n).unwrap()]).unwrap();
+    assert_eq!(built, input);
+}
+
+// =========================================================================
+// Version info
+// =========================================================================
+
+#[test]
+fn ffi_version_info() {
+    let version = tagotip_version();
+    let text = unsafe { str_from_tagotip(&version) };
+    assert!(!text.is_empty());
+    assert_eq!(text, env!("CARGO_PKG_VERSION"));
+    assert_ne!(tagotip_abi_version(), 0);
+    assert_eq!(tagotip_abi_version(), TAGOTIP_ABI_VERSION);
+}
+
+// =========================================================================
+// Limits
+// =========================================================================
+
+#[test]
+fn ffi_limits_match_codec() {
+    assert_eq!(tagotip_max_frame_size(), tagotip_codec::MAX_FRAME_SIZE);
+    assert_eq!(tagotip_max_variables(), tagotip_codec::MAX_VARIABLES);
+    assert_eq!(tagotip_max_meta_pairs(), tagotip_codec::MAX_META_PAIRS);
+    assert_eq!(tagotip_max_total_meta(), tagotip_codec::MAX_TOTAL_META);
+    assert_eq!(tagotip_max_varname_len(), tagotip_codec::MAX_VARNAME_LEN);
+    assert_eq!(tagotip_max_serial_len(), tagotip_codec::MAX_SERIAL_LEN);
+}
+
+#[test]
+fn header_limits_match_codec() {
+    let header = include_str!("../tagotip.h");
+    let define = |name: &str| -> usize {
+        header
+            .lines()
+            .find_map(|line| {
+                let rest = line.strip_prefix("#define ")?.trim_start();
+                let value = rest.strip_prefix(name)?;
+                value
+                    .starts_with(' ')
+                    .then(|| value.trim().parse().unwrap())
+            })
+            .unwrap_or_else(|| panic!("missing #define {name}"))
+    };
+    assert_eq!(define("TAGOTIP_MAX_FRAME_SIZE"), tagotip_max_frame_size());
+    assert_eq!(define("TAGOTIP_MAX_VARIABLES"), tagotip_max_variables());
+    assert_eq!(define("TAGOTIP_MAX_META_PAIRS"), tagotip_max_meta_pairs());
+    assert_eq!(define("TAGOTIP_MAX_TOTAL_META"), tagotip_max_total_meta());
+    assert_eq!(
+        define("TAGOTIP_AUTH_HASH_LEN"),
+        tagotip_codec::AUTH_HASH_LEN
+    );
+}
+
+// =========================================================================
+// Error messages
+// =========================================================================
+
+#[test]
+fn ffi_error_message_covers_all_codes() {
+    for code in TAGOTIP_ERR_SINK_FAILED..=TAGOTIP_OK {
+        let msg = tagotip_error_message(code);
+        let text = unsafe { str_from_tagotip(&msg) };
+        assert!(!text.is_empty(), "empty message for code {code}");
+        assert_ne!(text, "unknown error", "code {code} has no message");
+    }
+    let auth = tagotip_error_message(TAGOTIP_ERR_INVALID_AUTH);
+    assert_eq!(unsafe { str_from_tagotip(&auth) }, "invalid auth hash");
+}
+
+#[test]
+fn ffi_error_message_unknown_code() {
+    let msg = tagotip_error_message(-999);
+    assert_eq!(unsafe { str_from_tagotip(&msg) }, "unknown error");
+}
+
+// =========================================================================
+// TagoTiP/S crypto via FFI (spec section 11.1 vector)
+// =========================================================================
+
+const SPEC_TOKEN: &str = "ate2bd319014b24e0a8aca9f00aea4c0d0";
+const SPEC_SERIAL: &str = "sensor-01";
+const SPEC_KEY: [u8; 16] = [
+    0xfe, 0x09, 0xda, 0x81, 0xbc, 0x44, 0x00, 0xee, 0x12, 0xab, 0x56, 0xcd, 0x78, 0xef, 0x90, 0x12,
+];
+const SPEC_AUTH_HASH: [u8; 8] = [0x4d, 0xee, 0xdd, 0x7b, 0xab, 0x88, 0x17, 0xec];
+const SPEC_DEVICE_HASH: [u8; 8] = [0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f];
+const SPEC_INNER_FRAME: &[u8] = b"sensor-01|[temp:=32]";
+#[rustfmt::skip]
+const SPEC_ENVELOPE: [u8; 49] = [
+  0x00, 0x00, 0x00, 0x00, 0x2a, 0x4d, 0xee, 0xdd, 0x7b, 0xab, 0x88, 0x17, 0xec, 0xab, 0x77, 0x88,
+  0xd2, 0x2e, 0xb7, 0x37, 0x2f, 0xc8, 0xc5, 0xaa, 0x56, 0xd7, 0x55, 0x58, 0x2b, 0xac, 0xea, 0x13,
+  0xbb, 0x57, 0x24, 0x93, 0xbb, 0x8c, 0xb1, 0x08, 0x03, 0xcf, 0x82, 0x6f, 0xdb, 0x83, 0x3b, 0x79,
+  0xc6,
+];
+#[rustfmt::skip]
+const SPEC_DERIVED_KEY: [u8; 32] = [
+    0xe5, 0x05, 0xf0, 0x3c, 0xc9, 0xe9, 0x3f, 0xdb,
+    0xcc, 0x38, 0x28, 0x44, 0xcc, 0xa3, 0xe1, 0x7f,
+    0xdf, 0x0b, 0xb3, 0x13, 0x18, 0x58, 0x53, 0x95,
+    0xce, 0xaa, 0xa3, 0x9a, 0x5d, 0x14, 0x19, 0x64,
+];
+
+/// Helper: seal the spec inner frame via FFI into `buf`.
+unsafe fn ffi_seal_spec_helper(buf: &mut [u8]) -> i32 {
+    unsafe {
+        tagotip_seal_raw(
+            SPEC_INNER_FRAME.as_ptr(),
+            SPEC_INNER_FRAME.len(),
+            0,
+            42,
+            SPEC_AUTH_HASH.as_ptr(),
+            SPEC_DEVICE_HASH.as_ptr(),
+            SPEC_KEY.as_ptr(),
+            SPEC_KEY.len(),
+            0,
+            buf.as_mut_ptr(),
+            buf.len(),
+        )
+    }
+}
+
+#[test]
+fn ffi_derive_hashes() {
+    let mut auth = [0u8; 8];
+    let rc = unsafe {
+        tagotip_derive_auth_hash(SPEC_TOKEN.as_ptr(), SPEC_TOKEN.len(), auth.as_mut_ptr())
+    };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert_eq!(auth, SPEC_AUTH_HASH);
+
+    let mut device = [0u8; 8];
+    let rc = unsafe {
+        tagotip_derive_device_hash(SPEC_SERIAL.as_ptr(), SPEC_SERIAL.len(), device.as_mut_ptr())
+    };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert_eq!(device, SPEC_DEVICE_HASH);
+}
+
+#[test]
+fn ffi_derive_key() {
+    let mut key = [0u8; 32];
+    let rc = unsafe {
+        tagotip_derive_key(
+            SPEC_TOKEN.as_ptr(),
+            SPEC_TOKEN.len(),
+            SPEC_SERIAL.as_ptr(),
+            SPEC_SERIAL.len(),
+            key.as_mut_ptr(),
+            key.len(),
+        )
+    };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert_eq!(key, SPEC_DERIVED_KEY);
+
+    let mut short = [0u8; 16];
+    let rc = unsafe {
+        tagotip_derive_key(
+            SPEC_TOKEN.as_ptr(),
+            SPEC_TOKEN.len(),
+            SPEC_SERIAL.as_ptr(),
+            SPEC_SERIAL.len(),
+            short.as_mut_ptr(),
+            short.len(),
+        )
+    };
+    assert_eq!(rc, TAGOTIP_OK);
+    assert_eq!(short, SPEC_DERIVED_KEY[..16]);
+}
+
+#[test]
+fn ffi_seal_raw_spec_vector() {
+    let mut buf = [0u8; 64];
+    let n = unsafe { ffi_seal_spec_helper(&mut buf) };
+    assert_eq!(n, SPEC_ENVELOPE.len() as i32);
+    assert_eq!(buf[..usize::try_from(n).unwrap()], SPEC_ENVELOPE);
+}
+
+#[test]
+fn ffi_seal_raw_buffer_too_small() {
+    let mut buf = [0u8; 48];
+    let rc = unsafe { ffi_seal_spec_helper(&mut buf) };
+    assert_eq!(rc, TAGOTIP_ERR_BUFFER_TOO_SMALL);
+}
+
+#[test]
+fn ffi_seal_raw_invalid_key_size() {
+    let mut buf = [0u8; 64];
+    let rc = unsafe {
+        tagotip_seal_raw(
+            SPEC_INNER_FRAME.as_ptr(),
+            SPEC_INNER_FRAME.len(),
+            0,
+            42,
+            SPEC_AUTH_HASH.as_ptr(),
+            SPEC_DEVICE_HASH.as_ptr(),
+            SPEC_KEY.as_ptr(),
+            8,
+            0,
+            buf.as_mut_ptr(),
+            buf.len(),
+        )
+    };
+    assert_eq!(rc, TAGOTIP_ERR_INVALID_KEY_SIZE);
+}
+
+#[test]
+fn ffi_open_envelope_spec_vector() {
+    let mut header = MaybeUninit::<TagotipEnvelopeHeader>::zeroed();
+    let mut method = 0xffu8;
+    let mut buf = [0u8; 64];
+    let n = unsafe {
+        tagotip_open_envelope(
+            SPEC_ENVELOPE.as_ptr(),
+            SPEC_ENVELOPE.len(),
+            SPEC_KEY.as_ptr(),
+            SPEC_KEY.len(),
+            header.as_mut_ptr(),
+            &raw mut method,
+            buf.as_mut_ptr(),
+            buf.len(),
+        )
+    };
+    assert_eq!(n, SPEC_INNER_FRAME.len() as i32);
+    assert_eq!(&buf[..usize::try_from(n).unwrap()], SPEC_INNER_FRAME);
+    assert_eq!(method, 0);
+    let header = unsafe { header.assume_init() };
+    assert_eq!(header.flags, 0x00);
+    assert_eq!(header.counter, 42);
+    assert_eq!(header.auth_hash, SPEC_AUTH_HASH);
+    assert_eq!(header.device_hash, SPEC_DEVICE_HASH);
+}
+
+#[test]
+fn ffi_open_envelope_wrong_key() {
+    let mut header = MaybeUninit::<TagotipEnvelopeHeader>::zeroed();
+    let mut method = 0u8;
+    let mut buf = [0u8; 64];
+    let wrong_key = [0u8; 16];
+    let rc = unsafe {
+        tagotip_open_envelope(
+            SPEC_ENVELOPE.as_ptr(),
+            SPEC_ENVELOPE.len(),
+            wrong_key.as_ptr(),
+            wrong_key.len(),
+            header.as_mut_ptr(),
+            &raw mut method,
+            buf.as_mut_ptr(),
+            buf.len(),
+        )
+    };
+    assert_eq!(rc, TAGOTIP_ERR_DECRYPTION_FAILED);
+}
+
+#[test]
+fn ffi_parse_envelope_header() {
+    let mut header = MaybeUninit::<TagotipEnvelopeHeader>::zeroed();
+    let rc = unsafe {
+        tagotip_parse_envelope_header(
+            SPEC_ENVELOPE.as_ptr(),
+            SPEC_ENVELOPE.len(),
+            header.as_mut_ptr(),
+        )
+    };
+    assert_eq!(rc, TAGOTIP_OK);
+    let header = unsafe { header.assume_init() };
+    assert_eq!(header.counter, 42);
+    assert_eq!(header.auth_hash, SPEC_AUTH_HASH);
+    assert_eq!(header.device_hash, SPEC_DEVICE_HASH);
+
+    let mut short = MaybeUninit::<TagotipEnvelopeHeader>::zeroed();
+    let rc =
+        unsafe { tagotip_parse_envelope_header(SPEC_ENVELOPE.as_ptr(), 10, short.as_mut_ptr()) };
+    assert_eq!(rc, TAGOTIP_ERR_ENVELOPE_TOO_SHORT);
+}
+
+#[test]
+fn ffi_is_envelope() {
+    assert_eq!(
+        unsafe { tagotip_is_envelope(SPEC_ENVELOPE.as_ptr(), SPEC_ENVELOPE.len()) },
+        1
+    );
+    let ack = b"ACK|OK|3";
+    assert_eq!(unsafe { tagotip_is_envelope(ack.as_ptr(), ack.len()) }, 0);
+    assert_eq!(unsafe { tagotip_is_envelope(ack.as_ptr(), 0) }, 0);
+}
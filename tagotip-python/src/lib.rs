@@ -1,16 +1,26 @@
+use pyo3::create_exception;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
-use tagotip_codec::parse;
+use tagotip_codec::inline_vec::InlineVec;
 use tagotip_codec::types::{
-    AckDetail, AckStatus, ErrorCode, Method, Operator, PassthroughEncoding, PushBody,
-    StructuredBody, Value,
+    AckDetail, AckStatus, ErrorCode, HeadlessFrame, MAX_TOTAL_META, MetaPair, MetaRange, Method,
+    Operator, PassthroughBody, PassthroughEncoding, PullBody, PushBody, StructuredBody, Value,
+    Variable,
 };
 use tagotip_codec::{ParseError, ParseErrorKind};
+use tagotip_codec::{build, parse};
 
-fn parse_error_to_py(e: ParseError) -> PyErr {
-    let kind = match e.kind {
+create_exception!(
+    _tagotip_native,
+    TagotipParseError,
+    PyValueError,
+    "Raised when a TagoTiP frame fails to parse. Carries `kind` (str) and `position` (int)."
+);
+
+fn parse_error_kind_str(kind: ParseErrorKind) -> &'static str {
+    match kind {
         ParseErrorKind::EmptyFrame => "empty_frame",
         ParseErrorKind::NulByte => "nul_byte",
         ParseErrorKind::InvalidMethod => "invalid_method",
@@ -27,8 +37,23 @@ fn parse_error_to_py(e: ParseError) -> PyErr {
         ParseErrorKind::InvalidAck => "invalid_ack",
         ParseErrorKind::TooManyItems => "too_many_items",
         ParseErrorKind::FrameTooLarge => "frame_too_large",
-    };
-    PyValueError::new_err(format!("{}:{}", kind, e.position))
+        ParseErrorKind::ChecksumMismatch => "checksum_mismatch",
+    }
+}
+
+fn parse_error_to_py(e: ParseError) -> PyErr {
+    let kind = parse_error_kind_str(e.kind);
+    Python::with_gil(|py| {
+        let err = TagotipParseError::new_err(format!("{e} ({kind})"));
+        let value = err.value(py);
+        if let Err(attr_err) = value
+            .setattr("kind", kind)
+            .and_then(|()| value.setattr("position", e.position))
+        {
+            return attr_err;
+        }
+        err
+    })
 }
 
 fn method_str(m: &Method) -> &'static str {
@@ -74,6 +99,14 @@ fn error_code_str(c: &ErrorCode) -> &'static str {
     }
 }
 
+/// Store `s` parsed as `f64` under `key`; skipped if it doesn't parse.
+fn set_float(dict: &Bound<'_, PyDict>, key: &str, s: &str) -> PyResult<()> {
+    if let Ok(f) = s.parse::<f64>() {
+        dict.set_item(key, f)?;
+    }
+    Ok(())
+}
+
 fn structured_body_to_dict<'py>(
     py: Python<'py>,
     sb: &StructuredBody<'_>,
@@ -111,6 +144,7 @@ fn structured_body_to_dict<'py>(
             Value::Number(s) => {
                 value_dict.set_item("type", "number")?;
                 value_dict.set_item("str_value", *s)?;
+                set_float(&value_dict, "numeric_value", s)?;
             }
             Value::String(s) => {
                 value_dict.set_item("type", "string")?;
@@ -124,9 +158,12 @@ fn structured_body_to_dict<'py>(
                 value_dict.set_item("type", "location")?;
                 let loc_dict = PyDict::new(py);
                 loc_dict.set_item("lat", *lat)?;
+                set_float(&loc_dict, "lat_value", lat)?;
                 loc_dict.set_item("lng", *lng)?;
+                set_float(&loc_dict, "lng_value", lng)?;
                 if let Some(a) = alt {
                     loc_dict.set_item("alt", *a)?;
+                    set_float(&loc_dict, "alt_value", a)?;
                 }
                 value_dict.set_item("location", loc_dict)?;
             }
@@ -162,10 +199,211 @@ fn structured_body_to_dict<'py>(
     Ok(body_dict)
 }
 
+fn push_body_to_dict<'py>(py: Python<'py>, body: &PushBody<'_>) -> PyResult<Bound<'py, PyDict>> {
+    match body {
+        PushBody::Structured(sb) => structured_body_to_dict(py, sb),
+        PushBody::Passthrough(pt) => {
+            let body_dict = PyDict::new(py);
+            body_dict.set_item("type", "passthrough")?;
+            body_dict.set_item(
+                "encoding",
+                match pt.encoding {
+                    PassthroughEncoding::Hex => "hex",
+                    PassthroughEncoding::Base64 => "base64",
+                    PassthroughEncoding::Base64Url => "base64url",
+                },
+            )?;
+            body_dict.set_item("data", pt.data)?;
+            Ok(body_dict)
+        }
+    }
+}
+
+fn pull_body_to_dict<'py>(py: Python<'py>, pb: &PullBody<'_>) -> PyResult<Bound<'py, PyDict>> {
+    let pull_dict = PyDict::new(py);
+    let var_list = PyList::empty(py);
+    for name in pb.variables.as_slice() {
+        var_list.append(*name)?;
+    }
+    pull_dict.set_item("variables", var_list)?;
+    Ok(pull_dict)
+}
+
+/// Parsed variable value. `type` is one of `number`, `string`, `boolean`,
+/// `location`; only the attributes for that type are set.
+#[pyclass(frozen, get_all, module = "tagotip._tagotip_native")]
+struct PyValue {
+    #[pyo3(name = "type")]
+    kind: &'static str,
+    str_value: Option<String>,
+    number: Option<f64>,
+    bool_value: Option<bool>,
+    lat: Option<String>,
+    lng: Option<String>,
+    alt: Option<String>,
+}
+
+impl PyValue {
+    fn new(value: &Value<'_>) -> Self {
+        let mut out = Self {
+            kind: "",
+            str_value: None,
+            number: None,
+            bool_value: None,
+            lat: None,
+            lng: None,
+            alt: None,
+        };
+        match value {
+            Value::Number(s) => {
+                out.kind = "number";
+                out.str_value = Some((*s).to_string());
+                out.number = s.parse().ok();
+            }
+            Value::String(s) => {
+                out.kind = "string";
+                out.str_value = Some((*s).to_string());
+            }
+            Value::Boolean(b) => {
+                out.kind = "boolean";
+                out.bool_value = Some(*b);
+            }
+            Value::Location { lat, lng, alt } => {
+                out.kind = "location";
+                out.lat = Some((*lat).to_string());
+                out.lng = Some((*lng).to_string());
+                out.alt = alt.map(str::to_string);
+            }
+        }
+        out
+    }
+}
+
+/// A parsed variable. `meta` is a list of `(key, value)` tuples.
+#[pyclass(frozen, get_all, module = "tagotip._tagotip_native")]
+struct PyVariable {
+    name: String,
+    operator: &'static str,
+    value: Py<PyValue>,
+    unit: Option<String>,
+    timestamp: Option<String>,
+    group: Option<String>,
+    meta: Vec<(String, String)>,
+}
+
+/// A parsed uplink frame.
+///
+/// Structured PUSH bodies fill `variables` (plus the body-level `group`,
+/// `timestamp`, `meta`); passthrough bodies fill `passthrough_encoding` and
+/// `passthrough_data`; PULL fills `pull_variables`.
+#[pyclass(frozen, get_all, module = "tagotip._tagotip_native")]
+struct PyUplinkFrame {
+    method: &'static str,
+    auth: String,
+    serial: String,
+    seq: Option<u32>,
+    group: Option<String>,
+    timestamp: Option<String>,
+    meta: Vec<(String, String)>,
+    variables: Vec<Py<PyVariable>>,
+    passthrough_encoding: Option<&'static str>,
+    passthrough_data: Option<String>,
+    pull_variables: Vec<String>,
+}
+
+fn meta_tuples(pairs: &[tagotip_codec::types::MetaPair<'_>]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|mp| (mp.key.to_string(), mp.value.to_string()))
+        .collect()
+}
+
+/// Parse an uplink frame into `PyUplinkFrame` objects instead of nested dicts.
+#[pyfunction]
+fn parse_uplink_native2(py: Python<'_>, input: &str) -> PyResult<Py<PyUplinkFrame>> {
+    let frame = parse::parse_uplink(input).map_err(parse_error_to_py)?;
+
+    let mut out = PyUplinkFrame {
+        method: method_str(&frame.method),
+        auth: frame.auth.to_string(),
+        serial: frame.serial.to_string(),
+        seq: frame.seq,
+        group: None,
+        timestamp: None,
+        meta: Vec::new(),
+        variables: Vec::new(),
+        passthrough_encoding: None,
+        passthrough_data: None,
+        pull_variables: Vec::new(),
+    };
+
+    match &frame.push_body {
+        Some(PushBody::Structured(sb)) => {
+            out.group = sb.group.map(str::to_string);
+            out.timestamp = sb.timestamp.map(str::to_string);
+            out.meta = meta_tuples(sb.body_metadata());
+            for var in &sb.variables {
+                let variable = PyVariable {
+                    name: var.name.to_string(),
+                    operator: operator_str(&var.operator),
+                    value: Py::new(py, PyValue::new(&var.value))?,
+                    unit: var.unit.map(str::to_string),
+                    timestamp: var.timestamp.map(str::to_string),
+                    group: var.group.map(str::to_string),
+                    meta: meta_tuples(sb.variable_metadata(var)),
+                };
+                out.variables.push(Py::new(py, variable)?);
+            }
+        }
+        Some(PushBody::Passthrough(pt)) => {
+            out.passthrough_encoding = Some(match pt.encoding {
+                PassthroughEncoding::Hex => "hex",
+                PassthroughEncoding::Base64 => "base64",
+                PassthroughEncoding::Base64Url => "base64url",
+            });
+            out.passthrough_data = Some(pt.data.to_string());
+        }
+        None => {}
+    }
+
+    if let Some(pb) = &frame.pull_body {
+        out.pull_variables = pb.variables.iter().map(|n| (*n).to_string()).collect();
+    }
+
+    Py::new(py, out)
+}
+
 #[pyfunction]
 fn parse_uplink_native(py: Python<'_>, input: &str) -> PyResult<Py<PyDict>> {
     let frame = parse::parse_uplink(input).map_err(parse_error_to_py)?;
+    Ok(uplink_to_dict(py, &frame)?.into())
+}
+
+/// Parse newline-separated uplink frames in one call. Empty lines are skipped.
+///
+/// Each element is the frame dict, or `{"error": kind, "position": n}` for a
+/// line that fails to parse (`position` is relative to that line).
+#[pyfunction]
+fn parse_uplink_batch_native(py: Python<'_>, input: &str) -> PyResult<Py<PyList>> {
+    let out = PyList::empty(py);
+    for line in input.split('\n').filter(|line| !line.is_empty()) {
+        match parse::parse_uplink(line) {
+            Ok(frame) => out.append(uplink_to_dict(py, &frame)?)?,
+            Err(e) => {
+                let err = PyDict::new(py);
+                err.set_item("error", parse_error_kind_str(e.kind))?;
+                err.set_item("position", e.position)?;
+                out.append(err)?;
+            }
+        }
+    }
+    Ok(out.into())
+}
 
+fn uplink_to_dict<'py>(
+    py: Python<'py>,
+    frame: &tagotip_codec::types::UplinkFrame<'_>,
+) -> PyResult<Bound<'py, PyDict>> {
     let dict = PyDict::new(py);
     dict.set_item("method", method_str(&frame.method))?;
     dict.set_item("auth", frame.auth)?;
@@ -175,37 +413,302 @@ fn parse_uplink_native(py: Python<'_>, input: &str) -> PyResult<Py<PyDict>> {
         dict.set_item("seq", seq)?;
     }
 
-    match &frame.push_body {
-        Some(PushBody::Structured(sb)) => {
-            dict.set_item("push_body", structured_body_to_dict(py, sb)?)?;
+    if let Some(pb) = &frame.push_body {
+        dict.set_item("push_body", push_body_to_dict(py, pb)?)?;
+    }
+
+    if let Some(pb) = &frame.pull_body {
+        dict.set_item("pull_body", pull_body_to_dict(py, pb)?)?;
+    }
+
+    Ok(dict)
+}
+
+/// Parse a headless inner frame. `method` is the envelope method ID (0=PUSH, 1=PULL, 2=PING).
+#[pyfunction]
+fn parse_headless_native(py: Python<'_>, method: u8, input: &str) -> PyResult<Py<PyDict>> {
+    let envelope_method =
+        tagotip_secure::EnvelopeMethod::from_id(method).map_err(crypto_error_to_py)?;
+    let codec_method = envelope_method
+        .to_codec_method()
+        .ok_or_else(|| PyValueError::new_err("ACK inner frames are not headless frames"))?;
+
+    let frame = parse::parse_headless(codec_method, input).map_err(parse_error_to_py)?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("serial", frame.serial)?;
+
+    if let Some(pb) = &frame.push_body {
+        dict.set_item("push_body", push_body_to_dict(py, pb)?)?;
+    }
+
+    if let Some(pb) = &frame.pull_body {
+        dict.set_item("pull_body", pull_body_to_dict(py, pb)?)?;
+    }
+
+    Ok(dict.into())
+}
+
+type OwnedMeta = Vec<(String, String)>;
+
+enum OwnedValue {
+    Number(String),
+    String(String),
+    Boolean(bool),
+    Location(String, String, Option<String>),
+}
+
+struct OwnedVariable {
+    name: String,
+    value: OwnedValue,
+    unit: Option<String>,
+    timestamp: Option<String>,
+    group: Option<String>,
+    meta: OwnedMeta,
+}
+
+fn dict_str(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<String> {
+    dict_opt_str(dict, key)?.ok_or_else(|| PyValueError::new_err(format!("missing '{key}'")))
+}
+
+fn dict_opt_str(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<String>> {
+    dict.get_item(key)?
+        .filter(|v| !v.is_none())
+        .map(|v| v.extract())
+        .transpose()
+}
+
+fn dict_sub<'py>(dict: &Bound<'py, PyDict>, key: &str) -> PyResult<Bound<'py, PyDict>> {
+    dict.get_item(key)?
+        .ok_or_else(|| PyValueError::new_err(format!("missing '{key}'")))?
+        .downcast_into::<PyDict>()
+        .map_err(Into::into)
+}
+
+fn dict_meta(dict: &Bound<'_, PyDict>) -> PyResult<OwnedMeta> {
+    let Some(list) = dict.get_item("meta")?.filter(|v| !v.is_none()) else {
+        return Ok(Vec::new());
+    };
+    list.try_iter()?
+        .map(|pair| {
+            let pair = pair?.downcast_into::<PyDict>()?;
+            Ok((dict_str(&pair, "key")?, dict_str(&pair, "value")?))
+        })
+        .collect()
+}
+
+fn dict_variable(var: &Bound<'_, PyDict>) -> PyResult<OwnedVariable> {
+    let value = dict_sub(var, "value")?;
+    let kind = dict_str(&value, "type")?;
+    let value = match kind.as_str() {
+        "number" => OwnedValue::Number(dict_str(&value, "str_value")?),
+        "string" => OwnedValue::String(dict_str(&value, "str_value")?),
+        "boolean" => OwnedValue::Boolean(
+            value
+                .get_item("bool_value")?
+                .ok_or_else(|| PyValueError::new_err("missing 'bool_value'"))?
+                .extract()?,
+        ),
+        "location" => {
+            let loc = dict_sub(&value, "location")?;
+            OwnedValue::Location(
+                dict_str(&loc, "lat")?,
+                dict_str(&loc, "lng")?,
+                dict_opt_str(&loc, "alt")?,
+            )
         }
-        Some(PushBody::Passthrough(pt)) => {
-            let body_dict = PyDict::new(py);
-            body_dict.set_item("type", "passthrough")?;
-            body_dict.set_item(
-                "encoding",
-                match pt.encoding {
-                    PassthroughEncoding::Hex => "hex",
-                    PassthroughEncoding::Base64 => "base64",
-                },
-            )?;
-            body_dict.set_item("data", pt.data)?;
-            dict.set_item("push_body", body_dict)?;
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "unknown value type: {other}"
+            )));
         }
-        None => {}
+    };
+    Ok(OwnedVariable {
+        name: dict_str(var, "name")?,
+        value,
+        unit: dict_opt_str(var, "unit")?,
+        timestamp: dict_opt_str(var, "timestamp")?,
+        group: dict_opt_str(var, "group")?,
+        meta: dict_meta(var)?,
+    })
+}
+
+fn push_meta<'a>(
+    pool: &mut InlineVec<MetaPair<'a>, MAX_TOTAL_META>,
+    meta: &'a OwnedMeta,
+) -> PyResult<Option<MetaRange>> {
+    if meta.is_empty() {
+        return Ok(None);
+    }
+    let too_many = || PyValueError::new_err("too many metadata pairs");
+    let start = u16::try_from(pool.len()).map_err(|_| too_many())?;
+    for (key, value) in meta {
+        pool.push(MetaPair { key, value }).map_err(|_| too_many())?;
     }
+    Ok(Some(MetaRange {
+        start,
+        len: u16::try_from(meta.len()).map_err(|_| too_many())?,
+    }))
+}
 
-    if let Some(pb) = &frame.pull_body {
-        let pull_dict = PyDict::new(py);
-        let var_list = PyList::empty(py);
-        for name in pb.variables.as_slice() {
-            var_list.append(*name)?;
+enum OwnedPushBody {
+    Passthrough(PassthroughEncoding, String),
+    Structured {
+        group: Option<String>,
+        timestamp: Option<String>,
+        meta: OwnedMeta,
+        variables: Vec<OwnedVariable>,
+    },
+}
+
+fn dict_push_body(push: &Bound<'_, PyDict>) -> PyResult<OwnedPushBody> {
+    match dict_str(push, "type")?.as_str() {
+        "passthrough" => {
+            let encoding = match dict_str(push, "encoding")?.as_str() {
+                "hex" => PassthroughEncoding::Hex,
+                "base64" => PassthroughEncoding::Base64,
+                "base64url" => PassthroughEncoding::Base64Url,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown passthrough encoding: {other}"
+                    )));
+                }
+            };
+            Ok(OwnedPushBody::Passthrough(
+                encoding,
+                dict_str(push, "data")?,
+            ))
+        }
+        "structured" => {
+            let vars = push
+                .get_item("variables")?
+                .ok_or_else(|| PyValueError::new_err("missing 'variables'"))?;
+            let variables = vars
+                .try_iter()?
+                .map(|var| dict_variable(&var?.downcast_into::<PyDict>()?))
+                .collect::<PyResult<_>>()?;
+            Ok(OwnedPushBody::Structured {
+                group: dict_opt_str(push, "group")?,
+                timestamp: dict_opt_str(push, "timestamp")?,
+                meta: dict_meta(push)?,
+                variables,
+            })
         }
-        pull_dict.set_item("variables", var_list)?;
-        dict.set_item("pull_body", pull_dict)?;
+        other => Err(PyValueError::new_err(format!(
+            "unknown push body type: {other}"
+        ))),
     }
+}
 
-    Ok(dict.into())
+fn push_body_from_owned(owned: &OwnedPushBody) -> PyResult<PushBody<'_>> {
+    let (group, timestamp, meta, variables) = match owned {
+        OwnedPushBody::Passthrough(encoding, data) => {
+            return Ok(PushBody::Passthrough(PassthroughBody {
+                encoding: *encoding,
+                data,
+            }));
+        }
+        OwnedPushBody::Structured {
+            group,
+            timestamp,
+            meta,
+            variables,
+        } => (group, timestamp, meta, variables),
+    };
+
+    let mut sb = StructuredBody {
+        group: group.as_deref(),
+        timestamp: timestamp.as_deref(),
+        body_meta: None,
+        variables: InlineVec::new(),
+        meta_pool: InlineVec::new(),
+        unknown_modifiers: InlineVec::new(),
+    };
+    sb.body_meta = push_meta(&mut sb.meta_pool, meta)?;
+    for var in variables {
+        let (operator, value) = match &var.value {
+            OwnedValue::Number(s) => (Operator::Number, Value::Number(s)),
+            OwnedValue::String(s) => (Operator::String, Value::String(s)),
+            OwnedValue::Boolean(b) => (Operator::Boolean, Value::Boolean(*b)),
+            OwnedValue::Location(lat, lng, alt) => (
+                Operator::Location,
+                Value::Location {
+                    lat,
+                    lng,
+                    alt: alt.as_deref(),
+                },
+            ),
+        };
+        let meta = push_meta(&mut sb.meta_pool, &var.meta)?;
+        sb.variables
+            .push(Variable {
+                name: &var.name,
+                operator,
+                value,
+                unit: var.unit.as_deref(),
+                timestamp: var.timestamp.as_deref(),
+                group: var.group.as_deref(),
+                meta,
+            })
+            .map_err(|_| PyValueError::new_err("too many variables"))?;
+    }
+    Ok(PushBody::Structured(sb))
+}
+
+/// Build a headless inner frame from a dict shaped like the output of
+/// `parse_headless_native`. `method` is the envelope method ID (0=PUSH,
+/// 1=PULL, 2=PING). The result can be passed to `seal_uplink_native`.
+#[pyfunction]
+fn build_headless_native(
+    py: Python<'_>,
+    method: u8,
+    frame: &Bound<'_, PyDict>,
+) -> PyResult<Py<pyo3::types::PyBytes>> {
+    let envelope_method =
+        tagotip_secure::EnvelopeMethod::from_id(method).map_err(crypto_error_to_py)?;
+    let codec_method = envelope_method
+        .to_codec_method()
+        .ok_or_else(|| PyValueError::new_err("ACK inner frames are not headless frames"))?;
+
+    // Extract everything into owned data first so the codec types can borrow it.
+    let serial = dict_str(frame, "serial")?;
+    let owned_push = match frame.get_item("push_body")? {
+        Some(pb) if !pb.is_none() => Some(dict_push_body(&pb.downcast_into::<PyDict>()?)?),
+        _ => None,
+    };
+    let pull_names: Option<Vec<String>> = match frame.get_item("pull_body")? {
+        Some(pb) if !pb.is_none() => Some(
+            pb.downcast_into::<PyDict>()?
+                .get_item("variables")?
+                .ok_or_else(|| PyValueError::new_err("missing 'variables'"))?
+                .extract()?,
+        ),
+        _ => None,
+    };
+
+    let push_body = owned_push.as_ref().map(push_body_from_owned).transpose()?;
+    let pull_body = match &pull_names {
+        Some(names) => {
+            let mut variables = InlineVec::new();
+            for name in names {
+                variables
+                    .push(name.as_str())
+                    .map_err(|_| PyValueError::new_err("too many variables"))?;
+            }
+            Some(PullBody { variables })
+        }
+        None => None,
+    };
+
+    let headless = HeadlessFrame {
+        serial: &serial,
+        push_body,
+        pull_body,
+    };
+    let mut buf = vec![0u8; tagotip_secure::consts::MAX_INNER_FRAME_SIZE];
+    let n = build::build_headless(codec_method, &headless, &mut buf)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(pyo3::types::PyBytes::new(py, &buf[..n]).into())
 }
 
 #[pyfunction]
@@ -239,6 +742,17 @@ fn parse_ack_native(py: Python<'_>, input: &str) -> PyResult<Py<PyDict>> {
                 detail_dict.set_item("error_code", error_code_str(code))?;
                 detail_dict.set_item("text", *text)?;
             }
+            AckDetail::Errors(errors) => {
+                detail_dict.set_item("type", "errors")?;
+                let list = PyList::empty(py);
+                for (code, text) in errors.iter() {
+                    let entry = PyDict::new(py);
+                    entry.set_item("error_code", error_code_str(&code))?;
+                    entry.set_item("text", text)?;
+                    list.append(entry)?;
+                }
+                detail_dict.set_item("errors", list)?;
+            }
             AckDetail::Raw(s) => {
                 detail_dict.set_item("type", "raw")?;
                 detail_dict.set_item("text", *s)?;
@@ -258,6 +772,12 @@ fn crypto_error_to_py(e: tagotip_secure::CryptoError) -> PyErr {
     PyValueError::new_err(format!("tagotips: {e}"))
 }
 
+fn hash_from_slice(bytes: &[u8], name: &str) -> PyResult<[u8; 8]> {
+    bytes
+        .try_into()
+        .map_err(|_| PyValueError::new_err(format!("{name} must be 8 bytes")))
+}
+
 #[pyfunction]
 fn derive_auth_hash_native(py: Python<'_>, token: &str) -> PyResult<Py<pyo3::types::PyBytes>> {
     let hash = tagotip_secure::derive_auth_hash(token);
@@ -280,17 +800,8 @@ fn seal_uplink_native(
     device_hash: &[u8],
     key: &[u8],
 ) -> PyResult<Py<pyo3::types::PyBytes>> {
-    if auth_hash.len() != 8 {
-        return Err(PyValueError::new_err("auth_hash must be 8 bytes"));
-    }
-    if device_hash.len() != 8 {
-        return Err(PyValueError::new_err("device_hash must be 8 bytes"));
-    }
-
-    let mut ah = [0u8; 8];
-    ah.copy_from_slice(auth_hash);
-    let mut dh = [0u8; 8];
-    dh.copy_from_slice(device_hash);
+    let ah = hash_from_slice(auth_hash, "auth_hash")?;
+    let dh = hash_from_slice(device_hash, "device_hash")?;
 
     let envelope_method =
         tagotip_secure::EnvelopeMethod::from_id(method).map_err(crypto_error_to_py)?;
@@ -314,6 +825,34 @@ fn seal_uplink_native(
     Ok(pyo3::types::PyBytes::new(py, &envelope).into())
 }
 
+/// Seal an ACK inner frame (`STATUS[|DETAIL]`, no `ACK|` prefix) into a downlink envelope.
+#[pyfunction]
+fn seal_downlink_native(
+    py: Python<'_>,
+    ack_inner: &str,
+    counter: u32,
+    auth_hash: &[u8],
+    device_hash: &[u8],
+    key: &[u8],
+) -> PyResult<Py<pyo3::types::PyBytes>> {
+    let ah = hash_from_slice(auth_hash, "auth_hash")?;
+    let dh = hash_from_slice(device_hash, "device_hash")?;
+
+    let ack = parse::parse_ack_inner(ack_inner).map_err(parse_error_to_py)?;
+
+    let envelope = tagotip_secure::seal_downlink(
+        &ack,
+        counter,
+        ah,
+        dh,
+        key,
+        tagotip_secure::CipherSuite::Aes128Ccm,
+    )
+    .map_err(crypto_error_to_py)?;
+
+    Ok(pyo3::types::PyBytes::new(py, &envelope).into())
+}
+
 #[pyfunction]
 fn open_envelope_native(py: Python<'_>, envelope: &[u8], key: &[u8]) -> PyResult<Py<PyDict>> {
     let (header, method, plaintext) =
@@ -360,6 +899,17 @@ fn is_envelope_native(data: &[u8]) -> bool {
     tagotip_secure::is_envelope(data)
 }
 
+#[pyfunction]
+fn classify_frame_native(data: &[u8]) -> String {
+    match tagotip_secure::classify_frame(data) {
+        tagotip_secure::FrameClass::Envelope => "envelope",
+        tagotip_secure::FrameClass::Uplink => "uplink",
+        tagotip_secure::FrameClass::Ack => "ack",
+        tagotip_secure::FrameClass::Unknown => "unknown",
+    }
+    .to_string()
+}
+
 #[pyfunction]
 fn derive_key_native(
     py: Python<'_>,
@@ -370,6 +920,18 @@ fn derive_key_native(
     Ok(pyo3::types::PyBytes::new(py, &key).into())
 }
 
+#[pyfunction]
+fn derive_key_for_native(
+    py: Python<'_>,
+    token: &str,
+    serial: &str,
+    cipher_id: u8,
+) -> PyResult<Py<pyo3::types::PyBytes>> {
+    let suite = tagotip_secure::CipherSuite::from_id(cipher_id).map_err(crypto_error_to_py)?;
+    let key = tagotip_secure::derive_key(token, serial);
+    Ok(pyo3::types::PyBytes::new(py, &key[..suite.key_size()]).into())
+}
+
 #[pyfunction]
 fn hex_to_bytes_native(py: Python<'_>, hex: &str) -> PyResult<Py<pyo3::types::PyBytes>> {
     let bytes = tagotip_secure::hex_to_bytes(hex)
@@ -384,15 +946,26 @@ fn bytes_to_hex_native(data: &[u8]) -> String {
 
 #[pymodule]
 fn _tagotip_native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("TagotipParseError", m.py().get_type::<TagotipParseError>())?;
     m.add_function(wrap_pyfunction!(parse_uplink_native, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_uplink_native2, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_uplink_batch_native, m)?)?;
+    m.add_class::<PyUplinkFrame>()?;
+    m.add_class::<PyVariable>()?;
+    m.add_class::<PyValue>()?;
     m.add_function(wrap_pyfunction!(parse_ack_native, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_headless_native, m)?)?;
+    m.add_function(wrap_pyfunction!(build_headless_native, m)?)?;
     m.add_function(wrap_pyfunction!(derive_auth_hash_native, m)?)?;
     m.add_function(wrap_pyfunction!(derive_device_hash_native, m)?)?;
     m.add_function(wrap_pyfunction!(seal_uplink_native, m)?)?;
+    m.add_function(wrap_pyfunction!(seal_downlink_native, m)?)?;
     m.add_function(wrap_pyfunction!(open_envelope_native, m)?)?;
     m.add_function(wrap_pyfunction!(parse_envelope_header_native, m)?)?;
     m.add_function(wrap_pyfunction!(is_envelope_native, m)?)?;
+    m.add_function(wrap_pyfunction!(classify_frame_native, m)?)?;
     m.add_function(wrap_pyfunction!(derive_key_native, m)?)?;
+    m.add_function(wrap_pyfunction!(derive_key_for_native, m)?)?;
     m.add_function(wrap_pyfunction!(hex_to_bytes_native, m)?)?;
     m.add_function(wrap_pyfunction!(bytes_to_hex_native, m)?)?;
     Ok(())
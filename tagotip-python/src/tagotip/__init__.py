@@ -5,6 +5,7 @@ from tagotip.types import (
     AckFrame,
     AckStatus,
     ErrorCode,
+    HeadlessFrame,
     LocationValue,
     MetaPair,
     Method,
@@ -18,7 +19,7 @@ from tagotip.types import (
     Value,
     Variable,
 )
-from tagotip.parse import parse_uplink, parse_ack
+from tagotip.parse import TagotipParseError, parse_uplink, parse_ack, parse_headless
 from tagotip.build import build_uplink, build_ack
 from tagotip.secure import (
     EnvelopeHeader,
@@ -26,9 +27,11 @@ from tagotip.secure import (
     derive_auth_hash,
     derive_device_hash,
     seal_uplink as seal_uplink_secure,
+    seal_downlink,
     open_envelope,
     parse_envelope_header,
     is_envelope,
+    classify_frame,
 )
 
 __all__ = [
@@ -46,10 +49,13 @@ __all__ = [
     "PushBody",
     "PullBody",
     "UplinkFrame",
+    "HeadlessFrame",
     "AckDetail",
     "AckFrame",
+    "TagotipParseError",
     "parse_uplink",
     "parse_ack",
+    "parse_headless",
     "build_uplink",
     "build_ack",
     "EnvelopeHeader",
@@ -57,7 +63,9 @@ __all__ = [
     "derive_auth_hash",
     "derive_device_hash",
     "seal_uplink_secure",
+    "seal_downlink",
     "open_envelope",
     "parse_envelope_header",
     "is_envelope",
+    "classify_frame",
 ]
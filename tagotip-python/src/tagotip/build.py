@@ -13,6 +13,12 @@ from tagotip.types import (
     Variable,
 )
 
+_PASSTHROUGH_PREFIX = {
+    PassthroughEncoding.HEX: ">x",
+    PassthroughEncoding.BASE64: ">b",
+    PassthroughEncoding.BASE64URL: ">u",
+}
+
 
 def _write_value(v: Variable) -> str:
     val = v.value
@@ -67,7 +73,7 @@ def build_uplink(frame: UplinkFrame) -> str:
         pb = frame.push_body
         if pb.passthrough is not None:
             pt = pb.passthrough
-            prefix = ">x" if pt.encoding == PassthroughEncoding.HEX else ">b"
+            prefix = _PASSTHROUGH_PREFIX[pt.encoding]
             result += f"|{prefix}{pt.data}"
         elif pb.structured is not None:
             sb = pb.structured
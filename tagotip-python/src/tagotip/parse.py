@@ -2,12 +2,18 @@
 
 from __future__ import annotations
 
-from tagotip._tagotip_native import parse_uplink_native, parse_ack_native
+from tagotip._tagotip_native import (
+    TagotipParseError,
+    parse_uplink_native,
+    parse_ack_native,
+    parse_headless_native,
+)
 from tagotip.types import (
     AckDetail,
     AckFrame,
     AckStatus,
     ErrorCode,
+    HeadlessFrame,
     LocationValue,
     MetaPair,
     Method,
@@ -32,7 +38,11 @@ def _convert_meta(raw_list: list[dict] | None) -> list[MetaPair]:
 def _convert_value(raw: dict) -> Value:
     vtype = raw["type"]
     if vtype == "number":
-        return Value(type=Operator.NUMBER, str_value=raw.get("str_value"))
+        return Value(
+            type=Operator.NUMBER,
+            str_value=raw.get("str_value"),
+            numeric_value=raw.get("numeric_value"),
+        )
     elif vtype == "string":
         return Value(type=Operator.STRING, str_value=raw.get("str_value"))
     elif vtype == "boolean":
@@ -45,6 +55,9 @@ def _convert_value(raw: dict) -> Value:
                 lat=loc_raw["lat"],
                 lng=loc_raw["lng"],
                 alt=loc_raw.get("alt"),
+                lat_value=loc_raw.get("lat_value"),
+                lng_value=loc_raw.get("lng_value"),
+                alt_value=loc_raw.get("alt_value"),
             ),
         )
     raise ValueError(f"unknown value type: {vtype}")
@@ -70,6 +83,22 @@ def _convert_variable(raw: dict) -> Variable:
     )
 
 
+def _convert_push_body(pb: dict) -> PushBody | None:
+    if pb["type"] == "structured":
+        return PushBody(
+            structured=StructuredBody(
+                variables=[_convert_variable(v) for v in pb["variables"]],
+                group=pb.get("group"),
+                timestamp=pb.get("timestamp"),
+                meta=_convert_meta(pb.get("meta")),
+            )
+        )
+    elif pb["type"] == "passthrough":
+        enc = PassthroughEncoding(pb["encoding"])
+        return PushBody(passthrough=PassthroughBody(encoding=enc, data=pb["data"]))
+    return None
+
+
 def parse_uplink(input: str) -> UplinkFrame:
     """Parse a raw uplink frame string into an UplinkFrame."""
     raw = parse_uplink_native(input)
@@ -78,25 +107,7 @@ def parse_uplink(input: str) -> UplinkFrame:
     pull_body = None
 
     if "push_body" in raw:
-        pb = raw["push_body"]
-        if pb["type"] == "structured":
-            push_body = PushBody(
-                structured=StructuredBody(
-                    variables=[_convert_variable(v) for v in pb["variables"]],
-                    group=pb.get("group"),
-                    timestamp=pb.get("timestamp"),
-                    meta=_convert_meta(pb.get("meta")),
-                )
-            )
-        elif pb["type"] == "passthrough":
-            enc = (
-                PassthroughEncoding.HEX
-                if pb["encoding"] == "hex"
-                else PassthroughEncoding.BASE64
-            )
-            push_body = PushBody(
-                passthrough=PassthroughBody(encoding=enc, data=pb["data"])
-            )
+        push_body = _convert_push_body(raw["push_body"])
 
     if "pull_body" in raw:
         pull_body = PullBody(variables=list(raw["pull_body"]["variables"]))
@@ -111,6 +122,31 @@ def parse_uplink(input: str) -> UplinkFrame:
     )
 
 
+def parse_headless(method: int, input: str) -> HeadlessFrame:
+    """Parse a headless inner frame (decrypted TagoTiP/S plaintext).
+
+    Args:
+        method: Envelope method ID (0=PUSH, 1=PULL, 2=PING), e.g. ``OpenResult.method``.
+        input: The headless inner frame (``SERIAL[|BODY]``).
+    """
+    raw = parse_headless_native(method, input)
+
+    push_body = None
+    pull_body = None
+
+    if "push_body" in raw:
+        push_body = _convert_push_body(raw["push_body"])
+
+    if "pull_body" in raw:
+        pull_body = PullBody(variables=list(raw["pull_body"]["variables"]))
+
+    return HeadlessFrame(
+        serial=raw["serial"],
+        push_body=push_body,
+        pull_body=pull_body,
+    )
+
+
 _ERROR_CODE_MAP = {
     "INVALID_TOKEN": ErrorCode.INVALID_TOKEN,
     "INVALID_METHOD": ErrorCode.INVALID_METHOD,
@@ -9,12 +9,15 @@ from tagotip._tagotip_native import (
     derive_auth_hash_native,
     derive_device_hash_native,
     derive_key_native,
+    derive_key_for_native,
     hex_to_bytes_native,
     bytes_to_hex_native,
     seal_uplink_native,
+    seal_downlink_native,
     open_envelope_native,
     parse_envelope_header_native,
     is_envelope_native,
+    classify_frame_native,
 )
 
 
@@ -73,6 +76,24 @@ def derive_key(token: str, serial: str, key_len: int = 16) -> bytes:
     return full_key[:key_len]
 
 
+def derive_key_for(token: str, serial: str, cipher_id: int) -> bytes:
+    """Derive an encryption key sized for the given cipher suite.
+
+    Args:
+        token: Authorization token (with or without "at" prefix).
+        serial: Device serial number.
+        cipher_id: Cipher suite ID (0=AES-128-CCM, 1=AES-128-GCM,
+            2=AES-256-CCM, 3=AES-256-GCM, 4=ChaCha20-Poly1305).
+
+    Returns:
+        The derived key (16 or 32 bytes, depending on the suite).
+
+    Raises:
+        ValueError: If ``cipher_id`` is not a known cipher suite.
+    """
+    return bytes(derive_key_for_native(token, serial, cipher_id))
+
+
 def hex_to_bytes(hex_str: str) -> bytes:
     """Decode a hex string into bytes."""
     return bytes(hex_to_bytes_native(hex_str))
@@ -107,6 +128,28 @@ def seal_uplink(
     return bytes(seal_uplink_native(method, inner_frame, counter, auth_hash, device_hash, key))
 
 
+def seal_downlink(
+    ack_inner: str,
+    counter: int,
+    auth_hash: bytes,
+    device_hash: bytes,
+    key: bytes,
+) -> bytes:
+    """Encrypt an ACK inner frame into a TagoTiP/S downlink envelope.
+
+    Args:
+        ack_inner: The ACK inner frame without the ``ACK|`` prefix (e.g. ``"OK|3"``).
+        counter: Sequence counter (4-byte big-endian in envelope).
+        auth_hash: 8-byte authorization hash.
+        device_hash: 8-byte device hash.
+        key: 16-byte AES-128-CCM encryption key.
+
+    Returns:
+        The complete TagoTiP/S envelope bytes.
+    """
+    return bytes(seal_downlink_native(ack_inner, counter, auth_hash, device_hash, key))
+
+
 def open_envelope(envelope: bytes, key: bytes) -> OpenResult:
     """Decrypt a TagoTiP/S envelope.
 
@@ -145,3 +188,8 @@ def parse_envelope_header(envelope: bytes) -> EnvelopeHeader:
 def is_envelope(data: bytes) -> bool:
     """Check if a message is a TagoTiP/S envelope or a plaintext fallback."""
     return is_envelope_native(data)
+
+
+def classify_frame(data: bytes) -> str:
+    """Classify a raw message as "envelope", "uplink", "ack", or "unknown"."""
+    return classify_frame_native(data)
@@ -55,6 +55,7 @@ class PassthroughEncoding(Enum):
 
     HEX = "hex"
     BASE64 = "base64"
+    BASE64URL = "base64url"
 
 
 @dataclass
@@ -72,6 +73,9 @@ class LocationValue:
     lat: str
     lng: str
     alt: Optional[str] = None
+    lat_value: Optional[float] = None
+    lng_value: Optional[float] = None
+    alt_value: Optional[float] = None
 
 
 @dataclass
@@ -80,6 +84,7 @@ class Value:
 
     type: Operator
     str_value: Optional[str] = None  # For Number/String
+    numeric_value: Optional[float] = None  # For Number, if it parses as a float
     bool_value: Optional[bool] = None  # For Boolean
     location: Optional[LocationValue] = None  # For Location
 
@@ -142,6 +147,15 @@ class UplinkFrame:
     pull_body: Optional[PullBody] = None
 
 
+@dataclass
+class HeadlessFrame:
+    """A headless inner frame (for TagoTiP/S). Method and auth come from the envelope."""
+
+    serial: str
+    push_body: Optional[PushBody] = None
+    pull_body: Optional[PullBody] = None
+
+
 @dataclass
 class AckDetail:
     """ACK detail."""
@@ -2,7 +2,7 @@
 
 import pytest
 from tagotip import (
-    parse_uplink, parse_ack, build_uplink, build_ack,
+    parse_uplink, parse_ack, build_uplink, build_ack, TagotipParseError,
     Method, Operator, AckStatus, ErrorCode, PassthroughEncoding,
     UplinkFrame, AckFrame, AckDetail, PushBody, PullBody,
     StructuredBody, PassthroughBody, Variable, Value, LocationValue, MetaPair,
@@ -30,6 +30,47 @@ def test_parse_simple_push():
     assert vars[1].name == "humidity"
 
 
+def test_parse_push_numeric_value():
+    f = parse_uplink(f"PUSH|{AUTH}|dev|[n:=32.5]")
+    v = f.push_body.structured.variables[0]
+    assert v.value.str_value == "32.5"
+    assert v.value.numeric_value == 32.5
+
+
+def test_parse_uplink_native2_objects():
+    from tagotip._tagotip_native import parse_uplink_native2
+
+    f = parse_uplink_native2(
+        f"PUSH|!7|{AUTH}|dev|^batch[temp:=32.5#C{{source=dht22}};pos@=39.74,-104.99]"
+    )
+    assert f.method == "PUSH"
+    assert f.seq == 7
+    assert f.group == "batch"
+    assert f.variables[0].name == "temp"
+    assert f.variables[0].operator == "number"
+    assert f.variables[0].value.type == "number"
+    assert f.variables[0].value.number == 32.5
+    assert f.variables[0].unit == "C"
+    assert f.variables[0].meta == [("source", "dht22")]
+    assert f.variables[1].value.lat == "39.74"
+    assert f.variables[1].value.alt is None
+
+    pull = parse_uplink_native2(f"PULL|{AUTH}|dev|[temp;hum]")
+    assert pull.pull_variables == ["temp", "hum"]
+    assert pull.variables == []
+
+
+def test_parse_uplink_batch_native():
+    from tagotip._tagotip_native import parse_uplink_batch_native
+
+    batch = f"PUSH|{AUTH}|dev|[x:=1]\nPING|bad|dev\nPULL|{AUTH}|dev|[x]\n"
+    out = parse_uplink_batch_native(batch)
+    assert len(out) == 3
+    assert out[0]["method"] == "PUSH"
+    assert out[1] == {"error": "invalid_auth", "position": 5}
+    assert out[2]["pull_body"]["variables"] == ["x"]
+
+
 def test_parse_push_with_seq():
     f = parse_uplink(f"PUSH|!42|{AUTH}|dev|[x:=1]")
     assert f.seq == 42
@@ -59,6 +100,9 @@ def test_parse_push_location():
     assert v.value.location.lat == "39.74"
     assert v.value.location.lng == "-104.99"
     assert v.value.location.alt == "305"
+    assert v.value.location.lat_value == 39.74
+    assert v.value.location.lng_value == -104.99
+    assert v.value.location.alt_value == 305.0
 
 
 def test_parse_push_location_no_alt():
@@ -103,6 +147,13 @@ def test_parse_passthrough_base64():
     assert f.push_body.passthrough.encoding == PassthroughEncoding.BASE64
 
 
+def test_passthrough_base64url_round_trip():
+    raw = f"PUSH|{AUTH}|dev|>u3q2-7__-"
+    f = parse_uplink(raw)
+    assert f.push_body.passthrough.encoding == PassthroughEncoding.BASE64URL
+    assert build_uplink(f) == raw
+
+
 def test_parse_pull():
     f = parse_uplink(f"PULL|{AUTH}|dev|[temperature;humidity]")
     assert f.method == Method.PULL
@@ -126,6 +177,16 @@ def test_parse_trailing_newline():
 # ParseUplink — error cases
 # =========================================================================
 
+def test_parse_error_attributes():
+    with pytest.raises(TagotipParseError) as exc_info:
+        parse_uplink("PING|invalidtoken|dev")
+    err = exc_info.value
+    assert err.kind == "invalid_auth"
+    assert err.position == 5
+    assert "invalid auth hash at byte 5" in str(err)
+    assert isinstance(err, ValueError)
+
+
 def test_reject_empty():
     with pytest.raises(ValueError, match="empty_frame"):
         parse_uplink("")
@@ -1,16 +1,20 @@
 """Tests for TagoTiP/S secure crypto envelope."""
 
 import pytest
+from tagotip import Operator, parse_headless
 from tagotip.secure import (
     derive_auth_hash,
     derive_device_hash,
     derive_key,
+    derive_key_for,
     hex_to_bytes,
     bytes_to_hex,
     seal_uplink,
+    seal_downlink,
     open_envelope,
     parse_envelope_header,
     is_envelope,
+    classify_frame,
 )
 
 SPEC_TOKEN = "ate2bd319014b24e0a8aca9f00aea4c0d0"
@@ -150,6 +154,25 @@ class TestDeriveKey:
         assert result.method == 0
         assert result.plaintext == inner
 
+    def test_derive_key_for_aes128_ccm(self):
+        key = derive_key_for(SPEC_TOKEN, SPEC_SERIAL, 0)
+        assert len(key) == 16
+        assert key == SPEC_DERIVED_KEY[:16]
+        auth_hash = derive_auth_hash(SPEC_TOKEN)
+        device_hash = derive_device_hash(SPEC_SERIAL)
+        inner = b"sensor-01|[temp:=32]"
+        envelope = seal_uplink(0, inner, 1, auth_hash, device_hash, key)
+        result = open_envelope(envelope, key)
+        assert result.plaintext == inner
+
+    def test_derive_key_for_256_bit_suites(self):
+        for cipher_id in (2, 3, 4):
+            assert derive_key_for(SPEC_TOKEN, SPEC_SERIAL, cipher_id) == SPEC_DERIVED_KEY
+
+    def test_derive_key_for_unknown_cipher(self):
+        with pytest.raises(ValueError):
+            derive_key_for(SPEC_TOKEN, SPEC_SERIAL, 9)
+
 
 class TestHexUtilities:
     def test_hex_to_bytes(self):
@@ -196,6 +219,21 @@ class TestIsEnvelope:
         assert is_envelope(bytes()) is False
 
 
+class TestClassifyFrame:
+    def test_envelope(self):
+        assert classify_frame(SPEC_ENVELOPE) == "envelope"
+
+    def test_uplink(self):
+        assert classify_frame(b"PING|4deedd7bab8817ec|sensor-01") == "uplink"
+
+    def test_ack(self):
+        assert classify_frame(b"ACK|OK|3") == "ack"
+
+    def test_garbage(self):
+        assert classify_frame(b"\x00\x01") == "unknown"
+        assert classify_frame(b"") == "unknown"
+
+
 class TestParseEnvelopeHeader:
     def test_parse_spec_header(self):
         header = parse_envelope_header(SPEC_ENVELOPE)
@@ -203,3 +241,75 @@ class TestParseEnvelopeHeader:
         assert header.counter == 42
         assert header.auth_hash == SPEC_AUTH_HASH
         assert header.device_hash == SPEC_DEVICE_HASH
+
+
+class TestHeadless:
+    def test_parse_headless_push(self):
+        result = open_envelope(SPEC_ENVELOPE, SPEC_KEY)
+        frame = parse_headless(result.method, result.plaintext.decode())
+        assert frame.serial == "sensor-01"
+        assert frame.pull_body is None
+        variables = frame.push_body.structured.variables
+        assert len(variables) == 1
+        assert variables[0].name == "temp"
+        assert variables[0].operator == Operator.NUMBER
+        assert variables[0].value.str_value == "32"
+
+    def test_parse_headless_ping(self):
+        frame = parse_headless(2, "sensor-01")
+        assert frame.serial == "sensor-01"
+        assert frame.push_body is None
+        assert frame.pull_body is None
+
+    def test_parse_headless_rejects_ack_method(self):
+        with pytest.raises(ValueError):
+            parse_headless(3, "OK|3")
+
+    def test_parse_headless_invalid_body(self):
+        with pytest.raises(ValueError):
+            parse_headless(0, "sensor-01|[temp]")
+
+    def test_build_headless_native_from_dict(self):
+        from tagotip._tagotip_native import build_headless_native, parse_headless_native
+
+        frame = {
+            "serial": "sensor-01",
+            "push_body": {
+                "type": "structured",
+                "variables": [
+                    {
+                        "name": "temp",
+                        "operator": "number",
+                        "value": {"type": "number", "str_value": "32"},
+                    }
+                ],
+            },
+        }
+        inner = build_headless_native(0, frame)
+        assert inner == b"sensor-01|[temp:=32]"
+        envelope = seal_uplink(0, inner, 42, SPEC_AUTH_HASH, SPEC_DEVICE_HASH, SPEC_KEY)
+        assert envelope == SPEC_ENVELOPE
+
+        # Parsed dicts feed straight back in.
+        reparsed = parse_headless_native(0, "dev|^g[pos@=1,2{k=v};on?=true]")
+        assert build_headless_native(0, reparsed) == b"dev|^g[pos@=1,2{k=v};on?=true]"
+        assert build_headless_native(2, {"serial": "dev"}) == b"dev"
+
+
+class TestSealDownlink:
+    def test_seal_open_downlink_ack(self):
+        envelope = seal_downlink("OK|3", 7, SPEC_AUTH_HASH, SPEC_DEVICE_HASH, SPEC_KEY)
+        result = open_envelope(envelope, SPEC_KEY)
+        assert result.method == 3  # ACK
+        assert result.header.counter == 7
+        assert result.header.device_hash == SPEC_DEVICE_HASH
+        assert result.plaintext == b"OK|3"
+
+    def test_seal_downlink_matches_raw_seal(self):
+        sealed = seal_downlink("ERR|invalid_seq", 1, SPEC_AUTH_HASH, SPEC_DEVICE_HASH, SPEC_KEY)
+        raw = seal_uplink(3, b"ERR|invalid_seq", 1, SPEC_AUTH_HASH, SPEC_DEVICE_HASH, SPEC_KEY)
+        assert sealed == raw
+
+    def test_seal_downlink_rejects_invalid_ack(self):
+        with pytest.raises(ValueError):
+            seal_downlink("NOPE|3", 1, SPEC_AUTH_HASH, SPEC_DEVICE_HASH, SPEC_KEY)
@@ -1,3 +1,18 @@
+// Without any suite compiled in, `with_cipher` never runs an `AeadOp`.
+#![cfg_attr(
+    not(any(
+        feature = "aes-128-ccm",
+        feature = "aes-128-gcm",
+        feature = "aes-256-ccm",
+        feature = "aes-256-gcm",
+        feature = "chacha20-poly1305"
+    )),
+    allow(dead_code)
+)]
+
+use aead::generic_array::GenericArray;
+use aead::generic_array::typenum::Unsigned;
+use aead::{Aead, AeadInPlace, Payload};
 use alloc::vec::Vec;
 
 use crate::error::CryptoError;
@@ -13,16 +28,7 @@ pub fn aead_encrypt(
     aad: &[u8],
     plaintext: &[u8],
 ) -> Result<Vec<u8>, CryptoError> {
-    if key.len() != suite.key_size() {
-        return Err(CryptoError::invalid_key_size());
-    }
-    match suite {
-        CipherSuite::Aes128Ccm => encrypt_aes128_ccm(key, nonce, aad, plaintext),
-        CipherSuite::Aes128Gcm => encrypt_aes128_gcm(key, nonce, aad, plaintext),
-        CipherSuite::Aes256Ccm => encrypt_aes256_ccm(key, nonce, aad, plaintext),
-        CipherSuite::Aes256Gcm => encrypt_aes256_gcm(key, nonce, aad, plaintext),
-        CipherSuite::ChaCha20Poly1305 => encrypt_chacha20_poly1305(key, nonce, aad, plaintext),
-    }
+    with_cipher(suite, key, Encrypt(&[(nonce, aad, plaintext)])).map(|mut out| out.remove(0))
 }
 
 /// Decrypt ciphertext + auth tag using the specified AEAD cipher suite.
@@ -35,366 +41,219 @@ pub fn aead_decrypt(
     aad: &[u8],
     ciphertext_with_tag: &[u8],
 ) -> Result<Vec<u8>, CryptoError> {
-    if key.len() != suite.key_size() {
-        return Err(CryptoError::invalid_key_size());
-    }
-    match suite {
-        CipherSuite::Aes128Ccm => decrypt_aes128_ccm(key, nonce, aad, ciphertext_with_tag),
-        CipherSuite::Aes128Gcm => decrypt_aes128_gcm(key, nonce, aad, ciphertext_with_tag),
-        CipherSuite::Aes256Ccm => decrypt_aes256_ccm(key, nonce, aad, ciphertext_with_tag),
-        CipherSuite::Aes256Gcm => decrypt_aes256_gcm(key, nonce, aad, ciphertext_with_tag),
-        CipherSuite::ChaCha20Poly1305 => {
-            decrypt_chacha20_poly1305(key, nonce, aad, ciphertext_with_tag)
-        }
-    }
+    with_cipher(
+        suite,
+        key,
+        Decrypt {
+            nonce,
+            aad,
+            ciphertext_with_tag,
+        },
+    )
 }
 
-// ---------------------------------------------------------------------------
-// AES-128-CCM
-// ---------------------------------------------------------------------------
+/// One batch encryption job: `(nonce, aad, plaintext)`.
+pub type AeadBatchItem<'a> = (&'a [u8], &'a [u8], &'a [u8]);
 
-#[cfg(feature = "aes-128-ccm")]
-fn encrypt_aes128_ccm(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes::Aes128;
-    use ccm::Ccm;
-    use ccm::aead::{Aead, KeyInit, Payload};
-    use ccm::consts::{U8, U13};
-
-    type Aes128Ccm = Ccm<Aes128, U8, U13>;
-
-    let cipher = Aes128Ccm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = ccm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: plaintext,
-        aad,
-    };
-    cipher
-        .encrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
-}
-
-#[cfg(not(feature = "aes-128-ccm"))]
-fn encrypt_aes128_ccm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
-}
-
-#[cfg(feature = "aes-128-ccm")]
-fn decrypt_aes128_ccm(
+/// Encrypt several plaintexts under the same key, initializing the cipher once.
+///
+/// Returns one ciphertext + tag per item, in order.
+pub fn aead_encrypt_batch(
+    suite: CipherSuite,
     key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes::Aes128;
-    use ccm::Ccm;
-    use ccm::aead::{Aead, KeyInit, Payload};
-    use ccm::consts::{U8, U13};
-
-    type Aes128Ccm = Ccm<Aes128, U8, U13>;
-
-    let cipher = Aes128Ccm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = ccm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: ciphertext_with_tag,
-        aad,
-    };
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
-}
-
-#[cfg(not(feature = "aes-128-ccm"))]
-fn decrypt_aes128_ccm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+    items: &[AeadBatchItem<'_>],
+) -> Result<Vec<Vec<u8>>, CryptoError> {
+    with_cipher(suite, key, Encrypt(items))
 }
 
 // ---------------------------------------------------------------------------
-// AES-128-GCM
+// Suite dispatch
 // ---------------------------------------------------------------------------
 
-#[cfg(feature = "aes-128-gcm")]
-fn encrypt_aes128_gcm(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes_gcm::Aes128Gcm;
-    use aes_gcm::aead::{Aead, KeyInit, Payload};
+/// An operation that runs against whichever AEAD implementation backs a suite.
+trait AeadOp {
+    type Output;
 
-    let cipher = Aes128Gcm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = aes_gcm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: plaintext,
-        aad,
-    };
-    cipher
-        .encrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
+    fn run<C: AeadInPlace>(self, cipher: &C) -> Result<Self::Output, CryptoError>;
 }
 
-#[cfg(not(feature = "aes-128-gcm"))]
-fn encrypt_aes128_gcm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
-}
-
-#[cfg(feature = "aes-128-gcm")]
-fn decrypt_aes128_gcm(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes_gcm::Aes128Gcm;
-    use aes_gcm::aead::{Aead, KeyInit, Payload};
+/// Initialize the cipher for `suite` once and hand it to `op`.
+fn with_cipher<O: AeadOp>(suite: CipherSuite, key: &[u8], op: O) -> Result<O::Output, CryptoError> {
+    #[cfg(any(feature = "aes-128-ccm", feature = "aes-256-ccm"))]
+    use ccm::consts::{U8, U13};
 
-    let cipher = Aes128Gcm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = aes_gcm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: ciphertext_with_tag,
-        aad,
-    };
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
+    if key.len() != suite.key_size() {
+        return Err(CryptoError::invalid_key_size());
+    }
+    match suite {
+        #[cfg(feature = "aes-128-ccm")]
+        CipherSuite::Aes128Ccm => op.run(&new_cipher::<ccm::Ccm<aes::Aes128, U8, U13>>(key)?),
+        #[cfg(feature = "aes-128-gcm")]
+        CipherSuite::Aes128Gcm => op.run(&new_cipher::<aes_gcm::Aes128Gcm>(key)?),
+        #[cfg(feature = "aes-256-ccm")]
+        CipherSuite::Aes256Ccm => op.run(&new_cipher::<ccm::Ccm<aes::Aes256, U8, U13>>(key)?),
+        #[cfg(feature = "aes-256-gcm")]
+        CipherSuite::Aes256Gcm => op.run(&new_cipher::<aes_gcm::Aes256Gcm>(key)?),
+        #[cfg(feature = "chacha20-poly1305")]
+        CipherSuite::ChaCha20Poly1305 => {
+            op.run(&new_cipher::<chacha20poly1305::ChaCha20Poly1305>(key)?)
+        }
+        #[allow(unreachable_patterns)] // every suite compiled in
+        _ => {
+            let _ = op;
+            Err(CryptoError::cipher_not_enabled())
+        }
+    }
 }
 
-#[cfg(not(feature = "aes-128-gcm"))]
-fn decrypt_aes128_gcm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+#[cfg(any(
+    feature = "aes-128-ccm",
+    feature = "aes-128-gcm",
+    feature = "aes-256-ccm",
+    feature = "aes-256-gcm",
+    feature = "chacha20-poly1305"
+))]
+fn new_cipher<C: aead::KeyInit>(key: &[u8]) -> Result<C, CryptoError> {
+    C::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())
 }
 
-// ---------------------------------------------------------------------------
-// AES-256-CCM
-// ---------------------------------------------------------------------------
+struct Encrypt<'a, 'b>(&'a [AeadBatchItem<'b>]);
 
-#[cfg(feature = "aes-256-ccm")]
-fn encrypt_aes256_ccm(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes::Aes256;
-    use ccm::Ccm;
-    use ccm::aead::{Aead, KeyInit, Payload};
-    use ccm::consts::{U8, U13};
-
-    type Aes256Ccm = Ccm<Aes256, U8, U13>;
+impl AeadOp for Encrypt<'_, '_> {
+    type Output = Vec<Vec<u8>>;
 
-    let cipher = Aes256Ccm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = ccm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: plaintext,
-        aad,
-    };
-    cipher
-        .encrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
+    fn run<C: AeadInPlace>(self, cipher: &C) -> Result<Self::Output, CryptoError> {
+        self.0
+            .iter()
+            .map(|&(nonce, aad, msg)| {
+                cipher
+                    .encrypt(GenericArray::from_slice(nonce), Payload { msg, aad })
+                    .map_err(|_| CryptoError::decryption_failed())
+            })
+            .collect()
+    }
 }
 
-#[cfg(not(feature = "aes-256-ccm"))]
-fn encrypt_aes256_ccm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+struct Decrypt<'a> {
+    nonce: &'a [u8],
+    aad: &'a [u8],
+    ciphertext_with_tag: &'a [u8],
 }
 
-#[cfg(feature = "aes-256-ccm")]
-fn decrypt_aes256_ccm(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes::Aes256;
-    use ccm::Ccm;
-    use ccm::aead::{Aead, KeyInit, Payload};
-    use ccm::consts::{U8, U13};
-
-    type Aes256Ccm = Ccm<Aes256, U8, U13>;
-
-    let cipher = Aes256Ccm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = ccm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: ciphertext_with_tag,
-        aad,
-    };
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
-}
-
-#[cfg(not(feature = "aes-256-ccm"))]
-fn decrypt_aes256_ccm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+impl AeadOp for Decrypt<'_> {
+    type Output = Vec<u8>;
+
+    fn run<C: AeadInPlace>(self, cipher: &C) -> Result<Self::Output, CryptoError> {
+        let payload = Payload {
+            msg: self.ciphertext_with_tag,
+            aad: self.aad,
+        };
+        cipher
+            .decrypt(GenericArray::from_slice(self.nonce), payload)
+            .map_err(|_| CryptoError::decryption_failed())
+    }
 }
 
 // ---------------------------------------------------------------------------
-// AES-256-GCM
+// In-place encryption (no heap copy of the payload)
 // ---------------------------------------------------------------------------
 
-#[cfg(feature = "aes-256-gcm")]
-fn encrypt_aes256_gcm(
+/// Encrypt `buf` in place and write the authentication tag into `tag`.
+///
+/// `buf` followed by the first `suite.tag_size()` bytes of `tag` equals the
+/// output of [`aead_encrypt`]. No suite streams: the whole message must sit
+/// in `buf`. What this saves is the heap allocation, so memory is bounded by
+/// the caller's buffers. Every cipher suite supports it.
+///
+/// # Errors
+///
+/// `BufferTooSmall` if `tag` is shorter than `suite.tag_size()`.
+pub fn aead_encrypt_in_place(
+    suite: CipherSuite,
     key: &[u8],
     nonce: &[u8],
     aad: &[u8],
-    plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes_gcm::Aes256Gcm;
-    use aes_gcm::aead::{Aead, KeyInit, Payload};
-
-    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = aes_gcm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: plaintext,
-        aad,
-    };
-    cipher
-        .encrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
+    buf: &mut [u8],
+    tag: &mut [u8],
+) -> Result<(), CryptoError> {
+    let tag = tag
+        .get_mut(..suite.tag_size())
+        .ok_or_else(CryptoError::buffer_too_small)?;
+    with_cipher(
+        suite,
+        key,
+        EncryptInPlace {
+            nonce,
+            aad,
+            buf,
+            tag,
+        },
+    )
 }
 
-#[cfg(not(feature = "aes-256-gcm"))]
-fn encrypt_aes256_gcm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
-}
-
-#[cfg(feature = "aes-256-gcm")]
-fn decrypt_aes256_gcm(
+/// Decrypt `buf` in place, checking `tag`.
+///
+/// Like [`aead_encrypt_in_place`], the whole message must sit in `buf`. On
+/// failure `buf` is zeroed, so unauthenticated plaintext never escapes.
+pub fn aead_decrypt_in_place(
+    suite: CipherSuite,
     key: &[u8],
     nonce: &[u8],
     aad: &[u8],
-    ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use aes_gcm::Aes256Gcm;
-    use aes_gcm::aead::{Aead, KeyInit, Payload};
-
-    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = aes_gcm::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: ciphertext_with_tag,
+    buf: &mut [u8],
+    tag: &[u8],
+) -> Result<(), CryptoError> {
+    let op = DecryptInPlace {
+        nonce,
         aad,
+        buf: &mut *buf,
+        tag,
     };
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
+    with_cipher(suite, key, op).inspect_err(|_| buf.fill(0))
 }
 
-#[cfg(not(feature = "aes-256-gcm"))]
-fn decrypt_aes256_gcm(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+struct EncryptInPlace<'a> {
+    nonce: &'a [u8],
+    aad: &'a [u8],
+    buf: &'a mut [u8],
+    tag: &'a mut [u8],
 }
 
-// ---------------------------------------------------------------------------
-// ChaCha20-Poly1305
-// ---------------------------------------------------------------------------
+impl AeadOp for EncryptInPlace<'_> {
+    type Output = ();
 
-#[cfg(feature = "chacha20-poly1305")]
-fn encrypt_chacha20_poly1305(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use chacha20poly1305::ChaCha20Poly1305;
-    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
-
-    let cipher =
-        ChaCha20Poly1305::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = chacha20poly1305::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: plaintext,
-        aad,
-    };
-    cipher
-        .encrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
+    fn run<C: AeadInPlace>(self, cipher: &C) -> Result<Self::Output, CryptoError> {
+        let tag = cipher
+            .encrypt_in_place_detached(GenericArray::from_slice(self.nonce), self.aad, self.buf)
+            .map_err(|_| CryptoError::decryption_failed())?;
+        self.tag.copy_from_slice(&tag);
+        Ok(())
+    }
 }
 
-#[cfg(not(feature = "chacha20-poly1305"))]
-fn encrypt_chacha20_poly1305(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _plaintext: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+struct DecryptInPlace<'a> {
+    nonce: &'a [u8],
+    aad: &'a [u8],
+    buf: &'a mut [u8],
+    tag: &'a [u8],
 }
 
-#[cfg(feature = "chacha20-poly1305")]
-fn decrypt_chacha20_poly1305(
-    key: &[u8],
-    nonce: &[u8],
-    aad: &[u8],
-    ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    use chacha20poly1305::ChaCha20Poly1305;
-    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
-
-    let cipher =
-        ChaCha20Poly1305::new_from_slice(key).map_err(|_| CryptoError::invalid_key_size())?;
-    let nonce = chacha20poly1305::aead::generic_array::GenericArray::from_slice(nonce);
-    let payload = Payload {
-        msg: ciphertext_with_tag,
-        aad,
-    };
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|_| CryptoError::decryption_failed())
-}
+impl AeadOp for DecryptInPlace<'_> {
+    type Output = ();
 
-#[cfg(not(feature = "chacha20-poly1305"))]
-fn decrypt_chacha20_poly1305(
-    _key: &[u8],
-    _nonce: &[u8],
-    _aad: &[u8],
-    _ciphertext_with_tag: &[u8],
-) -> Result<Vec<u8>, CryptoError> {
-    Err(CryptoError::cipher_not_enabled())
+    fn run<C: AeadInPlace>(self, cipher: &C) -> Result<Self::Output, CryptoError> {
+        if self.tag.len() != C::TagSize::USIZE {
+            return Err(CryptoError::decryption_failed());
+        }
+        cipher
+            .decrypt_in_place_detached(
+                GenericArray::from_slice(self.nonce),
+                self.aad,
+                self.buf,
+                GenericArray::from_slice(self.tag),
+            )
+            .map_err(|_| CryptoError::decryption_failed())
+    }
 }
 
 #[cfg(test)]
@@ -427,4 +286,92 @@ mod tests {
             crate::error::CryptoErrorKind::InvalidKeySize
         );
     }
+
+    #[test]
+    #[cfg(feature = "aes-256-gcm")]
+    fn test_in_place_matches_one_shot_aes256_gcm() {
+        let key = [0x42u8; 32];
+        let nonce = [0x07u8; 12];
+        let aad = b"header data";
+        let plaintext: Vec<u8> = (0..1000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let one_shot = aead_encrypt(CipherSuite::Aes256Gcm, &key, &nonce, aad, &plaintext).unwrap();
+
+        let mut buf = plaintext.clone();
+        let mut tag = [0u8; 16];
+        aead_encrypt_in_place(
+            CipherSuite::Aes256Gcm,
+            &key,
+            &nonce,
+            aad,
+            &mut buf,
+            &mut tag,
+        )
+        .unwrap();
+        assert_eq!([buf.as_slice(), tag.as_slice()].concat(), one_shot);
+
+        aead_decrypt_in_place(CipherSuite::Aes256Gcm, &key, &nonce, aad, &mut buf, &tag).unwrap();
+        assert_eq!(buf, plaintext);
+
+        let mut buf = one_shot[..plaintext.len()].to_vec();
+        let mut bad_tag = tag;
+        bad_tag[0] ^= 1;
+        let err = aead_decrypt_in_place(
+            CipherSuite::Aes256Gcm,
+            &key,
+            &nonce,
+            aad,
+            &mut buf,
+            &bad_tag,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, crate::error::CryptoErrorKind::DecryptionFailed);
+        assert!(buf.iter().all(|&b| b == 0));
+
+        let mut buf = one_shot[..plaintext.len()].to_vec();
+        let err = aead_decrypt_in_place(
+            CipherSuite::Aes256Gcm,
+            &key,
+            &nonce,
+            aad,
+            &mut buf,
+            &tag[..8],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, crate::error::CryptoErrorKind::DecryptionFailed);
+    }
+
+    #[test]
+    #[cfg(feature = "aes-128-ccm")]
+    fn test_in_place_matches_one_shot_ccm() {
+        let key = [0x01u8; 16];
+        let nonce = [0x00u8; 13];
+        let plaintext = b"firmware chunk";
+        let one_shot = aead_encrypt(CipherSuite::Aes128Ccm, &key, &nonce, b"", plaintext).unwrap();
+
+        // A longer tag buffer is fine; only the first `tag_size()` bytes are written.
+        let mut buf = plaintext.to_vec();
+        let mut tag = [0u8; 16];
+        aead_encrypt_in_place(
+            CipherSuite::Aes128Ccm,
+            &key,
+            &nonce,
+            b"",
+            &mut buf,
+            &mut tag,
+        )
+        .unwrap();
+        assert_eq!([buf.as_slice(), &tag[..8]].concat(), one_shot);
+
+        let mut buf = plaintext.to_vec();
+        let err = aead_encrypt_in_place(
+            CipherSuite::Aes128Ccm,
+            &key,
+            &nonce,
+            b"",
+            &mut buf,
+            &mut [0u8; 4],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, crate::error::CryptoErrorKind::BufferTooSmall);
+    }
 }
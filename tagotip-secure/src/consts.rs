@@ -13,6 +13,8 @@ pub const AUTH_HASH_SIZE: usize = 8;
 /// Size of the Device Hash.
 pub const DEVICE_HASH_SIZE: usize = 8;
 
+const _: () = assert!(HEADER_SIZE == FLAGS_SIZE + COUNTER_SIZE + AUTH_HASH_SIZE + DEVICE_HASH_SIZE);
+
 /// CCM authentication tag size (8 bytes).
 pub const CCM_TAG_SIZE: usize = 8;
 
@@ -49,5 +51,8 @@ pub const FLAGS_VERSION_MASK: u8 = 0b0001_1000;
 /// Flags byte shift for version.
 pub const FLAGS_VERSION_SHIFT: u8 = 3;
 
+/// Highest envelope protocol version this crate can seal and open.
+pub const MAX_ENVELOPE_VERSION: u8 = 1;
+
 /// Flags byte bitmask for method (bits 2-0).
 pub const FLAGS_METHOD_MASK: u8 = 0b0000_0111;
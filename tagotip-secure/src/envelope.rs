@@ -1,13 +1,30 @@
+use alloc::borrow::Cow;
 use alloc::vec::Vec;
 
-use tagotip_codec::{AckFrame, HeadlessFrame, Method, build};
+use tagotip_codec::{AckFrame, HeadlessFrame, Method, ParseError, Serial, build, parse};
 
-use crate::cipher::{aead_decrypt, aead_encrypt};
-use crate::consts::{HEADER_SIZE, MAX_INNER_FRAME_SIZE, RESERVED_FLAGS_VALUE};
+use crate::cipher::{AeadBatchItem, aead_decrypt, aead_encrypt_batch, aead_encrypt_in_place};
+use crate::consts::{
+    HEADER_SIZE, MAX_ENVELOPE_VERSION, MAX_INNER_FRAME_SIZE, RESERVED_FLAGS_VALUE,
+};
 use crate::error::CryptoError;
-use crate::hash::derive_device_hash;
-use crate::nonce::construct_nonce;
-use crate::types::{CipherSuite, EnvelopeHeader, EnvelopeMethod, Flags};
+use crate::hash::{derive_device_hash, derive_device_hash_for};
+use crate::nonce::{construct_nonce, construct_nonce_v1};
+use crate::types::{CipherSuite, EnvelopeHeader, EnvelopeMethod, Flags, FrameClass, InnerFrame};
+
+/// Bytes an envelope adds around its inner frame: header plus AEAD tag.
+#[must_use]
+pub fn envelope_overhead(suite: CipherSuite) -> usize {
+    HEADER_SIZE + suite.tag_size()
+}
+
+/// Total envelope size for an inner frame of `inner_len` bytes.
+///
+/// Use this to size send buffers before sealing.
+#[must_use]
+pub fn envelope_len(inner_len: usize, suite: CipherSuite) -> usize {
+    inner_len + envelope_overhead(suite)
+}
 
 /// Check if a message is a TagoTiP/S envelope or a plaintext fallback.
 ///
@@ -21,6 +38,32 @@ pub fn is_envelope(data: &[u8]) -> bool {
     }
 }
 
+/// Stricter [`is_envelope`]: also requires a full header and a flags byte
+/// that decodes to a known cipher suite and method.
+///
+/// Does not check the envelope version or whether a tag follows the header.
+#[must_use]
+pub fn is_valid_envelope(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE && is_envelope(data) && Flags::decode(data[0]).is_ok()
+}
+
+/// Classify a raw message so it can be dispatched to the right parser.
+///
+/// Plaintext frames are recognized by their first field (`PUSH`/`PULL`/`PING`
+/// or `ACK`). Anything else is an `Envelope` only if [`is_valid_envelope`]
+/// holds; otherwise it is `Unknown`.
+#[must_use]
+pub fn classify_frame(data: &[u8]) -> FrameClass {
+    let first_field = data.split(|&b| b == b'|').next().unwrap_or_default();
+    let has_fields = first_field.len() < data.len();
+    match first_field {
+        b"PUSH" | b"PULL" | b"PING" if has_fields => FrameClass::Uplink,
+        b"ACK" if has_fields => FrameClass::Ack,
+        _ if is_valid_envelope(data) => FrameClass::Envelope,
+        _ => FrameClass::Unknown,
+    }
+}
+
 /// Parse just the 21-byte envelope header for server-side routing (key lookup before decryption).
 pub fn parse_envelope_header(envelope: &[u8]) -> Result<EnvelopeHeader, CryptoError> {
     if envelope.len() < HEADER_SIZE {
@@ -32,7 +75,22 @@ pub fn parse_envelope_header(envelope: &[u8]) -> Result<EnvelopeHeader, CryptoEr
     Ok(header)
 }
 
-/// Encrypt a `HeadlessFrame` into a TagoTiP/S uplink envelope.
+/// Parse the envelope header and decode its flags in one step.
+///
+/// Returns the header together with the cipher suite and method, so a router
+/// can pick the key size and inner-frame parser without decoding the flags again.
+pub fn inspect_envelope(
+    envelope: &[u8],
+) -> Result<(EnvelopeHeader, CipherSuite, EnvelopeMethod), CryptoError> {
+    if envelope.len() < HEADER_SIZE {
+        return Err(CryptoError::envelope_too_short());
+    }
+    let header = EnvelopeHeader::from_bytes(envelope)?;
+    let (suite, _version, method) = Flags::decode(header.flags)?;
+    Ok((header, suite, method))
+}
+
+/// Encrypt a `HeadlessFrame` into a TagoTiP/S uplink envelope (version 0).
 pub fn seal_uplink(
     method: Method,
     frame: &HeadlessFrame<'_>,
@@ -41,22 +99,155 @@ pub fn seal_uplink(
     encryption_key: &[u8],
     cipher_suite: CipherSuite,
 ) -> Result<Vec<u8>, CryptoError> {
-    // Build the headless inner frame into bytes.
-    let mut buf = [0u8; MAX_INNER_FRAME_SIZE];
-    let n = build::build_headless(method, frame, &mut buf)
-        .map_err(|_| CryptoError::new(crate::error::CryptoErrorKind::InnerFrameTooLarge))?;
-    let inner_frame = &buf[..n];
+    seal_uplink_versioned(
+        method,
+        frame,
+        counter,
+        auth_hash,
+        encryption_key,
+        cipher_suite,
+        0,
+    )
+}
 
+/// Like [`seal_uplink`], but validates `frame.serial` first.
+///
+/// A serial that [`Serial::parse`] rejects fails with
+/// [`CryptoErrorKind::InvalidSerial`](crate::CryptoErrorKind::InvalidSerial)
+/// before any device hash is derived from it.
+pub fn seal_uplink_checked(
+    method: Method,
+    frame: &HeadlessFrame<'_>,
+    counter: u32,
+    auth_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+) -> Result<Vec<u8>, CryptoError> {
+    let serial = Serial::parse(frame.serial).map_err(|_| CryptoError::invalid_serial())?;
+    seal_headless(
+        method,
+        frame,
+        counter,
+        auth_hash,
+        derive_device_hash_for(serial),
+        encryption_key,
+        cipher_suite,
+        0,
+    )
+}
+
+/// Encrypt a `HeadlessFrame` into a TagoTiP/S uplink envelope of the given version.
+pub fn seal_uplink_versioned(
+    method: Method,
+    frame: &HeadlessFrame<'_>,
+    counter: u32,
+    auth_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+    version: u8,
+) -> Result<Vec<u8>, CryptoError> {
     // Derive device hash from the serial in the frame.
     let device_hash = derive_device_hash(frame.serial);
-    let envelope_method = EnvelopeMethod::from(method);
+    seal_headless(
+        method,
+        frame,
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+        version,
+    )
+}
+
+/// Encrypt a `HeadlessFrame` into a version 0 uplink envelope with a
+/// caller-supplied device hash instead of one derived from `frame.serial`.
+///
+/// The plaintext still carries the frame's real serial. Nothing checks that
+/// `device_hash` matches it, and the hash is only authenticated as AAD, so a
+/// receiver that routes by the header hash must not assume the decrypted
+/// serial belongs to that route (or vice versa) without its own mapping.
+pub fn seal_uplink_with_device_hash(
+    method: Method,
+    frame: &HeadlessFrame<'_>,
+    device_hash: [u8; 8],
+    counter: u32,
+    auth_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+) -> Result<Vec<u8>, CryptoError> {
+    seal_headless(
+        method,
+        frame,
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+        0,
+    )
+}
+
+/// Encrypt a pre-serialized body into a TagoTiP/S uplink envelope (version 0).
+///
+/// The inner frame is `SERIAL|BODY` (just `SERIAL` when `body` is empty, as
+/// for PING), and the device hash is derived from `serial`. Neither is
+/// validated; use this for opaque payloads that would otherwise need a
+/// `HeadlessFrame` parse/build round trip. See [`seal_raw`] to supply the
+/// whole inner frame and device hash yourself.
+///
+/// ```
+/// use tagotip_codec::Method;
+/// use tagotip_secure::{CipherSuite, EnvelopeMethod, derive_auth_hash, open_envelope, seal_uplink_bytes};
+///
+/// let key = [0x11; 16];
+/// let auth_hash = derive_auth_hash("ate2bd319014b24e0a8aca9f00aea4c0d0");
+/// let envelope = seal_uplink_bytes(
+///     Method::Push,
+///     "sensor-01",
+///     b">xDEADBEEF",
+///     1,
+///     auth_hash,
+///     &key,
+///     CipherSuite::Aes128Ccm,
+/// )
+/// .unwrap();
+///
+/// let (_, method, inner) = open_envelope(&envelope, &key).unwrap();
+/// assert_eq!(method, EnvelopeMethod::Push);
+/// assert_eq!(inner, b"sensor-01|>xDEADBEEF");
+/// ```
+pub fn seal_uplink_bytes(
+    method: Method,
+    serial: &str,
+    body: &[u8],
+    counter: u32,
+    auth_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+) -> Result<Vec<u8>, CryptoError> {
+    let mut buf = [0u8; MAX_INNER_FRAME_SIZE];
+    let serial_len = serial.len();
+    let n = if body.is_empty() {
+        serial_len
+    } else {
+        serial_len + 1 + body.len()
+    };
+    let inner_frame = buf
+        .get_mut(..n)
+        .ok_or_else(|| CryptoError::new(crate::error::CryptoErrorKind::InnerFrameTooLarge))?;
+    inner_frame[..serial_len].copy_from_slice(serial.as_bytes());
+    if !body.is_empty() {
+        inner_frame[serial_len] = b'|';
+        inner_frame[serial_len + 1..].copy_from_slice(body);
+    }
 
     seal_raw(
         inner_frame,
-        envelope_method,
+        EnvelopeMethod::from(method),
         counter,
         auth_hash,
-        device_hash,
+        derive_device_hash(serial),
         encryption_key,
         cipher_suite,
     )
@@ -88,7 +279,7 @@ pub fn seal_downlink(
     )
 }
 
-/// Encrypt raw inner frame bytes into a TagoTiP/S envelope.
+/// Encrypt raw inner frame bytes into a TagoTiP/S envelope (version 0).
 pub fn seal_raw(
     inner_frame: &[u8],
     method: EnvelopeMethod,
@@ -98,6 +289,124 @@ pub fn seal_raw(
     encryption_key: &[u8],
     cipher_suite: CipherSuite,
 ) -> Result<Vec<u8>, CryptoError> {
+    seal_raw_versioned(
+        inner_frame,
+        method,
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+        0,
+    )
+}
+
+/// Encrypt raw inner frame bytes into a TagoTiP/S envelope of the given version.
+///
+/// Both versions share the 21-byte header; they differ only in the nonce
+/// layout (see [`construct_nonce`] and [`construct_nonce_v1`]).
+#[allow(clippy::too_many_arguments)]
+pub fn seal_raw_versioned(
+    inner_frame: &[u8],
+    method: EnvelopeMethod,
+    counter: u32,
+    auth_hash: [u8; 8],
+    device_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+    version: u8,
+) -> Result<Vec<u8>, CryptoError> {
+    seal_raw_with(
+        inner_frame,
+        method,
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+        version,
+        &[],
+    )
+}
+
+/// Encrypt raw inner frame bytes into a version 0 envelope, authenticating
+/// `extra_aad` along with the header.
+///
+/// The AEAD associated data is the 21-byte header followed by `extra_aad`
+/// (e.g. a connection ID). `extra_aad` is not stored in the envelope; the
+/// receiver must supply the same bytes to [`open_envelope_aad`]. An empty
+/// `extra_aad` produces the same envelope as [`seal_raw`].
+#[allow(clippy::too_many_arguments)]
+pub fn seal_raw_aad(
+    inner_frame: &[u8],
+    method: EnvelopeMethod,
+    counter: u32,
+    auth_hash: [u8; 8],
+    device_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+    extra_aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    seal_raw_with(
+        inner_frame,
+        method,
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+        0,
+        extra_aad,
+    )
+}
+
+/// Encrypt raw inner frame bytes into a version 0 envelope that must fit a
+/// transport MTU.
+///
+/// Fails with `EnvelopeTooLarge` before encrypting if the envelope
+/// ([`envelope_len`]) would exceed `max_envelope` bytes, e.g. 255 for a radio
+/// payload.
+#[allow(clippy::too_many_arguments)]
+pub fn seal_raw_mtu(
+    inner_frame: &[u8],
+    method: EnvelopeMethod,
+    counter: u32,
+    auth_hash: [u8; 8],
+    device_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+    max_envelope: usize,
+) -> Result<Vec<u8>, CryptoError> {
+    if envelope_len(inner_frame.len(), cipher_suite) > max_envelope {
+        return Err(CryptoError::envelope_too_large());
+    }
+    seal_raw(
+        inner_frame,
+        method,
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn seal_raw_with(
+    inner_frame: &[u8],
+    method: EnvelopeMethod,
+    counter: u32,
+    auth_hash: [u8; 8],
+    device_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+    version: u8,
+    extra_aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    if version > MAX_ENVELOPE_VERSION {
+        return Err(CryptoError::unsupported_version());
+    }
+
     if inner_frame.len() > MAX_INNER_FRAME_SIZE {
         return Err(CryptoError::inner_frame_too_large());
     }
@@ -106,7 +415,7 @@ pub fn seal_raw(
         return Err(CryptoError::invalid_key_size());
     }
 
-    let flags = Flags::encode(cipher_suite, 0, method)?;
+    let flags = Flags::encode(cipher_suite, version, method)?;
 
     let header = EnvelopeHeader {
         flags,
@@ -114,25 +423,75 @@ pub fn seal_raw(
         auth_hash,
         device_hash,
     };
-    let aad = header.to_bytes();
+    let header_bytes = header.to_bytes();
+    let aad = with_extra_aad(&header_bytes, extra_aad);
 
-    let nonce = construct_nonce(cipher_suite, flags, &device_hash, counter);
+    let nonce = nonce_for_version(version, cipher_suite, flags, &device_hash, counter)?;
 
-    let ciphertext_with_tag =
-        aead_encrypt(cipher_suite, encryption_key, &nonce, &aad, inner_frame)?;
+    // Encrypt straight into the envelope: header, then ciphertext, then tag.
+    let envelope_size = envelope_len(inner_frame.len(), cipher_suite);
+    let mut envelope = Vec::with_capacity(envelope_size);
+    envelope.extend_from_slice(&header_bytes);
+    envelope.extend_from_slice(inner_frame);
+    envelope.resize(envelope_size, 0);
+    let (body, tag) = envelope[HEADER_SIZE..].split_at_mut(inner_frame.len());
+    aead_encrypt_in_place(cipher_suite, encryption_key, &nonce, &aad, body, tag)?;
 
-    // Check envelope size limit.
-    let envelope_size = HEADER_SIZE + ciphertext_with_tag.len();
-    let max_envelope_size = MAX_INNER_FRAME_SIZE + HEADER_SIZE + cipher_suite.tag_size();
-    if envelope_size > max_envelope_size {
-        return Err(CryptoError::envelope_too_large());
+    Ok(envelope)
+}
+
+/// Encrypt several raw inner frames for the same device into version 0 envelopes.
+///
+/// The AEAD cipher is initialized once and reused for every frame. Frame `i`
+/// is sealed with `counters[i]`; `counters` must have at least as many entries
+/// as `frames` (`BufferTooSmall` otherwise). Envelopes are returned in order.
+pub fn seal_batch(
+    frames: &[(EnvelopeMethod, &[u8])],
+    counters: &[u32],
+    auth_hash: [u8; 8],
+    device_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+) -> Result<Vec<Vec<u8>>, CryptoError> {
+    if counters.len() < frames.len() {
+        return Err(CryptoError::buffer_too_small());
     }
 
-    let mut envelope = Vec::with_capacity(envelope_size);
-    envelope.extend_from_slice(&aad);
-    envelope.extend_from_slice(&ciphertext_with_tag);
+    let mut headers = Vec::with_capacity(frames.len());
+    let mut nonces = Vec::with_capacity(frames.len());
+    for (&(method, inner_frame), &counter) in frames.iter().zip(counters) {
+        if inner_frame.len() > MAX_INNER_FRAME_SIZE {
+            return Err(CryptoError::inner_frame_too_large());
+        }
+        let flags = Flags::encode(cipher_suite, 0, method)?;
+        let header = EnvelopeHeader {
+            flags,
+            counter,
+            auth_hash,
+            device_hash,
+        };
+        headers.push(header.to_bytes());
+        nonces.push(construct_nonce(cipher_suite, flags, &device_hash, counter));
+    }
 
-    Ok(envelope)
+    let items: Vec<AeadBatchItem<'_>> = frames
+        .iter()
+        .zip(&headers)
+        .zip(&nonces)
+        .map(|((&(_, inner_frame), aad), nonce)| (nonce.as_slice(), aad.as_slice(), inner_frame))
+        .collect();
+    let ciphertexts = aead_encrypt_batch(cipher_suite, encryption_key, &items)?;
+
+    Ok(headers
+        .iter()
+        .zip(ciphertexts)
+        .map(|(aad, ciphertext_with_tag)| {
+            let mut envelope = Vec::with_capacity(HEADER_SIZE + ciphertext_with_tag.len());
+            envelope.extend_from_slice(aad);
+            envelope.extend_from_slice(&ciphertext_with_tag);
+            envelope
+        })
+        .collect())
 }
 
 /// Decrypt a TagoTiP/S envelope.
@@ -141,15 +500,46 @@ pub fn seal_raw(
 /// The caller uses the method to know how to parse the inner frame:
 ///   - Push/Pull/Ping -> `parse_headless(method, str)`
 ///   - Ack -> `parse_ack_inner(str)`
+///
+/// [`parse_inner`] does that dispatch in one call.
 pub fn open_envelope(
     envelope: &[u8],
     encryption_key: &[u8],
+) -> Result<(EnvelopeHeader, EnvelopeMethod, Vec<u8>), CryptoError> {
+    open_envelope_aad(envelope, encryption_key, &[])
+}
+
+/// Decrypt a TagoTiP/S envelope whose key is chosen from its header.
+///
+/// The header is parsed first and passed to `key_lookup` (typically keyed on
+/// `auth_hash`/`device_hash`); `None` fails with `KeyNotFound` before any
+/// decryption is attempted.
+pub fn open_envelope_with<'k, F>(
+    envelope: &[u8],
+    key_lookup: F,
+) -> Result<(EnvelopeHeader, EnvelopeMethod, Vec<u8>), CryptoError>
+where
+    F: FnOnce(&EnvelopeHeader) -> Option<&'k [u8]>,
+{
+    let header = parse_envelope_header(envelope)?;
+    let key = key_lookup(&header).ok_or_else(CryptoError::key_not_found)?;
+    open_envelope(envelope, key)
+}
+
+/// Decrypt a TagoTiP/S envelope sealed with [`seal_raw_aad`].
+///
+/// `extra_aad` must match the bytes used when sealing, or decryption fails
+/// with `DecryptionFailed`. An empty `extra_aad` behaves like [`open_envelope`].
+pub fn open_envelope_aad(
+    envelope: &[u8],
+    encryption_key: &[u8],
+    extra_aad: &[u8],
 ) -> Result<(EnvelopeHeader, EnvelopeMethod, Vec<u8>), CryptoError> {
     let header = parse_envelope_header(envelope)?;
     let (cipher, version, method) = Flags::decode(header.flags)?;
 
-    if version != 0 {
-        return Err(CryptoError::unsupported_version());
+    if !cipher.is_enabled() {
+        return Err(CryptoError::cipher_not_enabled_for(cipher));
     }
 
     if encryption_key.len() != cipher.key_size() {
@@ -161,14 +551,110 @@ pub fn open_envelope(
         return Err(CryptoError::envelope_too_short());
     }
 
-    let aad = &envelope[..HEADER_SIZE];
-    let nonce = construct_nonce(cipher, header.flags, &header.device_hash, header.counter);
+    let aad = with_extra_aad(&envelope[..HEADER_SIZE], extra_aad);
+    let nonce = nonce_for_version(
+        version,
+        cipher,
+        header.flags,
+        &header.device_hash,
+        header.counter,
+    )?;
 
-    let plaintext = aead_decrypt(cipher, encryption_key, &nonce, aad, ciphertext_with_tag)?;
+    let plaintext = aead_decrypt(cipher, encryption_key, &nonce, &aad, ciphertext_with_tag)?;
 
     Ok((header, method, plaintext))
 }
 
+/// Re-encrypt an envelope under a new key.
+///
+/// The envelope is opened with `old_key` and the same plaintext is sealed with
+/// `new_key`, keeping the cipher suite, version, method, counter, and both
+/// hashes. `new_key` must therefore fit the envelope's cipher suite.
+pub fn rekey_envelope(
+    envelope: &[u8],
+    old_key: &[u8],
+    new_key: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let (header, method, plaintext) = open_envelope(envelope, old_key)?;
+    let (cipher, version, _) = Flags::decode(header.flags)?;
+    seal_raw_versioned(
+        &plaintext,
+        method,
+        header.counter,
+        header.auth_hash,
+        header.device_hash,
+        new_key,
+        cipher,
+        version,
+    )
+}
+
+/// Parse a decrypted inner frame according to its envelope method.
+///
+/// Push/Pull/Ping go through `parse_headless`; Ack goes through `parse_ack_inner`.
+pub fn parse_inner(method: EnvelopeMethod, plaintext: &str) -> Result<InnerFrame<'_>, ParseError> {
+    match method.to_codec_method() {
+        Some(m) => parse::parse_headless(m, plaintext).map(InnerFrame::Headless),
+        None => parse::parse_ack_inner(plaintext).map(InnerFrame::Ack),
+    }
+}
+
+/// Build a headless inner frame and seal it with the given device hash.
+#[allow(clippy::too_many_arguments)]
+fn seal_headless(
+    method: Method,
+    frame: &HeadlessFrame<'_>,
+    counter: u32,
+    auth_hash: [u8; 8],
+    device_hash: [u8; 8],
+    encryption_key: &[u8],
+    cipher_suite: CipherSuite,
+    version: u8,
+) -> Result<Vec<u8>, CryptoError> {
+    // Build the headless inner frame into bytes.
+    let mut buf = [0u8; MAX_INNER_FRAME_SIZE];
+    let n = build::build_headless(method, frame, &mut buf)
+        .map_err(|_| CryptoError::new(crate::error::CryptoErrorKind::InnerFrameTooLarge))?;
+
+    seal_raw_versioned(
+        &buf[..n],
+        EnvelopeMethod::from(method),
+        counter,
+        auth_hash,
+        device_hash,
+        encryption_key,
+        cipher_suite,
+        version,
+    )
+}
+
+/// Concatenate the header bytes and any extra associated data, borrowing the
+/// header as-is when there is none.
+fn with_extra_aad<'a>(header: &'a [u8], extra_aad: &[u8]) -> Cow<'a, [u8]> {
+    if extra_aad.is_empty() {
+        return Cow::Borrowed(header);
+    }
+    let mut aad = Vec::with_capacity(header.len() + extra_aad.len());
+    aad.extend_from_slice(header);
+    aad.extend_from_slice(extra_aad);
+    Cow::Owned(aad)
+}
+
+/// Build the nonce for an envelope version, rejecting unknown versions.
+fn nonce_for_version(
+    version: u8,
+    suite: CipherSuite,
+    flags: u8,
+    device_hash: &[u8; 8],
+    counter: u32,
+) -> Result<Vec<u8>, CryptoError> {
+    match version {
+        0 => Ok(construct_nonce(suite, flags, device_hash, counter)),
+        1 => Ok(construct_nonce_v1(suite, flags, device_hash, counter)),
+        _ => Err(CryptoError::unsupported_version()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,6 +668,35 @@ mod tests {
         assert!(!is_envelope(&[])); // Empty
     }
 
+    #[test]
+    fn test_classify_frame() {
+        assert_eq!(classify_frame(b"ACK|OK|3"), FrameClass::Ack);
+        assert_eq!(
+            classify_frame(b"PING|4deedd7bab8817ec|sensor-01"),
+            FrameClass::Uplink
+        );
+        assert_eq!(
+            classify_frame(b"PUSH|4deedd7bab8817ec|sensor-01|[temp:=32]"),
+            FrameClass::Uplink
+        );
+        assert_eq!(classify_frame(b"PUSHY|x"), FrameClass::Unknown);
+        assert_eq!(classify_frame(b"hello"), FrameClass::Unknown);
+        assert_eq!(classify_frame(b""), FrameClass::Unknown);
+
+        // Flags 0x02 = AES-128-CCM, v0, PING; classification never decrypts.
+        let mut envelope = [0u8; 30];
+        envelope[0] = 0x02;
+        assert_eq!(classify_frame(&envelope), FrameClass::Envelope);
+        envelope[0] = 0xE0; // cipher 7 does not exist
+        assert_eq!(classify_frame(&envelope), FrameClass::Unknown);
+
+        // Long text whose first byte is not a valid flags byte ('G' = method 7).
+        assert_eq!(
+            classify_frame(b"GET /status HTTP/1.1\r\nHost: x"),
+            FrameClass::Unknown
+        );
+    }
+
     #[test]
     #[cfg(feature = "aes-128-ccm")]
     fn test_seal_open_uplink_push() {
@@ -1,5 +1,7 @@
 use core::fmt;
 
+use crate::types::CipherSuite;
+
 /// Specific kind of crypto error.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CryptoErrorKind {
@@ -25,18 +27,28 @@ pub enum CryptoErrorKind {
     BufferTooSmall,
     /// The Flags byte value 0x41 is reserved for disambiguation.
     ReservedFlagsValue,
+    /// A counter was not greater than the last one sealed for the same device and suite.
+    CounterReused,
+    /// A device or auth hash was not exactly 8 bytes.
+    InvalidHashSize,
+    /// The key lookup found no key for the envelope's header.
+    KeyNotFound,
+    /// The frame's device serial failed validation.
+    InvalidSerial,
 }
 
 /// Error returned by crypto envelope operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CryptoError {
     pub kind: CryptoErrorKind,
+    /// Cipher suite the error refers to, when known (set for `CipherNotEnabled`).
+    pub suite: Option<CipherSuite>,
 }
 
 impl CryptoError {
     #[must_use]
     pub fn new(kind: CryptoErrorKind) -> Self {
-        Self { kind }
+        Self { kind, suite: None }
     }
 
     #[must_use]
@@ -64,6 +76,14 @@ impl CryptoError {
         Self::new(CryptoErrorKind::CipherNotEnabled)
     }
 
+    #[must_use]
+    pub fn cipher_not_enabled_for(suite: CipherSuite) -> Self {
+        Self {
+            kind: CryptoErrorKind::CipherNotEnabled,
+            suite: Some(suite),
+        }
+    }
+
     #[must_use]
     pub fn decryption_failed() -> Self {
         Self::new(CryptoErrorKind::DecryptionFailed)
@@ -93,6 +113,26 @@ impl CryptoError {
     pub fn reserved_flags_value() -> Self {
         Self::new(CryptoErrorKind::ReservedFlagsValue)
     }
+
+    #[must_use]
+    pub fn counter_reused() -> Self {
+        Self::new(CryptoErrorKind::CounterReused)
+    }
+
+    #[must_use]
+    pub fn invalid_hash_size() -> Self {
+        Self::new(CryptoErrorKind::InvalidHashSize)
+    }
+
+    #[must_use]
+    pub fn key_not_found() -> Self {
+        Self::new(CryptoErrorKind::KeyNotFound)
+    }
+
+    #[must_use]
+    pub fn invalid_serial() -> Self {
+        Self::new(CryptoErrorKind::InvalidSerial)
+    }
 }
 
 impl fmt::Display for CryptoError {
@@ -109,8 +149,16 @@ impl fmt::Display for CryptoError {
             CryptoErrorKind::EnvelopeTooLarge => "envelope exceeds maximum size",
             CryptoErrorKind::BufferTooSmall => "output buffer too small",
             CryptoErrorKind::ReservedFlagsValue => "flags byte 0x41 is reserved",
+            CryptoErrorKind::CounterReused => "counter not greater than the last one sealed",
+            CryptoErrorKind::InvalidHashSize => "hash must be 8 bytes",
+            CryptoErrorKind::KeyNotFound => "no key found for envelope",
+            CryptoErrorKind::InvalidSerial => "invalid device serial",
         };
-        f.write_str(desc)
+        f.write_str(desc)?;
+        if let Some(suite) = self.suite {
+            write!(f, ": {}", suite.feature_name())?;
+        }
+        Ok(())
     }
 }
 
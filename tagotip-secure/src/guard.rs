@@ -0,0 +1,83 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::envelope::seal_raw;
+use crate::error::CryptoError;
+use crate::types::{CipherSuite, EnvelopeMethod};
+
+/// Nonce-reuse guard for sealing.
+///
+/// Remembers the last counter sealed per (device hash, cipher suite) and
+/// rejects any counter that is not strictly greater with
+/// `CryptoError::CounterReused`. `seal_raw` itself stays stateless; wrap it
+/// with a guard where counters might repeat (e.g. firmware that resets).
+#[derive(Debug, Clone, Default)]
+pub struct SealGuard {
+    last: BTreeMap<([u8; 8], u8), u32>,
+}
+
+impl SealGuard {
+    /// Create an empty guard.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check `counter` against the last one recorded and record it if it is newer.
+    pub fn check_and_record(
+        &mut self,
+        device_hash: [u8; 8],
+        cipher_suite: CipherSuite,
+        counter: u32,
+    ) -> Result<(), CryptoError> {
+        self.ensure_newer(device_hash, cipher_suite, counter)?;
+        self.last.insert((device_hash, cipher_suite.id()), counter);
+        Ok(())
+    }
+
+    /// Last counter recorded for a device and suite, if any.
+    #[must_use]
+    pub fn last_counter(&self, device_hash: [u8; 8], cipher_suite: CipherSuite) -> Option<u32> {
+        self.last.get(&(device_hash, cipher_suite.id())).copied()
+    }
+
+    /// [`seal_raw`] guarded against counter reuse.
+    ///
+    /// The counter is only recorded once sealing succeeds.
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal_raw(
+        &mut self,
+        inner_frame: &[u8],
+        method: EnvelopeMethod,
+        counter: u32,
+        auth_hash: [u8; 8],
+        device_hash: [u8; 8],
+        encryption_key: &[u8],
+        cipher_suite: CipherSuite,
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.ensure_newer(device_hash, cipher_suite, counter)?;
+        let envelope = seal_raw(
+            inner_frame,
+            method,
+            counter,
+            auth_hash,
+            device_hash,
+            encryption_key,
+            cipher_suite,
+        )?;
+        self.last.insert((device_hash, cipher_suite.id()), counter);
+        Ok(envelope)
+    }
+
+    fn ensure_newer(
+        &self,
+        device_hash: [u8; 8],
+        cipher_suite: CipherSuite,
+        counter: u32,
+    ) -> Result<(), CryptoError> {
+        match self.last_counter(device_hash, cipher_suite) {
+            Some(last) if counter <= last => Err(CryptoError::counter_reused()),
+            _ => Ok(()),
+        }
+    }
+}
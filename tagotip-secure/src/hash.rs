@@ -3,8 +3,11 @@ use alloc::vec::Vec;
 
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tagotip_codec::Serial;
 
 use crate::consts::AUTH_HASH_SIZE;
+use crate::types::EnvelopeHeader;
 
 /// Derive the Authorization Hash from an authorization token.
 ///
@@ -20,6 +23,15 @@ pub fn derive_auth_hash(token: &str) -> [u8; AUTH_HASH_SIZE] {
     hash
 }
 
+/// Whether `token` derives to the auth hash in `header`.
+///
+/// This is the check a server runs to validate a claimed token against an
+/// envelope; the comparison is constant-time.
+#[must_use]
+pub fn verify_auth_hash(token: &str, header: &EnvelopeHeader) -> bool {
+    derive_auth_hash(token).ct_eq(&header.auth_hash).into()
+}
+
 /// Derive the Device Hash from a device serial number.
 ///
 /// Computes SHA-256 of the serial (UTF-8 encoded) and returns the first 8 bytes.
@@ -31,6 +43,30 @@ pub fn derive_device_hash(serial: &str) -> [u8; AUTH_HASH_SIZE] {
     hash
 }
 
+/// [`derive_device_hash`] for a serial that has already passed validation.
+#[must_use]
+pub fn derive_device_hash_for(serial: Serial<'_>) -> [u8; AUTH_HASH_SIZE] {
+    derive_device_hash(serial.as_str())
+}
+
+/// Derive both envelope identity hashes in one call.
+///
+/// Returns `(auth_hash, device_hash)`, as [`derive_auth_hash`] and
+/// [`derive_device_hash`] would for `token` and `serial`.
+#[must_use]
+pub fn derive_identity(token: &str, serial: &str) -> ([u8; AUTH_HASH_SIZE], [u8; AUTH_HASH_SIZE]) {
+    (derive_auth_hash(token), derive_device_hash(serial))
+}
+
+/// [`derive_identity`] for a serial that has already passed validation.
+#[must_use]
+pub fn derive_identity_for(
+    token: &str,
+    serial: Serial<'_>,
+) -> ([u8; AUTH_HASH_SIZE], [u8; AUTH_HASH_SIZE]) {
+    derive_identity(token, serial.as_str())
+}
+
 /// Derive an encryption key from an authorization token and device serial
 /// using HMAC-SHA256.
 ///
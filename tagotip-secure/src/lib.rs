@@ -10,14 +10,24 @@ pub mod cipher;
 pub mod consts;
 pub mod envelope;
 pub mod error;
+pub mod guard;
 pub mod hash;
 pub mod nonce;
 pub mod types;
 
+pub use consts::{AUTH_HASH_SIZE, COUNTER_SIZE, DEVICE_HASH_SIZE, HEADER_SIZE};
 pub use error::{CryptoError, CryptoErrorKind};
-pub use types::{CipherSuite, EnvelopeHeader, EnvelopeMethod, Flags};
+pub use guard::SealGuard;
+pub use types::{CipherSuite, EnvelopeHeader, EnvelopeMethod, Flags, FrameClass, InnerFrame};
 
 pub use envelope::{
-    is_envelope, open_envelope, parse_envelope_header, seal_downlink, seal_raw, seal_uplink,
+    classify_frame, envelope_len, envelope_overhead, inspect_envelope, is_envelope,
+    is_valid_envelope, open_envelope, open_envelope_aad, open_envelope_with, parse_envelope_header,
+    parse_inner, rekey_envelope, seal_batch, seal_downlink, seal_raw, seal_raw_aad, seal_raw_mtu,
+    seal_raw_versioned, seal_uplink, seal_uplink_bytes, seal_uplink_checked, seal_uplink_versioned,
+    seal_uplink_with_device_hash,
+};
+pub use hash::{
+    bytes_to_hex, derive_auth_hash, derive_device_hash, derive_device_hash_for, derive_identity,
+    derive_identity_for, derive_key, hex_to_bytes, verify_auth_hash,
 };
-pub use hash::{bytes_to_hex, derive_auth_hash, derive_device_hash, derive_key, hex_to_bytes};
@@ -1,5 +1,6 @@
 use alloc::vec::Vec;
 
+use crate::error::CryptoError;
 use crate::types::CipherSuite;
 
 /// Construct the AEAD nonce from envelope fields.
@@ -27,6 +28,46 @@ pub fn construct_nonce(
     nonce
 }
 
+/// Like [`construct_nonce`], but takes the device hash as a slice and fails
+/// with `InvalidHashSize` unless it is exactly 8 bytes.
+pub fn try_construct_nonce(
+    suite: CipherSuite,
+    flags: u8,
+    device_hash: &[u8],
+    counter: u32,
+) -> Result<Vec<u8>, CryptoError> {
+    let device_hash: &[u8; 8] = device_hash
+        .try_into()
+        .map_err(|_| CryptoError::invalid_hash_size())?;
+    Ok(construct_nonce(suite, flags, device_hash, counter))
+}
+
+/// Construct the AEAD nonce for a version 1 envelope.
+///
+/// v1 drops the zero padding and uses more of the device hash:
+/// CCM (13 bytes): `[Flags:1] [DeviceHash:8] [Counter:4]`
+/// GCM/ChaCha (12 bytes): `[Flags:1] [DeviceHash[:7]:7] [Counter:4]`
+///
+/// The layout and a known-answer envelope are in the crate README and
+/// `tests/spec_vector.rs`.
+#[must_use]
+pub fn construct_nonce_v1(
+    suite: CipherSuite,
+    flags: u8,
+    device_hash: &[u8; 8],
+    counter: u32,
+) -> Vec<u8> {
+    let nonce_size = suite.nonce_size();
+    let mut nonce = alloc::vec![0u8; nonce_size];
+
+    nonce[0] = flags;
+    let dh_len = nonce_size - 5;
+    nonce[1..=dh_len].copy_from_slice(&device_hash[..dh_len]);
+    nonce[nonce_size - 4..].copy_from_slice(&counter.to_be_bytes());
+
+    nonce
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,6 +86,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_construct_nonce_checks_length() {
+        let device_hash = [0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f];
+        let nonce = try_construct_nonce(CipherSuite::Aes128Ccm, 0x00, &device_hash, 42).unwrap();
+        assert_eq!(
+            nonce,
+            construct_nonce(CipherSuite::Aes128Ccm, 0x00, &device_hash, 42)
+        );
+
+        for bad in [&device_hash[..7], &[0u8; 9][..], &[]] {
+            let err = try_construct_nonce(CipherSuite::Aes128Ccm, 0x00, bad, 42).unwrap_err();
+            assert_eq!(err.kind, crate::error::CryptoErrorKind::InvalidHashSize);
+        }
+    }
+
     #[test]
     fn test_nonce_gcm() {
         let device_hash: [u8; 8] = [0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f];
@@ -71,4 +127,28 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_nonce_v1_ccm() {
+        let device_hash: [u8; 8] = [0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f];
+        let nonce = construct_nonce_v1(CipherSuite::Aes128Ccm, 0x08, &device_hash, 42);
+        assert_eq!(
+            nonce.as_slice(),
+            &[
+                0x08, 0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f, 0x00, 0x00, 0x00, 0x2a
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nonce_v1_gcm() {
+        let device_hash: [u8; 8] = [0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f];
+        let nonce = construct_nonce_v1(CipherSuite::Aes128Gcm, 0x28, &device_hash, 1);
+        assert_eq!(
+            nonce.as_slice(),
+            &[
+                0x28, 0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x00, 0x00, 0x00, 0x01
+            ]
+        );
+    }
 }
@@ -5,7 +5,7 @@ use crate::consts::{
     RESERVED_FLAGS_VALUE,
 };
 use crate::error::CryptoError;
-use tagotip_codec::Method;
+use tagotip_codec::{AckFrame, HeadlessFrame, Method};
 
 /// AEAD cipher suite.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -68,6 +68,18 @@ impl CipherSuite {
         }
     }
 
+    /// Name of the Cargo feature that compiles this cipher suite in.
+    #[must_use]
+    pub fn feature_name(self) -> &'static str {
+        match self {
+            Self::Aes128Ccm => "aes-128-ccm",
+            Self::Aes128Gcm => "aes-128-gcm",
+            Self::Aes256Ccm => "aes-256-ccm",
+            Self::Aes256Gcm => "aes-256-gcm",
+            Self::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+
     /// Check if the feature flag for this cipher suite is enabled.
     #[must_use]
     pub fn is_enabled(self) -> bool {
@@ -124,6 +136,29 @@ impl EnvelopeMethod {
     }
 }
 
+/// A decrypted inner frame, as returned by [`parse_inner`](crate::envelope::parse_inner).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum InnerFrame<'a> {
+    /// PUSH, PULL or PING inner frame.
+    Headless(HeadlessFrame<'a>),
+    /// ACK inner frame (no `ACK|` prefix on the wire).
+    Ack(AckFrame<'a>),
+}
+
+/// Kind of a raw message, as determined by [`classify_frame`](crate::envelope::classify_frame).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameClass {
+    /// Plaintext uplink (`PUSH|`, `PULL|`, `PING|`).
+    Uplink,
+    /// Plaintext ACK (`ACK|...`).
+    Ack,
+    /// Binary TagoTiP/S envelope with a valid header.
+    Envelope,
+    /// None of the above.
+    Unknown,
+}
+
 impl From<Method> for EnvelopeMethod {
     fn from(m: Method) -> Self {
         match m {
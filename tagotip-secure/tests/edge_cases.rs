@@ -1,8 +1,9 @@
 use tagotip_codec::types::{HeadlessFrame, Method};
 use tagotip_secure::error::CryptoErrorKind;
 use tagotip_secure::{
-    CipherSuite, EnvelopeMethod, Flags, derive_auth_hash, is_envelope, open_envelope,
-    parse_envelope_header, seal_raw, seal_uplink,
+    CipherSuite, EnvelopeMethod, Flags, HEADER_SIZE, SealGuard, derive_auth_hash, is_envelope,
+    is_valid_envelope, open_envelope, parse_envelope_header, seal_raw, seal_uplink,
+    seal_uplink_checked,
 };
 
 const TOKEN: &str = "ate2bd319014b24e0a8aca9f00aea4c0d0";
@@ -84,7 +85,7 @@ fn test_tampered_ciphertext() {
     .unwrap();
 
     // Tamper with ciphertext
-    let ct_start = 21;
+    let ct_start = HEADER_SIZE;
     envelope[ct_start] ^= 0xFF;
 
     let result = open_envelope(&envelope, &KEY_16);
@@ -186,6 +187,27 @@ fn test_reserved_flags_in_envelope() {
     );
 }
 
+// ---------------------------------------------------------------------------
+// Cipher suites not compiled in
+// ---------------------------------------------------------------------------
+
+#[cfg(not(feature = "chacha20-poly1305"))]
+#[test]
+fn test_open_disabled_suite_fails_early() {
+    let flags = Flags::encode(CipherSuite::ChaCha20Poly1305, 0, EnvelopeMethod::Push).unwrap();
+    let mut envelope = [0u8; HEADER_SIZE + 16 + 4];
+    envelope[0] = flags;
+
+    // The key size check is skipped: the suite is rejected before it.
+    let err = open_envelope(&envelope, &KEY_16).unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::CipherNotEnabled);
+    assert_eq!(err.suite, Some(CipherSuite::ChaCha20Poly1305));
+    assert_eq!(
+        err.to_string(),
+        "cipher suite not enabled (missing feature flag): chacha20-poly1305"
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Invalid key sizes
 // ---------------------------------------------------------------------------
@@ -232,13 +254,13 @@ fn test_invalid_key_size_too_long() {
 
 #[test]
 fn test_unsupported_version_decode() {
-    // Version 1 is not currently supported
-    // cipher=0 (bits 7-5 = 000), version=1 (bits 4-3 = 01), method=0 (bits 2-0 = 000)
-    // = 0b0000_1000 = 0x08
+    // Version 2 is not currently supported
+    // cipher=0 (bits 7-5 = 000), version=2 (bits 4-3 = 10), method=0 (bits 2-0 = 000)
+    // = 0b0001_0000 = 0x10
     let result = open_envelope(
         &{
             let mut env = [0u8; 30];
-            env[0] = 0x08; // version 1
+            env[0] = 0x10; // version 2
             env
         },
         &KEY_16,
@@ -287,6 +309,22 @@ fn test_is_envelope_various() {
     assert!(!is_envelope(&[])); // Empty
 }
 
+#[test]
+fn test_is_valid_envelope_requires_header() {
+    assert!(is_envelope(&[0x00]));
+    assert!(!is_valid_envelope(&[0x00]));
+
+    let mut data = [0u8; HEADER_SIZE];
+    assert!(is_valid_envelope(&data));
+    assert!(!is_valid_envelope(&data[..HEADER_SIZE - 1]));
+    data[0] = 0xE0; // cipher id 7
+    assert!(!is_valid_envelope(&data));
+    data[0] = 0x04; // method id 4
+    assert!(!is_valid_envelope(&data));
+    data[0] = 0x41; // reserved
+    assert!(!is_valid_envelope(&data));
+}
+
 // ---------------------------------------------------------------------------
 // Flags encode/decode round-trip
 // ---------------------------------------------------------------------------
@@ -392,3 +430,133 @@ fn test_envelope_method_conversions() {
     );
     assert_eq!(EnvelopeMethod::Ack.to_codec_method(), None);
 }
+
+// ---------------------------------------------------------------------------
+// Seal guard (nonce reuse)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_seal_guard_rejects_reused_counter() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let device_hash = [0xab; 8];
+    let mut guard = SealGuard::new();
+
+    let seal = |guard: &mut SealGuard, counter: u32| {
+        guard.seal_raw(
+            b"sensor-01",
+            EnvelopeMethod::Ping,
+            counter,
+            auth_hash,
+            device_hash,
+            &KEY_16,
+            CipherSuite::Aes128Ccm,
+        )
+    };
+
+    assert!(seal(&mut guard, 5).is_ok());
+    assert_eq!(
+        seal(&mut guard, 5).unwrap_err().kind,
+        CryptoErrorKind::CounterReused
+    );
+    assert_eq!(
+        seal(&mut guard, 4).unwrap_err().kind,
+        CryptoErrorKind::CounterReused
+    );
+    assert!(seal(&mut guard, 6).is_ok());
+    assert_eq!(
+        guard.last_counter(device_hash, CipherSuite::Aes128Ccm),
+        Some(6)
+    );
+}
+
+#[test]
+fn test_seal_guard_tracks_devices_and_suites_separately() {
+    let mut guard = SealGuard::new();
+    guard
+        .check_and_record([1; 8], CipherSuite::Aes128Ccm, 10)
+        .unwrap();
+    guard
+        .check_and_record([2; 8], CipherSuite::Aes128Ccm, 10)
+        .unwrap();
+    guard
+        .check_and_record([1; 8], CipherSuite::Aes128Gcm, 10)
+        .unwrap();
+    assert_eq!(
+        guard
+            .check_and_record([1; 8], CipherSuite::Aes128Ccm, 10)
+            .unwrap_err()
+            .kind,
+        CryptoErrorKind::CounterReused
+    );
+}
+
+#[test]
+fn test_seal_guard_does_not_record_failed_seal() {
+    let mut guard = SealGuard::new();
+    let result = guard.seal_raw(
+        b"x",
+        EnvelopeMethod::Ping,
+        1,
+        [0; 8],
+        [0; 8],
+        &[0u8; 8], // wrong key size
+        CipherSuite::Aes128Ccm,
+    );
+    assert_eq!(result.unwrap_err().kind, CryptoErrorKind::InvalidKeySize);
+    assert_eq!(guard.last_counter([0; 8], CipherSuite::Aes128Ccm), None);
+}
+
+// ---------------------------------------------------------------------------
+// Serial validation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_checked_seal_rejects_invalid_serial() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let frame = HeadlessFrame {
+        serial: "my.device",
+        push_body: None,
+        pull_body: None,
+    };
+
+    let err = seal_uplink_checked(
+        Method::Ping,
+        &frame,
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::InvalidSerial);
+}
+
+#[test]
+fn test_checked_seal_matches_unchecked() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let frame = HeadlessFrame {
+        serial: "sensor-01",
+        push_body: None,
+        pull_body: None,
+    };
+
+    let checked = seal_uplink_checked(
+        Method::Ping,
+        &frame,
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    let unchecked = seal_uplink(
+        Method::Ping,
+        &frame,
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    assert_eq!(checked, unchecked);
+}
@@ -0,0 +1,75 @@
+//! Fuzz-style robustness tests: malformed envelopes must never panic.
+
+use tagotip_codec::testutil::XorShift;
+use tagotip_secure::{
+    CipherSuite, EnvelopeMethod, Flags, derive_auth_hash, derive_device_hash, is_envelope,
+    open_envelope, parse_envelope_header, seal_raw,
+};
+
+const KEY_16: [u8; 16] = [
+    0xfe, 0x09, 0xda, 0x81, 0xbc, 0x44, 0x00, 0xee, 0x12, 0xab, 0x56, 0xcd, 0x78, 0xef, 0x90, 0x12,
+];
+const KEY_32: [u8; 32] = [0x5a; 32];
+
+fn exercise(data: &[u8]) {
+    let _ = is_envelope(data);
+    let _ = parse_envelope_header(data);
+    let _ = open_envelope(data, &KEY_16);
+    let _ = open_envelope(data, &KEY_32);
+    let _ = open_envelope(data, &[]);
+    if let Some(&flags) = data.first() {
+        let _ = Flags::decode(flags);
+    }
+}
+
+#[test]
+fn random_envelopes_never_panic() {
+    let mut rng = XorShift::new(0xD1B5_4A32_D192_ED03);
+    for _ in 0..5_000 {
+        let len = rng.below(64);
+        let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        exercise(&data);
+    }
+}
+
+#[test]
+fn every_flags_byte_never_panics() {
+    for flags in 0..=u8::MAX {
+        for len in [0, 1, 20, 21, 22, 28, 29, 40] {
+            let mut data = vec![0u8; len];
+            if let Some(first) = data.first_mut() {
+                *first = flags;
+            }
+            exercise(&data);
+        }
+    }
+}
+
+#[test]
+fn truncated_and_mutated_envelopes_fail() {
+    let envelope = seal_raw(
+        b"sensor-01|[temp:=32]",
+        EnvelopeMethod::Push,
+        42,
+        derive_auth_hash("ate2bd319014b24e0a8aca9f00aea4c0d0"),
+        derive_device_hash("sensor-01"),
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+
+    for len in 0..envelope.len() {
+        assert!(
+            open_envelope(&envelope[..len], &KEY_16).is_err(),
+            "len {len}"
+        );
+    }
+
+    let mut rng = XorShift::new(0x2545_F491_4F6C_DD1D);
+    for _ in 0..2_000 {
+        let mut mutated = envelope.clone();
+        let idx = rng.below(mutated.len());
+        mutated[idx] ^= (rng.below(255) + 1) as u8;
+        assert!(open_envelope(&mutated, &KEY_16).is_err());
+    }
+}
@@ -1,11 +1,14 @@
 use tagotip_codec::inline_vec::InlineVec;
 use tagotip_codec::types::{
-    AckDetail, AckFrame, AckStatus, HeadlessFrame, Method, Operator, PullBody, PushBody,
-    StructuredBody, Value, Variable,
+    AckDetail, AckFrame, AckStatus, HeadlessFrame, Method, Operator, PassthroughBody,
+    PassthroughEncoding, PullBody, PushBody, StructuredBody, Value, Variable,
 };
 use tagotip_secure::{
-    CipherSuite, EnvelopeMethod, derive_auth_hash, derive_device_hash, open_envelope,
-    seal_downlink, seal_uplink,
+    CipherSuite, CryptoErrorKind, EnvelopeHeader, EnvelopeMethod, Flags, InnerFrame,
+    derive_auth_hash, derive_device_hash, envelope_len, envelope_overhead, open_envelope,
+    open_envelope_aad, open_envelope_with, parse_inner, rekey_envelope, seal_batch, seal_downlink,
+    seal_raw, seal_raw_aad, seal_raw_mtu, seal_uplink, seal_uplink_bytes, seal_uplink_versioned,
+    seal_uplink_with_device_hash,
 };
 
 const TOKEN: &str = "ate2bd319014b24e0a8aca9f00aea4c0d0";
@@ -52,6 +55,7 @@ fn make_push_frame() -> HeadlessFrame<'static> {
             body_meta: None,
             variables,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     }
@@ -244,6 +248,397 @@ fn test_aes128_ccm_ack_err() {
     assert_eq!(parsed.status, AckStatus::Err);
 }
 
+// ---------------------------------------------------------------------------
+// Inner frame dispatch
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_parse_inner_push_and_ack() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let device_hash = derive_device_hash(SERIAL);
+
+    let frame = make_push_frame();
+    let envelope = seal_uplink(
+        Method::Push,
+        &frame,
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    let (_, method, plaintext) = open_envelope(&envelope, &KEY_16).unwrap();
+    let inner = parse_inner(method, core::str::from_utf8(&plaintext).unwrap()).unwrap();
+    assert_eq!(inner, InnerFrame::Headless(frame));
+
+    let ack = AckFrame {
+        seq: None,
+        status: AckStatus::Ok,
+        detail: Some(AckDetail::Count(2)),
+    };
+    let envelope = seal_downlink(
+        &ack,
+        2,
+        auth_hash,
+        device_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    let (_, method, plaintext) = open_envelope(&envelope, &KEY_16).unwrap();
+    let inner = parse_inner(method, core::str::from_utf8(&plaintext).unwrap()).unwrap();
+    assert_eq!(inner, InnerFrame::Ack(ack));
+}
+
+#[test]
+fn test_parse_inner_rejects_mismatched_method() {
+    assert!(parse_inner(EnvelopeMethod::Ack, "sensor-01|[temp:=1]").is_err());
+    assert!(parse_inner(EnvelopeMethod::Push, "OK|2").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Envelope version 1
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_v1_round_trip() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let frame = make_push_frame();
+
+    let envelope = seal_uplink_versioned(
+        Method::Push,
+        &frame,
+        7,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+        1,
+    )
+    .unwrap();
+    let (_, version, _) = Flags::decode(envelope[0]).unwrap();
+    assert_eq!(version, 1);
+
+    let (header, method, plaintext) = open_envelope(&envelope, &KEY_16).unwrap();
+    assert_eq!(header.counter, 7);
+    assert_eq!(method, EnvelopeMethod::Push);
+    let parsed = tagotip_codec::parse::parse_headless(
+        Method::Push,
+        core::str::from_utf8(&plaintext).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(parsed, frame);
+}
+
+#[test]
+fn test_v0_and_v1_ciphertexts_differ() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let frame = make_ping_frame();
+    let v0 = seal_uplink(
+        Method::Ping,
+        &frame,
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    let v1 = seal_uplink_versioned(
+        Method::Ping,
+        &frame,
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+        1,
+    )
+    .unwrap();
+    assert_eq!(v0.len(), v1.len());
+    assert_ne!(v0[21..], v1[21..]);
+}
+
+#[test]
+fn test_seal_unsupported_version() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let result = seal_uplink_versioned(
+        Method::Ping,
+        &make_ping_frame(),
+        1,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+        2,
+    );
+    assert_eq!(
+        result.unwrap_err().kind,
+        CryptoErrorKind::UnsupportedVersion
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Batch sealing
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_seal_batch_100_frames() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let device_hash = derive_device_hash(SERIAL);
+
+    let inners: Vec<String> = (0..100).map(|i| format!("{SERIAL}|[v:={i}]")).collect();
+    let frames: Vec<(EnvelopeMethod, &[u8])> = inners
+        .iter()
+        .map(|s| (EnvelopeMethod::Push, s.as_bytes()))
+        .collect();
+    let counters: Vec<u32> = (1000..1100).collect();
+
+    let envelopes = seal_batch(
+        &frames,
+        &counters,
+        auth_hash,
+        device_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    assert_eq!(envelopes.len(), 100);
+
+    for (i, envelope) in envelopes.iter().enumerate() {
+        let (header, method, plaintext) = open_envelope(envelope, &KEY_16).unwrap();
+        assert_eq!(header.counter, counters[i]);
+        assert_eq!(method, EnvelopeMethod::Push);
+        assert_eq!(plaintext, inners[i].as_bytes());
+    }
+}
+
+#[test]
+fn test_seal_batch_matches_seal_raw() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let device_hash = derive_device_hash(SERIAL);
+    let frames: [(EnvelopeMethod, &[u8]); 2] = [
+        (EnvelopeMethod::Ping, b"sensor-01"),
+        (EnvelopeMethod::Ack, b"OK|3"),
+    ];
+
+    let batch = seal_batch(
+        &frames,
+        &[5, 6],
+        auth_hash,
+        device_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    for (i, (method, inner)) in frames.iter().enumerate() {
+        let single = tagotip_secure::seal_raw(
+            inner,
+            *method,
+            5 + u32::try_from(i).unwrap(),
+            auth_hash,
+            device_hash,
+            &KEY_16,
+            CipherSuite::Aes128Ccm,
+        )
+        .unwrap();
+        assert_eq!(batch[i], single);
+    }
+}
+
+#[test]
+fn test_seal_uplink_bytes_matches_structured_path() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let passthrough = HeadlessFrame {
+        serial: SERIAL,
+        push_body: Some(PushBody::Passthrough(PassthroughBody {
+            encoding: PassthroughEncoding::Hex,
+            data: "DEADBEEF",
+        })),
+        pull_body: None,
+    };
+    let check = |method: Method, frame: &HeadlessFrame<'_>, body: &[u8]| {
+        let structured =
+            seal_uplink(method, frame, 9, auth_hash, &KEY_16, CipherSuite::Aes128Ccm).unwrap();
+        let raw = seal_uplink_bytes(
+            method,
+            SERIAL,
+            body,
+            9,
+            auth_hash,
+            &KEY_16,
+            CipherSuite::Aes128Ccm,
+        )
+        .unwrap();
+        assert_eq!(raw, structured);
+    };
+    check(Method::Push, &passthrough, b">xDEADBEEF");
+    check(Method::Ping, &make_ping_frame(), b"");
+}
+
+#[test]
+fn test_rekey_envelope_preserves_header() {
+    let new_key: [u8; 16] = [0x42; 16];
+    let frame = make_push_frame();
+    let auth_hash = derive_auth_hash(TOKEN);
+    let sealed = seal_uplink(
+        Method::Push,
+        &frame,
+        77,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+
+    let rekeyed = rekey_envelope(&sealed, &KEY_16, &new_key).unwrap();
+    assert_eq!(rekeyed[..21], sealed[..21]);
+    assert_ne!(rekeyed, sealed);
+
+    let (header, method, plaintext) = open_envelope(&rekeyed, &new_key).unwrap();
+    let (_, _, original) = open_envelope(&sealed, &KEY_16).unwrap();
+    assert_eq!(header.counter, 77);
+    assert_eq!(header.auth_hash, auth_hash);
+    assert_eq!(header.device_hash, derive_device_hash(SERIAL));
+    assert_eq!(method, EnvelopeMethod::Push);
+    assert_eq!(plaintext, original);
+
+    let err = open_envelope(&rekeyed, &KEY_16).unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::DecryptionFailed);
+}
+
+#[test]
+fn test_seal_uplink_with_device_hash_round_trip() {
+    let tenant_hash: [u8; 8] = *b"tenant42";
+    let frame = make_push_frame();
+    let auth_hash = derive_auth_hash(TOKEN);
+    let envelope = seal_uplink_with_device_hash(
+        Method::Push,
+        &frame,
+        tenant_hash,
+        5,
+        auth_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+
+    let (header, method, plaintext) = open_envelope(&envelope, &KEY_16).unwrap();
+    assert_eq!(header.device_hash, tenant_hash);
+    assert_ne!(header.device_hash, derive_device_hash(SERIAL));
+    assert_eq!(header.counter, 5);
+    let inner = core::str::from_utf8(&plaintext).unwrap();
+    let InnerFrame::Headless(opened) = parse_inner(method, inner).unwrap() else {
+        panic!("expected headless frame");
+    };
+    assert_eq!(opened.serial, SERIAL);
+}
+
+#[test]
+fn test_seal_raw_aad_binds_extra_data() {
+    let auth_hash = derive_auth_hash(TOKEN);
+    let device_hash = derive_device_hash(SERIAL);
+    let seal = |extra: &[u8]| {
+        seal_raw_aad(
+            b"sensor-01",
+            EnvelopeMethod::Ping,
+            3,
+            auth_hash,
+            device_hash,
+            &KEY_16,
+            CipherSuite::Aes128Ccm,
+            extra,
+        )
+        .unwrap()
+    };
+
+    let bound = seal(b"conn-17");
+    let (_, _, plaintext) = open_envelope_aad(&bound, &KEY_16, b"conn-17").unwrap();
+    assert_eq!(plaintext, b"sensor-01");
+
+    let err = open_envelope_aad(&bound, &KEY_16, b"conn-18").unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::DecryptionFailed);
+    let err = open_envelope(&bound, &KEY_16).unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::DecryptionFailed);
+
+    // No extra AAD stays byte-compatible with the default path.
+    let plain = seal(b"");
+    let default = seal_raw(
+        b"sensor-01",
+        EnvelopeMethod::Ping,
+        3,
+        auth_hash,
+        device_hash,
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    assert_eq!(plain, default);
+    assert!(open_envelope(&plain, &KEY_16).is_ok());
+}
+
+#[test]
+fn test_open_envelope_with_key_lookup() {
+    let frame = make_push_frame();
+    let envelope = seal_uplink(
+        Method::Push,
+        &frame,
+        8,
+        derive_auth_hash(TOKEN),
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    )
+    .unwrap();
+    let known = derive_device_hash(SERIAL);
+    let lookup = |header: &EnvelopeHeader| (header.device_hash == known).then_some(&KEY_16[..]);
+
+    let (header, method, _) = open_envelope_with(&envelope, lookup).unwrap();
+    assert_eq!(header.counter, 8);
+    assert_eq!(method, EnvelopeMethod::Push);
+
+    let other = derive_device_hash("sensor-02");
+    let err = open_envelope_with(&envelope, |header: &EnvelopeHeader| {
+        (header.device_hash == other).then_some(&KEY_16[..])
+    })
+    .unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::KeyNotFound);
+}
+
+#[test]
+fn test_seal_raw_mtu_rejects_oversized_envelope() {
+    let seal = |inner: &[u8]| {
+        seal_raw_mtu(
+            inner,
+            EnvelopeMethod::Push,
+            1,
+            derive_auth_hash(TOKEN),
+            derive_device_hash(SERIAL),
+            &KEY_16,
+            CipherSuite::Aes128Ccm,
+            64,
+        )
+    };
+
+    let small = seal(b"sensor-01|[temp:=32]").unwrap();
+    assert!(small.len() <= 64);
+
+    // 21-byte header + 8-byte CCM tag leaves 35 bytes for the inner frame.
+    assert_eq!(seal(&[b'a'; 35]).unwrap().len(), 64);
+    let err = seal(&[b'a'; 36]).unwrap_err();
+    assert_eq!(err.kind, CryptoErrorKind::EnvelopeTooLarge);
+}
+
+#[test]
+fn test_seal_batch_too_few_counters() {
+    let frames: [(EnvelopeMethod, &[u8]); 2] =
+        [(EnvelopeMethod::Ping, b"a"), (EnvelopeMethod::Ping, b"b")];
+    let result = seal_batch(
+        &frames,
+        &[1],
+        [0; 8],
+        [0; 8],
+        &KEY_16,
+        CipherSuite::Aes128Ccm,
+    );
+    assert_eq!(result.unwrap_err().kind, CryptoErrorKind::BufferTooSmall);
+}
+
 // ---------------------------------------------------------------------------
 // AES-128-GCM
 // ---------------------------------------------------------------------------
@@ -363,6 +758,8 @@ fn test_envelope_overhead_ccm() {
     // PING inner frame = "sensor-01" = 9 bytes
     // Envelope = 21 (header) + 9 (ciphertext) + 8 (CCM tag) = 38 bytes
     assert_eq!(envelope.len(), 21 + 9 + 8);
+    assert_eq!(envelope_overhead(CipherSuite::Aes128Ccm), 21 + 8);
+    assert_eq!(envelope.len(), envelope_len(9, CipherSuite::Aes128Ccm));
 }
 
 #[cfg(feature = "aes-128-gcm")]
@@ -383,4 +780,6 @@ fn test_envelope_overhead_gcm() {
     // PING inner frame = "sensor-01" = 9 bytes
     // Envelope = 21 (header) + 9 (ciphertext) + 16 (GCM tag) = 46 bytes
     assert_eq!(envelope.len(), 21 + 9 + 16);
+    assert_eq!(envelope_overhead(CipherSuite::Aes128Gcm), 21 + 16);
+    assert_eq!(envelope.len(), envelope_len(9, CipherSuite::Aes128Gcm));
 }
@@ -15,11 +15,13 @@ use tagotip_codec::inline_vec::InlineVec;
 ///   ASCII:  sensor-01|[temp:=32]
 ///   Hex:    73 65 6e 73 6f 72 2d 30 31 7c 5b 74 65 6d 70 3a 3d 33 32 5d
 use tagotip_codec::types::{
-    HeadlessFrame, Method, Operator, PushBody, StructuredBody, Value, Variable,
+    HeadlessFrame, Method, Operator, PushBody, Serial, StructuredBody, Value, Variable,
 };
 use tagotip_secure::{
-    CipherSuite, bytes_to_hex, derive_auth_hash, derive_device_hash, derive_key, hex_to_bytes,
-    is_envelope, open_envelope, parse_envelope_header, seal_uplink,
+    AUTH_HASH_SIZE, COUNTER_SIZE, CipherSuite, DEVICE_HASH_SIZE, HEADER_SIZE, bytes_to_hex,
+    derive_auth_hash, derive_device_hash, derive_device_hash_for, derive_identity,
+    derive_identity_for, derive_key, hex_to_bytes, inspect_envelope, is_envelope, open_envelope,
+    parse_envelope_header, seal_uplink, seal_uplink_versioned, verify_auth_hash,
 };
 
 const TOKEN: &str = "ate2bd319014b24e0a8aca9f00aea4c0d0";
@@ -66,18 +68,79 @@ const EXPECTED_ENVELOPE: [u8; 49] = [
 
 const EXPECTED_INNER_FRAME: &[u8] = b"sensor-01|[temp:=32]";
 
+// Version 1 of the same vector. Only the flags byte (0x08) and the nonce
+// layout change: `[Flags:1] [DeviceHash[..n-5]] [Counter:4]`, so CCM's
+// 13-byte nonce carries the full 8-byte device hash and no padding.
+const EXPECTED_V1_FLAGS: u8 = 0x08;
+
+const EXPECTED_V1_NONCE: [u8; 13] = [
+    0x08, 0xab, 0x77, 0x88, 0xd2, 0x2e, 0xb7, 0x37, 0x2f, 0x00, 0x00, 0x00, 0x2a,
+];
+
+#[rustfmt::skip]
+const EXPECTED_V1_ENVELOPE: [u8; 49] = [
+  0x08, 0x00, 0x00, 0x00, 0x2a, 0x4d, 0xee, 0xdd, 0x7b, 0xab, 0x88, 0x17, 0xec, 0xab, 0x77, 0x88,
+  0xd2, 0x2e, 0xb7, 0x37, 0x2f, 0x5c, 0x16, 0xab, 0xd0, 0x90, 0x43, 0xc6, 0x9a, 0xb3, 0x6a, 0x47,
+  0x20, 0x45, 0xec, 0xb0, 0x20, 0xfb, 0x29, 0x8f, 0x2c, 0x83, 0x37, 0x20, 0x55, 0xeb, 0x92, 0x88,
+  0x62,
+];
+
 #[test]
 fn test_auth_hash_derivation() {
     let hash = derive_auth_hash(TOKEN);
     assert_eq!(hash, EXPECTED_AUTH_HASH);
 }
 
+#[test]
+fn test_header_layout_constants() {
+    assert_eq!(HEADER_SIZE, 21);
+    assert_eq!(EXPECTED_AAD.len(), HEADER_SIZE);
+    let auth_start = 1 + COUNTER_SIZE;
+    let device_start = auth_start + AUTH_HASH_SIZE;
+    assert_eq!(
+        EXPECTED_ENVELOPE[auth_start..device_start],
+        EXPECTED_AUTH_HASH
+    );
+    assert_eq!(
+        EXPECTED_ENVELOPE[device_start..device_start + DEVICE_HASH_SIZE],
+        EXPECTED_DEVICE_HASH
+    );
+    assert_eq!(device_start + DEVICE_HASH_SIZE, HEADER_SIZE);
+}
+
+#[test]
+fn test_verify_auth_hash() {
+    let header = parse_envelope_header(&EXPECTED_ENVELOPE).unwrap();
+    assert!(verify_auth_hash(TOKEN, &header));
+    assert!(!verify_auth_hash(
+        "ate2bd319014b24e0a8aca9f00aea4c0d1",
+        &header
+    ));
+}
+
 #[test]
 fn test_device_hash_derivation() {
     let hash = derive_device_hash(SERIAL);
     assert_eq!(hash, EXPECTED_DEVICE_HASH);
 }
 
+#[test]
+fn test_identity_derivation() {
+    let (auth_hash, device_hash) = derive_identity(TOKEN, SERIAL);
+    assert_eq!(auth_hash, EXPECTED_AUTH_HASH);
+    assert_eq!(device_hash, EXPECTED_DEVICE_HASH);
+}
+
+#[test]
+fn test_identity_derivation_from_serial() {
+    let serial = Serial::parse(SERIAL).unwrap();
+    assert_eq!(derive_device_hash_for(serial), EXPECTED_DEVICE_HASH);
+    assert_eq!(
+        derive_identity_for(TOKEN, serial),
+        (EXPECTED_AUTH_HASH, EXPECTED_DEVICE_HASH)
+    );
+}
+
 #[test]
 fn test_nonce_construction() {
     let nonce = tagotip_secure::nonce::construct_nonce(
@@ -121,6 +184,7 @@ fn test_inner_frame_bytes() {
             body_meta: None,
             variables,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     };
@@ -153,6 +217,7 @@ fn test_seal_produces_spec_envelope() {
             body_meta: None,
             variables,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     };
@@ -212,6 +277,16 @@ fn test_parse_header_spec_envelope() {
     assert_eq!(header.device_hash, EXPECTED_DEVICE_HASH);
 }
 
+#[test]
+fn test_inspect_spec_envelope() {
+    let (header, suite, method) = inspect_envelope(&EXPECTED_ENVELOPE).unwrap();
+    assert_eq!(header, parse_envelope_header(&EXPECTED_ENVELOPE).unwrap());
+    assert_eq!(suite, CipherSuite::Aes128Ccm);
+    assert_eq!(method, tagotip_secure::EnvelopeMethod::Push);
+
+    assert!(inspect_envelope(&EXPECTED_ENVELOPE[..10]).is_err());
+}
+
 #[test]
 fn test_is_envelope_spec() {
     assert!(is_envelope(&EXPECTED_ENVELOPE));
@@ -249,6 +324,7 @@ fn test_seal_open_with_derived_key() {
             body_meta: None,
             variables,
             meta_pool: InlineVec::new(),
+            unknown_modifiers: InlineVec::new(),
         })),
         pull_body: None,
     };
@@ -294,3 +370,41 @@ fn test_round_trip_with_parse_headless() {
         panic!("expected structured push body");
     }
 }
+
+#[test]
+fn test_nonce_v1_spec_vector() {
+    let nonce = tagotip_secure::nonce::construct_nonce_v1(
+        CipherSuite::Aes128Ccm,
+        EXPECTED_V1_FLAGS,
+        &EXPECTED_DEVICE_HASH,
+        COUNTER,
+    );
+    assert_eq!(nonce.as_slice(), &EXPECTED_V1_NONCE);
+}
+
+#[test]
+fn test_seal_v1_spec_envelope() {
+    let inner = core::str::from_utf8(EXPECTED_INNER_FRAME).unwrap();
+    let frame = tagotip_codec::parse::parse_headless(Method::Push, inner).unwrap();
+    let envelope = seal_uplink_versioned(
+        Method::Push,
+        &frame,
+        COUNTER,
+        EXPECTED_AUTH_HASH,
+        &ENCRYPTION_KEY,
+        CipherSuite::Aes128Ccm,
+        1,
+    )
+    .unwrap();
+    assert_eq!(envelope.as_slice(), &EXPECTED_V1_ENVELOPE);
+}
+
+#[test]
+fn test_open_v1_spec_envelope() {
+    let (header, method, plaintext) =
+        open_envelope(&EXPECTED_V1_ENVELOPE, &ENCRYPTION_KEY).unwrap();
+    assert_eq!(header.flags, EXPECTED_V1_FLAGS);
+    assert_eq!(header.counter, COUNTER);
+    assert_eq!(method, tagotip_secure::EnvelopeMethod::Push);
+    assert_eq!(plaintext, EXPECTED_INNER_FRAME);
+}